@@ -6,19 +6,41 @@ struct CameraUniform {
     fog_end: f32,
     // x,y храним sky.r sky.g для совпадения цвета тумана и неба
     sky_rg: vec2<f32>,
+    // x — сдвиг текстуры воды в тайлах, y — непрозрачность воды.
+    water: vec4<f32>,
 };
 @group(1) @binding(0) // 1.
 var<uniform> camera: CameraUniform;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
+    // Координаты внутри грани в блоках, тайл повторяется каждую единицу.
     @location(1) tex_coords: vec2<f32>,
+    @location(2) tile: vec2<f32>,
+    @location(3) ao: f32,
+    @location(4) face_shade: f32,
+    // Свет неба и блоков, 0..1.
+    @location(5) light: vec2<f32>,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
     @location(1) view_dist: f32,
+    @location(2) @interpolate(flat) tile: vec2<f32>,
+    // Яркость вершины: AO угла, умноженный на яркость грани.
+    @location(3) shade: f32,
+    @location(4) light: vec2<f32>,
+}
+
+// Тайлов по стороне атласа.
+const ATLAS_TILES: f32 = 16.0;
+// Тёплый оттенок света блоков (лава).
+const BLOCK_LIGHT_TINT: vec3<f32> = vec3<f32>(1.0, 0.85, 0.6);
+
+// Уровень 0..1 в яркость: каждый шаг из 15 темнее на 20%.
+fn light_curve(level: f32) -> f32 {
+    return pow(0.8, (1.0 - level) * 15.0);
 }
 
 @vertex
@@ -27,6 +49,9 @@ fn vs_main(
 ) -> VertexOutput {
     var out: VertexOutput;
     out.tex_coords = vertex.tex_coords;
+    out.tile = vertex.tile;
+    out.shade = vertex.ao * vertex.face_shade;
+    out.light = vertex.light;
     out.clip_position = camera.view_proj * vec4<f32>(vertex.position, 1.0);
     out.view_dist = distance(vertex.position, camera.camera_pos.xyz);
     return out;
@@ -38,10 +63,18 @@ var t_diffuse: texture_2d<f32>;
 @group(0)@binding(1)
 var s_diffuse: sampler;
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let base_color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
-    // World-space distance based fog.
+// Цвет текселя с затенением и светом вершины.
+fn lit_texel(in: VertexOutput, tex_coords: vec2<f32>) -> vec4<f32> {
+    // Полтекселя отступа от края тайла, чтобы не захватить соседний.
+    let half_texel = 0.5 * ATLAS_TILES / vec2<f32>(textureDimensions(t_diffuse));
+    let local = clamp(fract(tex_coords), half_texel, vec2<f32>(1.0) - half_texel);
+    let texel = textureSample(t_diffuse, s_diffuse, in.tile + local / ATLAS_TILES);
+    let light = max(vec3<f32>(light_curve(in.light.x)), light_curve(in.light.y) * BLOCK_LIGHT_TINT);
+    return vec4<f32>(texel.rgb * in.shade * light, texel.a);
+}
+
+// World-space distance based fog.
+fn apply_fog(in: VertexOutput, color: vec3<f32>) -> vec3<f32> {
     let fog_factor = clamp(
         (in.view_dist - camera.fog_start) / (camera.fog_end - camera.fog_start),
         0.0,
@@ -49,6 +82,19 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     );
     // Цвет тумана совпадает с цветом неба/clear (см. renderer), чтобы шов не выделялся.
     let fog_color = vec3<f32>(camera.sky_rg.x, camera.sky_rg.y, camera.camera_pos.w);
-    return vec4<f32>(mix(base_color.rgb, fog_color, fog_factor), base_color.a);
+    return mix(color, fog_color, fog_factor);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = lit_texel(in, in.tex_coords);
+    return vec4<f32>(apply_fog(in, color.rgb), color.a);
+}
+
+// Жидкости: тайл медленно плывёт по диагонали, альфа из настроек.
+@fragment
+fn fs_fluid(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scroll = camera.water.x * vec2<f32>(1.0, 0.5);
+    let color = lit_texel(in, in.tex_coords + scroll);
+    return vec4<f32>(apply_fog(in, color.rgb), camera.water.y);
 }
- 
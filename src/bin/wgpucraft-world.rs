@@ -0,0 +1,7 @@
+use std::process::ExitCode;
+
+use wgpucraft::world_tool::run;
+
+fn main() -> ExitCode {
+    run()
+}
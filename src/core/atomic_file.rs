@@ -0,0 +1,47 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Временный файл рядом с целевым: `<name>.tmp`.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Записывает файл целиком через временный файл и переименование, чтобы
+/// при сбое на диске остался либо старый, либо новый вариант.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = temp_path(path);
+    let mut file = File::create(&tmp)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_dir::TestDir;
+
+    #[test]
+    fn replaces_file_without_leftovers() {
+        let dir = TestDir::new("atomic");
+        let path = dir.join("nested").join("level.ron");
+        write_atomic(&path, b"old").unwrap();
+        write_atomic(&path, b"new").unwrap();
+        let data = fs::read(&path).unwrap();
+        let leftover = temp_path(&path).exists();
+
+        assert_eq!(data, b"new");
+        assert!(!leftover);
+    }
+}
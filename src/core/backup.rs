@@ -0,0 +1,308 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+};
+
+use anyhow::{Context, Result, bail};
+
+use super::{
+    level::{SAVES_DIR, unix_now},
+    saves::{copy_dir, copy_dir_with},
+};
+
+/// Каталог резервных копий: `backups/<world>/<время Unix>/`.
+pub const BACKUPS_DIR: &str = "backups";
+/// Копии удалённых миров: `backups/.deleted/<world>.<время Unix>/`. Точки
+/// в имени мира не бывает, так что с копиями живых миров они не смешаются.
+pub const DELETED_BACKUPS_DIR: &str = ".deleted";
+
+/// Одна резервная копия мира — полный снимок его папки.
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    /// Время создания (секунды Unix).
+    pub created: u64,
+    /// Номер копии внутри одной секунды: папка `<время>-<номер>`.
+    sequence: u32,
+}
+
+/// Снимает копию открытого мира в фоновом потоке. `lock` — замок, под которым
+/// мир пишет регион-файлы: каждый файл копируется под ним, чтобы в копию
+/// не попал недописанный регион. Ничего не делает, если копии отключены
+/// (`keep == 0`) или мир ещё не создан.
+pub fn backup_in_background<T: Send + 'static>(world: &str, keep: usize, lock: Arc<Mutex<T>>) {
+    if keep == 0 || !Path::new(SAVES_DIR).join(world).is_dir() {
+        return;
+    }
+    let world = world.to_string();
+    thread::spawn(move || {
+        let backup = create_backup_locked(
+            Path::new(SAVES_DIR),
+            Path::new(BACKUPS_DIR),
+            &world,
+            keep,
+            &lock,
+        );
+        match backup {
+            Ok(path) => log::info!("World backup written to {}", path.display()),
+            Err(err) => log::warn!("World backup failed: {err:?}"),
+        }
+    });
+}
+
+/// Копии мира `world`, начиная с самой новой.
+pub fn list_backups(backups_root: &Path, world: &str) -> Vec<BackupInfo> {
+    let Ok(entries) = fs::read_dir(backups_root.join(world)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let (created, sequence) = match name.split_once('-') {
+                Some((created, sequence)) => (created.parse().ok()?, sequence.parse().ok()?),
+                None => (name.parse().ok()?, 0),
+            };
+            Some(BackupInfo {
+                path: entry.path(),
+                created,
+                sequence,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse((b.created, b.sequence)));
+    backups
+}
+
+/// Снимок `saves_root/<world>` в новую папку копий; старше `keep` последних удаляются.
+/// Мир не должен быть открыт: для открытого есть [`backup_in_background`].
+pub fn create_backup(
+    saves_root: &Path,
+    backups_root: &Path,
+    world: &str,
+    keep: usize,
+) -> Result<PathBuf> {
+    snapshot(saves_root, backups_root, world, keep, &|from, to| {
+        fs::copy(from, to)
+    })
+}
+
+/// [`create_backup`] для открытого мира: каждый файл копируется под `lock`.
+pub fn create_backup_locked<T>(
+    saves_root: &Path,
+    backups_root: &Path,
+    world: &str,
+    keep: usize,
+    lock: &Mutex<T>,
+) -> Result<PathBuf> {
+    snapshot(saves_root, backups_root, world, keep, &|from, to| {
+        let _guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+        fs::copy(from, to)
+    })
+}
+
+fn snapshot(
+    saves_root: &Path,
+    backups_root: &Path,
+    world: &str,
+    keep: usize,
+    copy_file: &dyn Fn(&Path, &Path) -> io::Result<u64>,
+) -> Result<PathBuf> {
+    let source = saves_root.join(world);
+    if !source.is_dir() {
+        bail!("world {world:?} does not exist");
+    }
+    let dir = backups_root.join(world);
+    let now = unix_now();
+    // Номер всегда больше уже занятых, иначе копия встала бы перед старыми.
+    let sequence = list_backups(backups_root, world)
+        .iter()
+        .filter(|b| b.created == now)
+        .map(|b| b.sequence + 1)
+        .max()
+        .unwrap_or(0);
+    let target = if sequence == 0 {
+        dir.join(now.to_string())
+    } else {
+        dir.join(format!("{now}-{sequence}"))
+    };
+    // Пишем во временную папку, чтобы прерванная копия не выглядела готовой.
+    let partial = target.with_extension("partial");
+    let _ = fs::remove_dir_all(&partial);
+    copy_dir_with(&source, &partial, copy_file)?;
+    fs::rename(&partial, &target)?;
+    prune_backups(backups_root, world, keep)?;
+    Ok(target)
+}
+
+/// Оставляет `keep` самых новых копий мира.
+pub fn prune_backups(backups_root: &Path, world: &str, keep: usize) -> Result<()> {
+    for old in list_backups(backups_root, world).into_iter().skip(keep) {
+        fs::remove_dir_all(&old.path)
+            .with_context(|| format!("failed to remove {}", old.path.display()))?;
+    }
+    Ok(())
+}
+
+/// Переносит копии мира вслед за его переименованием. Чужие копии
+/// под новым именем не трогаются: тогда это ошибка.
+pub fn rename_backups(backups_root: &Path, from: &str, to: &str) -> Result<()> {
+    let source = backups_root.join(from);
+    if !source.is_dir() {
+        return Ok(());
+    }
+    let target = backups_root.join(to);
+    if target.exists() {
+        bail!("backups for {to:?} already exist");
+    }
+    fs::rename(&source, &target)
+        .with_context(|| format!("failed to rename {} to {to}", source.display()))
+}
+
+/// Убирает копии удалённого мира в `backups/.deleted/`: они остаются
+/// единственным способом вернуть мир, поэтому удаляет их только сам игрок.
+/// Возвращает новую папку копий, если они были.
+pub fn trash_backups(backups_root: &Path, world: &str) -> Result<Option<PathBuf>> {
+    let source = backups_root.join(world);
+    if !source.is_dir() {
+        return Ok(None);
+    }
+    let trash = backups_root.join(DELETED_BACKUPS_DIR);
+    fs::create_dir_all(&trash)?;
+    let now = unix_now();
+    let target = (0..)
+        .map(|n| match n {
+            0 => trash.join(format!("{world}.{now}")),
+            n => trash.join(format!("{world}.{now}-{n}")),
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap();
+    fs::rename(&source, &target).with_context(|| {
+        format!(
+            "failed to move {} to {}",
+            source.display(),
+            target.display()
+        )
+    })?;
+    Ok(Some(target))
+}
+
+/// Заменяет папку мира содержимым копии. Текущее состояние мира перед этим
+/// само сохраняется в копию, так что восстановление можно откатить.
+pub fn restore_backup(
+    saves_root: &Path,
+    backups_root: &Path,
+    world: &str,
+    backup: &Path,
+    keep: usize,
+) -> Result<()> {
+    if !backup.is_dir() {
+        bail!("backup {} does not exist", backup.display());
+    }
+    let target = saves_root.join(world);
+    let staged = saves_root.join(format!("{world}.restore"));
+    let _ = fs::remove_dir_all(&staged);
+    copy_dir(backup, &staged)?;
+    if target.exists() {
+        // Снимок текущего состояния может вытеснить старейшую копию, поэтому
+        // выбранная копия уже скопирована выше.
+        create_backup(saves_root, backups_root, world, keep.max(1))?;
+        fs::remove_dir_all(&target)
+            .with_context(|| format!("failed to remove {}", target.display()))?;
+    }
+    fs::rename(&staged, &target)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_dir::TestDir;
+
+    #[test]
+    fn backups_rotate_and_restore() {
+        let root = TestDir::new("backup");
+        let saves = root.join("saves");
+        let backups = root.join("backups");
+        let level = saves.join("w").join("level.ron");
+        fs::create_dir_all(level.parent().unwrap()).unwrap();
+
+        fs::write(&level, "first").unwrap();
+        let first = create_backup(&saves, &backups, "w", 2).unwrap();
+        fs::write(&level, "second").unwrap();
+        create_backup(&saves, &backups, "w", 2).unwrap();
+        fs::write(&level, "third").unwrap();
+        create_backup(&saves, &backups, "w", 2).unwrap();
+        let rotated = list_backups(&backups, "w");
+        let first_gone = !first.exists();
+
+        fs::write(&level, "broken").unwrap();
+        restore_backup(&saves, &backups, "w", &rotated[1].path, 2).unwrap();
+        let restored = fs::read_to_string(&level).unwrap();
+        let newest = list_backups(&backups, "w")[0].path.join("level.ron");
+        let kept_broken = fs::read_to_string(newest).unwrap();
+
+        assert_eq!(rotated.len(), 2);
+        assert!(first_gone);
+        assert_eq!(restored, "second");
+        assert_eq!(kept_broken, "broken");
+    }
+
+    #[test]
+    fn locked_backup_waits_for_region_writes() {
+        let root = TestDir::new("backup_locked");
+        let saves = root.join("saves");
+        let backups = root.join("backups");
+        let region = saves.join("w").join("region").join("r.0.0.region");
+        fs::create_dir_all(region.parent().unwrap()).unwrap();
+        fs::write(&region, "torn").unwrap();
+
+        // Пока идёт запись региона, копия ждёт замок и видит уже целый файл.
+        let lock = Arc::new(Mutex::new(()));
+        let guard = lock.lock().unwrap();
+        let backup = {
+            let (saves, backups, lock) = (saves.clone(), backups.clone(), lock.clone());
+            thread::spawn(move || create_backup_locked(&saves, &backups, "w", 2, &lock))
+        };
+        thread::sleep(std::time::Duration::from_millis(50));
+        fs::write(&region, "whole").unwrap();
+        drop(guard);
+        let path = backup.join().unwrap().unwrap();
+
+        let copied = fs::read_to_string(path.join("region").join("r.0.0.region")).unwrap();
+        assert_eq!(copied, "whole");
+    }
+
+    #[test]
+    fn backups_follow_rename_and_delete() {
+        let root = TestDir::new("backup_move");
+        let saves = root.join("saves");
+        let backups = root.join("backups");
+        fs::create_dir_all(saves.join("old")).unwrap();
+        fs::create_dir_all(backups.join("taken")).unwrap();
+        create_backup(&saves, &backups, "old", 2).unwrap();
+
+        let clash = rename_backups(&backups, "old", "taken").is_err();
+        rename_backups(&backups, "old", "new").unwrap();
+        let moved = (
+            list_backups(&backups, "old").len(),
+            list_backups(&backups, "new").len(),
+        );
+        let trashed = trash_backups(&backups, "new").unwrap().unwrap();
+        let kept = list_backups(
+            trashed.parent().unwrap(),
+            trashed.file_name().unwrap().to_str().unwrap(),
+        );
+        let nothing_left = trash_backups(&backups, "new").unwrap();
+
+        assert!(clash);
+        assert_eq!(moved, (0, 1));
+        assert!(!backups.join("new").exists());
+        assert!(trashed.starts_with(backups.join(DELETED_BACKUPS_DIR)));
+        assert_eq!(kept.len(), 1);
+        assert!(nothing_left.is_none());
+    }
+}
@@ -4,9 +4,11 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use wgpu;
 
+use super::atomic_file::write_atomic;
+
 /// Основная конфигурация, загружаемая из `config.json`.
 /// Поля упрощены и покрывают нужные настройки ввода, цикла и размера мира;
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct AppConfig {
     pub input: InputConfig,
@@ -41,6 +43,25 @@ pub struct GraphicsConfig {
     pub fov_y_degrees: f32,
     /// Цвет неба и тумана (RGB, 0.0..1.0).
     pub sky_color: [f32; 3],
+    /// Затенение углов блоков соседями (AO); отключение ускоряет ремеш
+    /// и даёт более крупные склеенные грани.
+    pub ambient_occlusion: bool,
+    /// Сила AO: 0.0 — без затенения, 1.0 — полностью закрытый угол чёрный.
+    pub ao_strength: f32,
+    /// Разная яркость граней по направлению (верх светлее, низ темнее).
+    pub face_shading: bool,
+    /// Свет неба и светящихся блоков (тёмные пещеры). Отключение убирает
+    /// расчёт света на воркерах, всё освещено как под открытым небом.
+    pub lighting: bool,
+    /// Сглаживать свет по вершинам (среднее четырёх клеток у угла) вместо
+    /// одного значения на грань.
+    pub smooth_lighting: bool,
+    /// Непрозрачность воды: 0.0 — невидима, 1.0 — глухая.
+    pub water_opacity: f32,
+    /// Скорость течения текстуры воды в тайлах в секунду; 0.0 — без анимации.
+    pub water_animation_speed: f32,
+    /// Короткий синий туман, когда камера под водой.
+    pub underwater_fog: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +83,35 @@ pub struct WorldConfig {
     pub seed: u32,
     /// Имя мира (папка сохранения в каталоге saves/).
     pub world_name: String,
+    /// Пресет генерации для новых миров (`assets/worldgen/<preset>.ron`).
+    pub preset: String,
+    /// Тип новых миров; уже созданный мир хранит свой тип в сохранении.
+    pub world_type: WorldType,
+    /// Режим игры в новых мирах; открытый мир берёт режим из своего сохранения.
+    pub game_mode: PlayerMode,
+    /// Период автосохранения в секундах; `0` отключает автосохранение.
+    pub autosave_interval_secs: u32,
+    /// Период резервного копирования мира в минутах; `0` — только при открытии.
+    pub backup_interval_minutes: u32,
+    /// Сколько последних копий мира хранить; `0` отключает резервные копии.
+    pub backups_to_keep: usize,
+}
+
+/// Слои плоского мира по умолчанию, снизу вверх.
+pub const DEFAULT_SUPERFLAT_LAYERS: &str = "1*rock,3*dirt,1*grass";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WorldType {
+    /// Обычный мир по пресету генерации.
+    #[default]
+    Default,
+    /// Плоский мир из слоёв вида `"1*rock,3*dirt,1*grass"` (снизу вверх).
+    Superflat { layers: String },
+    /// Пустой мир с маленькой площадкой у точки появления.
+    Void,
+    /// Все материалы, разложенные сеткой на каменном полу.
+    DebugGrid,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,25 +163,10 @@ pub struct TerrainTuningConfig {
     pub min_vertex_cap: usize,
     /// Минимальный стартовый размер буфера индексов для чанка.
     pub min_index_cap: usize,
-    /// Уровень воды (y), ниже которого генерируется вода при пустоте.
+    /// Уровень воды (y) для новых миров, если пресет его не задаёт.
     pub land_level: usize,
 }
 
-impl Default for AppConfig {
-    fn default() -> Self {
-        Self {
-            input: InputConfig::default(),
-            graphics: GraphicsConfig::default(),
-            world: WorldConfig::default(),
-            multiplayer: MultiplayerConfig::default(),
-            debug: DebugConfig::default(),
-            player: PlayerConfig::default(),
-            terrain: TerrainTuningConfig::default(),
-            ui: UiConfig::default(),
-        }
-    }
-}
-
 impl Default for InputConfig {
     fn default() -> Self {
         Self {
@@ -151,6 +186,14 @@ impl Default for GraphicsConfig {
             render_distance_chunks: 32,
             fov_y_degrees: 60.0,
             sky_color: [0.60, 0.75, 0.90],
+            ambient_occlusion: true,
+            ao_strength: 0.6,
+            face_shading: true,
+            lighting: true,
+            smooth_lighting: true,
+            water_opacity: 0.7,
+            water_animation_speed: 0.25,
+            underwater_fog: true,
         }
     }
 }
@@ -171,6 +214,12 @@ impl Default for WorldConfig {
         Self {
             seed: 10,
             world_name: "default".to_string(),
+            preset: "default".to_string(),
+            world_type: WorldType::Default,
+            game_mode: PlayerMode::Adventure,
+            autosave_interval_secs: 60,
+            backup_interval_minutes: 30,
+            backups_to_keep: 5,
         }
     }
 }
@@ -241,7 +290,7 @@ impl AppConfig {
 
     pub fn write_to(&self, path: impl AsRef<Path>) -> Result<()> {
         let serialized = serde_json::to_string_pretty(self)?;
-        fs::write(&path, serialized)
+        write_atomic(path.as_ref(), serialized.as_bytes())
             .with_context(|| format!("failed to write config to {}", path.as_ref().display()))
     }
 
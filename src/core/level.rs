@@ -0,0 +1,196 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    atomic_file::write_atomic,
+    config::{AppConfig, PlayerMode, WorldType},
+};
+
+/// Каталог со всеми мирами.
+pub const SAVES_DIR: &str = "saves";
+/// Метаданные мира внутри `saves/<world>/`.
+pub const LEVEL_FILE: &str = "level.ron";
+pub const LEVEL_VERSION: u32 = 1;
+/// Точка появления (позиция камеры) для новых миров.
+pub const DEFAULT_SPAWN: [f32; 3] = [8.0, 12.0, 8.0];
+
+/// Каталог сохранения мира по имени.
+pub fn world_dir(world_name: &str) -> PathBuf {
+    Path::new(SAVES_DIR).join(world_name)
+}
+
+/// Секунды с начала эпохи.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Сид из строки диалога создания мира: число, влезающее в `u32` или `i32`,
+/// берётся как есть, любой другой текст хешируется (FNV-1a).
+/// Пустая строка — `None`.
+pub fn seed_from_text(text: &str) -> Option<u32> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if let Ok(number) = text.parse::<u32>() {
+        return Some(number);
+    }
+    if let Ok(number) = text.parse::<i32>() {
+        return Some(number as u32);
+    }
+    let hash = text.bytes().fold(0x811c_9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    });
+    Some(hash)
+}
+
+/// Метаданные мира. При загрузке мира они важнее `config.json`:
+/// сид, пресет и тип мира берутся отсюда, а не из конфига.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelData {
+    pub version: u32,
+    pub seed: u32,
+    /// Имя пресета генерации; параметры лежат в `worldgen.ron` рядом.
+    pub preset: String,
+    #[serde(default)]
+    pub world_type: WorldType,
+    /// Позиция камеры при появлении.
+    pub spawn: [f32; 3],
+    pub game_mode: PlayerMode,
+    /// Время, проведённое в мире, в секундах.
+    pub world_time: f64,
+    /// Время создания и последнего входа (секунды Unix).
+    pub created: u64,
+    #[serde(default)]
+    pub last_played: u64,
+    /// Файл на диске не удалось прочитать — не перезаписываем его.
+    #[serde(skip)]
+    read_only: bool,
+}
+
+impl LevelData {
+    /// Метаданные нового мира из текущих настроек конфига.
+    pub fn from_config(config: &AppConfig) -> Self {
+        let now = unix_now();
+        Self {
+            version: LEVEL_VERSION,
+            seed: config.world.seed,
+            preset: config.world.preset.clone(),
+            world_type: config.world.world_type.clone(),
+            spawn: DEFAULT_SPAWN,
+            game_mode: config.world.game_mode.clone(),
+            world_time: 0.0,
+            created: now,
+            last_played: now,
+            read_only: false,
+        }
+    }
+
+    pub fn load(world_dir: &Path) -> Result<Self> {
+        let path = world_dir.join(LEVEL_FILE);
+        let raw = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let level: LevelData =
+            ron::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))?;
+        if level.version > LEVEL_VERSION {
+            bail!(
+                "world format {} is newer than supported {LEVEL_VERSION}",
+                level.version
+            );
+        }
+        Ok(level)
+    }
+
+    /// Записывает `level.ron`; для метаданных, взятых взамен нечитаемого файла, ничего не делает.
+    pub fn save(&self, world_dir: &Path) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        let path = world_dir.join(LEVEL_FILE);
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        write_atomic(&path, serialized.as_bytes())
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Открывает мир `config.world.world_name`: читает `level.ron` и переносит
+    /// его значения в конфиг. Если файла нет (новый мир или сохранение старой
+    /// версии), он создаётся из конфига.
+    pub fn open(config: &mut AppConfig) -> Self {
+        let dir = world_dir(&config.world.world_name);
+        let mut level = match Self::load(&dir) {
+            Ok(level) => level,
+            Err(_) if !dir.join(LEVEL_FILE).exists() => Self::from_config(config),
+            Err(err) => {
+                // Не затираем файл, который не смогли прочитать: мир работает
+                // на настройках конфига до исправления.
+                log::warn!("World metadata ignored: {err:?}");
+                Self {
+                    read_only: true,
+                    ..Self::from_config(config)
+                }
+            }
+        };
+        level.last_played = unix_now();
+        level.apply_to(config);
+        if let Err(err) = level.save(&dir) {
+            log::warn!("Failed to store world metadata: {err:?}");
+        }
+        level
+    }
+
+    /// Переносит параметры мира в конфиг, чтобы генерация совпадала с сохранённой.
+    pub fn apply_to(&self, config: &mut AppConfig) {
+        config.world.seed = self.seed;
+        config.world.preset = self.preset.clone();
+        config.world.world_type = self.world_type.clone();
+        config.player.mode = self.game_mode.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_dir::TestDir;
+
+    #[test]
+    fn level_overrides_config_after_round_trip() {
+        let dir = TestDir::new("level");
+        let mut config = AppConfig::default();
+        config.world.seed = 1234;
+        config.world.world_type = WorldType::Void;
+        let mut level = LevelData::from_config(&config);
+        level.spawn = [1.0, 70.0, -3.0];
+        level.save(&dir).unwrap();
+
+        let loaded = LevelData::load(&dir).unwrap();
+        let mut other = AppConfig::default();
+        loaded.apply_to(&mut other);
+        assert_eq!(other.world.seed, 1234);
+        assert_eq!(other.world.world_type, WorldType::Void);
+        assert_eq!(loaded.spawn, [1.0, 70.0, -3.0]);
+    }
+
+    #[test]
+    fn seeds_from_text() {
+        assert_eq!(seed_from_text("  "), None);
+        assert_eq!(seed_from_text("42"), Some(42));
+        assert_eq!(seed_from_text("-1"), Some(u32::MAX));
+        assert_eq!(seed_from_text("4294967295"), Some(u32::MAX));
+        assert_eq!(seed_from_text("-2147483648"), Some(0x8000_0000));
+        // Вне диапазона число не обрезается, а хешируется как текст (FNV-1a строки).
+        assert_eq!(seed_from_text("4294967296"), Some(0xa5d2_f78f));
+        assert_eq!(seed_from_text("-2147483649"), Some(0x9315_e8be));
+        assert_eq!(seed_from_text("a"), Some(0xe40c_292c));
+        assert_eq!(seed_from_text("Glacier"), seed_from_text(" Glacier "));
+        assert_ne!(seed_from_text("Glacier"), seed_from_text("glacier"));
+    }
+}
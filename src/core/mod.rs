@@ -1 +1,7 @@
+pub mod atomic_file;
+pub mod backup;
 pub mod config;
+pub mod level;
+pub mod saves;
+#[cfg(test)]
+pub mod test_dir;
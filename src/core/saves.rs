@@ -0,0 +1,249 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result, bail};
+
+use super::level::LevelData;
+
+/// Краткие сведения о сохранённом мире для экрана выбора.
+#[derive(Debug, Clone)]
+pub struct WorldSummary {
+    /// Имя папки мира.
+    pub name: String,
+    /// `None`, если `level.ron` отсутствует или не читается.
+    pub seed: Option<u32>,
+    pub last_played: Option<u64>,
+    /// Размер папки на диске, в байтах.
+    pub size_bytes: u64,
+}
+
+/// Все миры в `root`, начиная с последнего открытого.
+pub fn list_worlds(root: &Path) -> Vec<WorldSummary> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut worlds: Vec<WorldSummary> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let path = entry.path();
+            let level = LevelData::load(&path).ok();
+            Some(WorldSummary {
+                name,
+                seed: level.as_ref().map(|l| l.seed),
+                last_played: level.map(|l| l.last_played),
+                size_bytes: dir_size(&path),
+            })
+        })
+        .collect();
+    worlds.sort_by(|a, b| {
+        b.last_played
+            .cmp(&a.last_played)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    worlds
+}
+
+/// Имя мира годится для папки: буквы, цифры, пробел, `-` и `_`.
+pub fn is_valid_world_name(name: &str) -> bool {
+    let trimmed = name.trim();
+    !trimmed.is_empty()
+        && trimmed.len() == name.len()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+}
+
+pub fn delete_world(root: &Path, name: &str) -> Result<()> {
+    let dir = existing_world(root, name)?;
+    fs::remove_dir_all(&dir).with_context(|| format!("failed to delete {}", dir.display()))
+}
+
+pub fn rename_world(root: &Path, from: &str, to: &str) -> Result<()> {
+    let source = existing_world(root, from)?;
+    let target = free_world_dir(root, to)?;
+    fs::rename(&source, &target)
+        .with_context(|| format!("failed to rename {} to {to}", source.display()))
+}
+
+/// Копирует мир в папку `<name> copy` (`<name> copy 2`, ...) и возвращает её имя.
+pub fn duplicate_world(root: &Path, name: &str) -> Result<String> {
+    let source = existing_world(root, name)?;
+    let copy_name = (1..)
+        .map(|n| {
+            if n == 1 {
+                format!("{name} copy")
+            } else {
+                format!("{name} copy {n}")
+            }
+        })
+        .find(|candidate| !root.join(candidate).exists())
+        .unwrap();
+    copy_dir(&source, &root.join(&copy_name))?;
+    Ok(copy_name)
+}
+
+/// Открывает папку в файловом менеджере системы.
+pub fn open_folder(path: &Path) -> Result<()> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(program)
+        .arg(path)
+        .spawn()
+        .with_context(|| format!("failed to run {program}"))?;
+    Ok(())
+}
+
+/// `1536` -> `"1.5 KB"`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Секунды Unix -> `"YYYY-MM-DD HH:MM"` (UTC).
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Алгоритм civil_from_days (H. Hinnant).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        rem / 3600,
+        rem % 3600 / 60
+    )
+}
+
+fn existing_world(root: &Path, name: &str) -> Result<PathBuf> {
+    if !is_valid_world_name(name) {
+        bail!("invalid world name {name:?}");
+    }
+    let dir = root.join(name);
+    if !dir.is_dir() {
+        bail!("world {name:?} does not exist");
+    }
+    Ok(dir)
+}
+
+fn free_world_dir(root: &Path, name: &str) -> Result<PathBuf> {
+    if !is_valid_world_name(name) {
+        bail!("invalid world name {name:?}");
+    }
+    let dir = root.join(name);
+    if dir.exists() {
+        bail!("world {name:?} already exists");
+    }
+    Ok(dir)
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Рекурсивно копирует каталог; недописанные `.tmp` файлы пропускаются.
+pub(crate) fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    copy_dir_with(from, to, &|from, to| fs::copy(from, to))
+}
+
+/// [`copy_dir`], где каждый файл копирует `copy_file`.
+pub(crate) fn copy_dir_with(
+    from: &Path,
+    to: &Path,
+    copy_file: &dyn Fn(&Path, &Path) -> io::Result<u64>,
+) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.path().extension().is_some_and(|ext| ext == "tmp") {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            copy_dir_with(&entry.path(), &target, copy_file)?;
+        } else {
+            copy_file(&entry.path(), &target)
+                .with_context(|| format!("failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{config::AppConfig, test_dir::TestDir};
+
+    #[test]
+    fn worlds_are_listed_copied_renamed_and_deleted() {
+        let root = TestDir::new("saves");
+        let mut config = AppConfig::default();
+        config.world.seed = 77;
+        let mut level = LevelData::from_config(&config);
+        level.last_played = 100;
+        level.save(&root.join("alpha")).unwrap();
+        fs::create_dir_all(root.join("alpha").join("region")).unwrap();
+        fs::write(
+            root.join("alpha").join("region").join("r.0.0.region"),
+            [0u8; 2048],
+        )
+        .unwrap();
+
+        let copy = duplicate_world(&root, "alpha").unwrap();
+        rename_world(&root, "alpha", "beta").unwrap();
+        assert!(rename_world(&root, "beta", "../escape").is_err());
+        assert!(rename_world(&root, "beta", &copy).is_err());
+        let listed = list_worlds(&root);
+        delete_world(&root, &copy).unwrap();
+        let after_delete = list_worlds(&root).len();
+
+        let names: Vec<&str> = listed.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, ["alpha copy", "beta"]);
+        assert!(listed.iter().all(|w| w.seed == Some(77)));
+        assert!(listed[0].size_bytes > 2048);
+        assert_eq!(after_delete, 1);
+    }
+
+    #[test]
+    fn formats_sizes_and_dates() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(format_timestamp(1_760_791_500), "2025-10-18 12:45");
+    }
+}
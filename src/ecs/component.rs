@@ -22,6 +22,12 @@ pub struct ComponentStorage<T> {
     components: HashMap<Entity, T>,
 }
 
+impl<T: Component> Default for ComponentStorage<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Component> ComponentStorage<T> {
     pub fn new() -> Self {
         Self {
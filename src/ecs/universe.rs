@@ -12,6 +12,12 @@ pub struct World {
     components: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
 }
 
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl World {
     pub fn new() -> Self {
         Self {
@@ -6,6 +6,15 @@ pub enum IconType {
     GRASS,
     DIRT,
     WATER,
+    SAND,
+    SNOW,
+    LOG,
+    LEAVES,
+    STONE,
+    COAL,
+    IRON,
+    GOLD,
+    LAVA,
     DEBUG,
 }
 
@@ -18,6 +27,15 @@ impl IconType {
             IconType::GRASS,
             IconType::DIRT,
             IconType::WATER,
+            IconType::SAND,
+            IconType::SNOW,
+            IconType::LOG,
+            IconType::LEAVES,
+            IconType::STONE,
+            IconType::COAL,
+            IconType::IRON,
+            IconType::GOLD,
+            IconType::LAVA,
             IconType::DEBUG,
         ]
     }
@@ -28,6 +46,15 @@ impl IconType {
             IconType::GRASS => (1, 0),
             IconType::DIRT => (2, 0),
             IconType::WATER => (9, 0),
+            IconType::SAND => (11, 0),
+            IconType::SNOW => (10, 4),
+            IconType::LOG => (0, 1),
+            IconType::LEAVES => (7, 1),
+            IconType::STONE => (0, 0),
+            IconType::COAL => (15, 0),
+            IconType::IRON => (14, 0),
+            IconType::GOLD => (13, 0),
+            IconType::LAVA => (10, 0),
             IconType::DEBUG => (0, 7),
         };
 
@@ -46,7 +73,16 @@ impl IconType {
             IconType::ROCK => IconType::GRASS,
             IconType::GRASS => IconType::DIRT,
             IconType::DIRT => IconType::WATER,
-            IconType::WATER => IconType::DEBUG,
+            IconType::WATER => IconType::SAND,
+            IconType::SAND => IconType::SNOW,
+            IconType::SNOW => IconType::LOG,
+            IconType::LOG => IconType::LEAVES,
+            IconType::LEAVES => IconType::STONE,
+            IconType::STONE => IconType::COAL,
+            IconType::COAL => IconType::IRON,
+            IconType::IRON => IconType::GOLD,
+            IconType::GOLD => IconType::LAVA,
+            IconType::LAVA => IconType::DEBUG,
             IconType::DEBUG => IconType::ROCK, // циклический переход
         }
     }
@@ -54,7 +90,16 @@ impl IconType {
     pub fn prev(self) -> Self {
         match self {
             IconType::ROCK => IconType::DEBUG,
-            IconType::DEBUG => IconType::WATER,
+            IconType::DEBUG => IconType::LAVA,
+            IconType::LAVA => IconType::GOLD,
+            IconType::GOLD => IconType::IRON,
+            IconType::IRON => IconType::COAL,
+            IconType::COAL => IconType::STONE,
+            IconType::STONE => IconType::LEAVES,
+            IconType::LEAVES => IconType::LOG,
+            IconType::LOG => IconType::SNOW,
+            IconType::SNOW => IconType::SAND,
+            IconType::SAND => IconType::WATER,
             IconType::WATER => IconType::DIRT,
             IconType::DIRT => IconType::GRASS,
             IconType::GRASS => IconType::ROCK,
@@ -105,6 +150,15 @@ impl IconType {
             IconType::GRASS => crate::render::atlas::MaterialType::GRASS,
             IconType::DIRT => crate::render::atlas::MaterialType::DIRT,
             IconType::WATER => crate::render::atlas::MaterialType::WATER,
+            IconType::SAND => crate::render::atlas::MaterialType::SAND,
+            IconType::SNOW => crate::render::atlas::MaterialType::SNOW,
+            IconType::LOG => crate::render::atlas::MaterialType::LOG,
+            IconType::LEAVES => crate::render::atlas::MaterialType::LEAVES,
+            IconType::STONE => crate::render::atlas::MaterialType::STONE,
+            IconType::COAL => crate::render::atlas::MaterialType::COAL,
+            IconType::IRON => crate::render::atlas::MaterialType::IRON,
+            IconType::GOLD => crate::render::atlas::MaterialType::GOLD,
+            IconType::LAVA => crate::render::atlas::MaterialType::LAVA,
             IconType::DEBUG => crate::render::atlas::MaterialType::DEBUG,
         }
     }
@@ -115,6 +169,15 @@ impl IconType {
             crate::render::atlas::MaterialType::GRASS => Some(IconType::GRASS),
             crate::render::atlas::MaterialType::DIRT => Some(IconType::DIRT),
             crate::render::atlas::MaterialType::WATER => Some(IconType::WATER),
+            crate::render::atlas::MaterialType::SAND => Some(IconType::SAND),
+            crate::render::atlas::MaterialType::SNOW => Some(IconType::SNOW),
+            crate::render::atlas::MaterialType::LOG => Some(IconType::LOG),
+            crate::render::atlas::MaterialType::LEAVES => Some(IconType::LEAVES),
+            crate::render::atlas::MaterialType::STONE => Some(IconType::STONE),
+            crate::render::atlas::MaterialType::COAL => Some(IconType::COAL),
+            crate::render::atlas::MaterialType::IRON => Some(IconType::IRON),
+            crate::render::atlas::MaterialType::GOLD => Some(IconType::GOLD),
+            crate::render::atlas::MaterialType::LAVA => Some(IconType::LAVA),
             crate::render::atlas::MaterialType::DEBUG => Some(IconType::DEBUG),
             crate::render::atlas::MaterialType::AIR => None,
         }
@@ -1,16 +1,21 @@
-
 use crate::{
-    core::config::AppConfig,
+    core::{
+        config::{AppConfig, PlayerMode, WorldType},
+        saves::{format_size, format_timestamp},
+    },
+    terrain_gen::saver::SaveProgress,
     text::{TextStyle, TextSystem},
     ui::{
-        style::StyleSheet, BitmapFont, ButtonSpec, Layout as UiLayout, MeasureCtx, UiElement,
-        UiNode,
+        BitmapFont, ButtonSpec, Layout as UiLayout, MeasureCtx, TextInputSpec, UiElement, UiNode,
+        style::StyleSheet,
     },
 };
 use glam::Vec2;
 use icons_atlas::IconType;
 use std::cell::RefCell;
 use std::sync::Arc;
+use winit::keyboard::{Key, NamedKey};
+use worlds::WorldBrowser;
 
 use crate::render::{
     mesh::Mesh,
@@ -24,6 +29,7 @@ use crate::render::{
 };
 
 pub mod icons_atlas;
+pub mod worlds;
 
 // Menu quad height/width in clip space (used for layout mapping).
 const MENU_CLIP_SIZE: f32 = 1.4;
@@ -34,6 +40,9 @@ pub struct OverlayStats {
     pub frame_ms: f32,
     pub chunks_loaded: usize,
     pub draw_calls: usize,
+    /// Память под блоки загруженных чанков, в байтах.
+    pub block_memory: usize,
+    pub light_memory: usize,
 }
 
 pub struct HUD {
@@ -53,6 +62,13 @@ pub struct HUD {
     screen_size: [f32; 2],
     text_scale: f32,
     last_stats: OverlayStats,
+    save_progress: SaveProgress,
+    /// Повреждённые чанки, отложенные в карантин в этой сессии.
+    quarantined_chunks: usize,
+    /// Выход был отменён из-за несохранённых чанков.
+    quit_blocked: bool,
+    /// Список миров для страницы выбора мира.
+    pub world_browser: WorldBrowser,
 }
 
 struct HUDElement {
@@ -81,6 +97,12 @@ struct MenuOverlay {
     visible: bool,
     aspect_correction: f32,
     hovered: Option<MenuAction>,
+    /// Кнопка, отмеченная как выбранная (например, мир в списке).
+    selected: Option<MenuAction>,
+    /// Поле ввода, получающее набранный текст.
+    focused_input: Option<String>,
+    hovered_input: Option<String>,
+    page: Option<MenuPage>,
     font: Arc<BitmapFont>,
     styles: Arc<crate::ui::style::StyleSheet>,
     tree: Option<UiNode>,
@@ -91,8 +113,22 @@ struct MenuOverlay {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MenuAction {
     Resume,
+    OpenCreateWorld,
     CreateWorld,
+    CycleWorldType,
+    CycleGameMode,
     OpenWorld,
+    SelectWorld(usize),
+    NextWorldsPage,
+    OpenSelectedWorld,
+    DuplicateWorld,
+    OpenRenameWorld,
+    ConfirmRenameWorld,
+    OpenDeleteWorld,
+    ConfirmDeleteWorld,
+    OpenWorldFolder,
+    OpenBackups,
+    RestoreBackup(usize),
     SaveConfig,
     OpenSettings,
     OpenAdvanced,
@@ -111,11 +147,25 @@ pub enum MenuAction {
 }
 
 impl MenuAction {
-    fn key(&self) -> &'static str {
-        match self {
+    fn key(&self) -> String {
+        let key = match self {
             MenuAction::Resume => "resume",
+            MenuAction::OpenCreateWorld => "open_create_world",
             MenuAction::CreateWorld => "create_world",
+            MenuAction::CycleWorldType => "cycle_world_type",
+            MenuAction::CycleGameMode => "cycle_game_mode",
             MenuAction::OpenWorld => "open_world",
+            MenuAction::SelectWorld(slot) => return format!("select_world_{slot}"),
+            MenuAction::NextWorldsPage => "next_worlds_page",
+            MenuAction::OpenSelectedWorld => "open_selected_world",
+            MenuAction::DuplicateWorld => "duplicate_world",
+            MenuAction::OpenRenameWorld => "open_rename_world",
+            MenuAction::ConfirmRenameWorld => "confirm_rename_world",
+            MenuAction::OpenDeleteWorld => "open_delete_world",
+            MenuAction::ConfirmDeleteWorld => "confirm_delete_world",
+            MenuAction::OpenWorldFolder => "open_world_folder",
+            MenuAction::OpenBackups => "open_backups",
+            MenuAction::RestoreBackup(slot) => return format!("restore_backup_{slot}"),
             MenuAction::SaveConfig => "save_config",
             MenuAction::OpenSettings => "open_settings",
             MenuAction::OpenAdvanced => "open_advanced",
@@ -131,14 +181,27 @@ impl MenuAction {
             MenuAction::CycleMinIndexCap => "cycle_min_index_cap",
             MenuAction::CycleLandLevel => "cycle_land_level",
             MenuAction::Quit => "quit",
-        }
+        };
+        key.to_string()
     }
 
     fn from_key(key: &str) -> Option<Self> {
         match key {
             "resume" => Some(MenuAction::Resume),
+            "open_create_world" => Some(MenuAction::OpenCreateWorld),
             "create_world" => Some(MenuAction::CreateWorld),
+            "cycle_world_type" => Some(MenuAction::CycleWorldType),
+            "cycle_game_mode" => Some(MenuAction::CycleGameMode),
             "open_world" => Some(MenuAction::OpenWorld),
+            "next_worlds_page" => Some(MenuAction::NextWorldsPage),
+            "open_selected_world" => Some(MenuAction::OpenSelectedWorld),
+            "duplicate_world" => Some(MenuAction::DuplicateWorld),
+            "open_rename_world" => Some(MenuAction::OpenRenameWorld),
+            "confirm_rename_world" => Some(MenuAction::ConfirmRenameWorld),
+            "open_delete_world" => Some(MenuAction::OpenDeleteWorld),
+            "confirm_delete_world" => Some(MenuAction::ConfirmDeleteWorld),
+            "open_world_folder" => Some(MenuAction::OpenWorldFolder),
+            "open_backups" => Some(MenuAction::OpenBackups),
             "save_config" => Some(MenuAction::SaveConfig),
             "open_settings" => Some(MenuAction::OpenSettings),
             "open_advanced" => Some(MenuAction::OpenAdvanced),
@@ -154,7 +217,15 @@ impl MenuAction {
             "cycle_min_index_cap" => Some(MenuAction::CycleMinIndexCap),
             "cycle_land_level" => Some(MenuAction::CycleLandLevel),
             "quit" => Some(MenuAction::Quit),
-            _ => None,
+            _ => {
+                if let Some(slot) = key.strip_prefix("select_world_") {
+                    slot.parse().ok().map(MenuAction::SelectWorld)
+                } else if let Some(slot) = key.strip_prefix("restore_backup_") {
+                    slot.parse().ok().map(MenuAction::RestoreBackup)
+                } else {
+                    None
+                }
+            }
         }
     }
 }
@@ -162,8 +233,24 @@ impl MenuAction {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MenuPage {
     Main,
+    CreateWorld,
     Settings,
     Advanced,
+    Worlds,
+    RenameWorld,
+    DeleteWorld,
+    Backups,
+}
+
+impl MenuPage {
+    /// Действие, которое выполняет Enter на странице с полем ввода.
+    pub fn submit_action(self) -> Option<MenuAction> {
+        match self {
+            MenuPage::CreateWorld => Some(MenuAction::CreateWorld),
+            MenuPage::RenameWorld => Some(MenuAction::ConfirmRenameWorld),
+            _ => None,
+        }
+    }
 }
 
 impl HUD {
@@ -185,6 +272,7 @@ impl HUD {
         let font_handle = text_system
             .load_font(&ui_cfg.font_path)
             .expect("Failed to load text font");
+        #[allow(clippy::arc_with_non_send_sync)]
         let font = Arc::new(
             BitmapFont::load_from_path(&ui_cfg.font_path, ui_cfg.font_size, ui_cfg.font_weight_px)
                 .expect("Failed to load UI font from config"),
@@ -226,7 +314,7 @@ impl HUD {
         // Создаём конвейер HUD с глобальным layout
         let pipeline = create_hud_pipeline(
             &renderer.device,
-            &global_layout, // Используем глобальный layout
+            global_layout, // Используем глобальный layout
             shader,
             &renderer.config,
         );
@@ -350,7 +438,13 @@ impl HUD {
                 frame_ms: 0.0,
                 chunks_loaded: 0,
                 draw_calls: 0,
+                block_memory: 0,
+                light_memory: 0,
             },
+            save_progress: SaveProgress::default(),
+            quarantined_chunks: 0,
+            quit_blocked: false,
+            world_browser: WorldBrowser::default(),
         }
     }
 
@@ -398,6 +492,17 @@ impl HUD {
         }
     }
 
+    pub fn selected_slot(&self) -> usize {
+        self.selected_index
+    }
+
+    pub fn select_slot(&mut self, slot: usize, renderer: &Renderer) {
+        if slot < self.palette.len() {
+            self.selected_index = slot;
+            self.update(renderer);
+        }
+    }
+
     pub fn selected_icon(&self) -> IconType {
         self.palette[self.selected_index]
     }
@@ -409,6 +514,18 @@ impl HUD {
         self.last_stats = *stats;
     }
 
+    pub fn set_save_progress(&mut self, progress: SaveProgress) {
+        self.save_progress = progress;
+    }
+
+    pub fn set_quarantined_chunks(&mut self, count: usize) {
+        self.quarantined_chunks = count;
+    }
+
+    pub fn set_quit_blocked(&mut self, blocked: bool) {
+        self.quit_blocked = blocked;
+    }
+
     pub fn draw_call_count(&self) -> usize {
         let base = 3;
         if self.debug_overlay.is_some() {
@@ -458,7 +575,8 @@ impl HUD {
     }
 
     pub fn open_menu(&mut self, page: MenuPage, config: &AppConfig, queue: &wgpu::Queue) {
-        self.menu.load_page(page, config, queue);
+        self.menu
+            .load_page(page, config, &self.world_browser, queue);
         self.menu.visible = true;
     }
 
@@ -472,9 +590,22 @@ impl HUD {
         }
     }
 
-    pub fn click_menu(&self) -> Option<MenuAction> {
+    /// Передаёт нажатие клавиши полю ввода в фокусе. `true`, если оно поглощено.
+    pub fn menu_key_input(&mut self, event: &winit::event::KeyEvent, queue: &wgpu::Queue) -> bool {
+        if !self.menu.visible || event.state != winit::event::ElementState::Pressed {
+            return false;
+        }
+        self.menu.edit_focused_input(event, queue)
+    }
+
+    /// Текст поля ввода текущей страницы меню.
+    pub fn menu_input_text(&self, id: &str) -> Option<String> {
+        self.menu.input_text(id)
+    }
+
+    pub fn click_menu(&mut self, queue: &wgpu::Queue) -> Option<MenuAction> {
         if self.menu.visible {
-            self.menu.click()
+            self.menu.click(queue)
         } else {
             None
         }
@@ -496,12 +627,12 @@ impl Draw for HUD {
         if self.menu.visible {
             elements.push(&self.menu.element);
         }
-        if let Some(overlay) = &self.debug_overlay {
-            if overlay.visible {
-                // прячем оверлей под меню, чтобы не перекрывать кнопки
-                if !self.menu.visible {
-                    elements.push(&overlay.element);
-                }
+        if let Some(overlay) = &self.debug_overlay
+            && overlay.visible
+        {
+            // прячем оверлей под меню, чтобы не перекрывать кнопки
+            if !self.menu.visible {
+                elements.push(&overlay.element);
             }
         }
 
@@ -527,6 +658,14 @@ impl Draw for HUD {
                     format!("MS    {:>5.2}", stats.frame_ms),
                     format!("CHUNKS {:>4}", stats.chunks_loaded),
                     format!("DRAWS  {:>4}", stats.draw_calls),
+                    format!(
+                        "BLOCKS {:>5.1} MB",
+                        stats.block_memory as f32 / (1024.0 * 1024.0)
+                    ),
+                    format!(
+                        "LIGHT  {:>5.1} MB",
+                        stats.light_memory as f32 / (1024.0 * 1024.0)
+                    ),
                 ];
                 let mut y = 16.0;
                 for line in lines.iter() {
@@ -543,6 +682,58 @@ impl Draw for HUD {
                 }
             }
 
+            if !self.save_progress.is_idle() {
+                let line = format!(
+                    "Сохранение {}/{}",
+                    self.save_progress.written, self.save_progress.queued
+                );
+                let x = screen[0] - base_style.pixel_size as f32 * 10.0;
+                if let Ok(obj) = text.build_gui_text(
+                    &line,
+                    self.font_handle,
+                    base_style,
+                    Vec2::new(x, 16.0),
+                    screen,
+                ) {
+                    text.draw(render_pass, None, &obj, screen);
+                }
+            }
+
+            if self.quarantined_chunks > 0 {
+                let line = format!("Повреждённых чанков: {}", self.quarantined_chunks);
+                let x = screen[0] - base_style.pixel_size as f32 * 12.0;
+                let y = 20.0 + base_style.pixel_size as f32;
+                if let Ok(obj) = text.build_gui_text(
+                    &line,
+                    self.font_handle,
+                    base_style,
+                    Vec2::new(x, y),
+                    screen,
+                ) {
+                    text.draw(render_pass, None, &obj, screen);
+                }
+            }
+
+            if self.save_progress.failed > 0 {
+                let mut line = format!("Не сохранено чанков: {}", self.save_progress.failed);
+                let mut width = 12.0;
+                if self.quit_blocked {
+                    line.push_str(" — «Выход» ещё раз закроет игру без них");
+                    width = 30.0;
+                }
+                let x = screen[0] - base_style.pixel_size as f32 * width;
+                let y = 24.0 + 2.0 * base_style.pixel_size as f32;
+                if let Ok(obj) = text.build_gui_text(
+                    &line,
+                    self.font_handle,
+                    base_style,
+                    Vec2::new(x, y),
+                    screen,
+                ) {
+                    text.draw(render_pass, None, &obj, screen);
+                }
+            }
+
             if self.menu.visible {
                 // Menu quad occupies MENU_CLIP_SIZE of clip height; convert to pixels so text matches the textured quad.
                 let menu_px = (MENU_CLIP_SIZE * 0.5) * screen[1];
@@ -577,8 +768,8 @@ impl Draw for HUD {
                                 ) {
                                     text.draw(render_pass, None, &obj, screen);
                                 }
-                                if let Some(detail) = &btn.detail {
-                                    if let Ok(obj) = text.build_gui_text(
+                                if let Some(detail) = &btn.detail
+                                    && let Ok(obj) = text.build_gui_text(
                                         detail,
                                         self.font_handle,
                                         detail_style,
@@ -587,9 +778,9 @@ impl Draw for HUD {
                                             y + title_style.pixel_size as f32 + 4.0 * menu_scale,
                                         ),
                                         screen,
-                                    ) {
-                                        text.draw(render_pass, None, &obj, screen);
-                                    }
+                                    )
+                                {
+                                    text.draw(render_pass, None, &obj, screen);
                                 }
                             }
                             UiElement::Label(label) => {
@@ -612,6 +803,35 @@ impl Draw for HUD {
                                     text.draw(render_pass, None, &obj, screen);
                                 }
                             }
+                            UiElement::TextInput(input) => {
+                                let focused =
+                                    node.id.is_some() && node.id == self.menu.focused_input;
+                                let (line, color) = if input.text.is_empty() && !focused {
+                                    (input.placeholder.clone(), [0.55, 0.6, 0.7, 1.0])
+                                } else if focused {
+                                    (format!("{}_", input.text), [1.0, 1.0, 1.0, 1.0])
+                                } else {
+                                    (input.text.clone(), [1.0, 1.0, 1.0, 1.0])
+                                };
+                                let style = TextStyle {
+                                    color,
+                                    pixel_size: ((base_style.pixel_size as f32) * menu_scale)
+                                        .max(1.0)
+                                        .round()
+                                        as u32,
+                                };
+                                let x = origin.x + (node.rect[0] + input.padding) * menu_scale;
+                                let y = origin.y + (node.rect[1] + input.padding) * menu_scale;
+                                if let Ok(obj) = text.build_gui_text(
+                                    &line,
+                                    self.font_handle,
+                                    style,
+                                    Vec2::new(x, y),
+                                    screen,
+                                ) {
+                                    text.draw(render_pass, None, &obj, screen);
+                                }
+                            }
                             _ => {}
                         }
                     }
@@ -654,6 +874,10 @@ impl MenuOverlay {
             visible: false,
             aspect_correction,
             hovered: None,
+            selected: None,
+            focused_input: None,
+            hovered_input: None,
+            page: None,
             font: font.clone(),
             styles,
             tree: None,
@@ -686,56 +910,213 @@ impl MenuOverlay {
         }
     }
 
-    fn load_page(&mut self, page: MenuPage, config: &AppConfig, queue: &wgpu::Queue) {
+    fn load_page(
+        &mut self,
+        page: MenuPage,
+        config: &AppConfig,
+        worlds: &WorldBrowser,
+        queue: &wgpu::Queue,
+    ) {
         let path = match page {
             MenuPage::Main => "assets/ui/menu_main.ron",
+            MenuPage::CreateWorld => "assets/ui/menu_create_world.ron",
             MenuPage::Settings => "assets/ui/menu_settings.ron",
             MenuPage::Advanced => "assets/ui/menu_advanced.ron",
+            MenuPage::Worlds => "assets/ui/menu_worlds.ron",
+            MenuPage::RenameWorld => "assets/ui/menu_rename_world.ron",
+            MenuPage::DeleteWorld => "assets/ui/menu_delete_world.ron",
+            MenuPage::Backups => "assets/ui/menu_backups.ron",
         };
         let mut tree = crate::ui::load_ron(path).expect("failed to load menu RON");
         let title_text = match page {
             MenuPage::Main => "Меню",
+            MenuPage::CreateWorld => worlds.status.as_deref().unwrap_or("Новый мир"),
             MenuPage::Settings => "Настройки",
             MenuPage::Advanced => "Продвинутые настройки",
+            MenuPage::Worlds => worlds.status.as_deref().unwrap_or("Миры"),
+            MenuPage::RenameWorld => "Новое имя мира",
+            MenuPage::DeleteWorld => "Удалить мир?",
+            MenuPage::Backups => worlds.status.as_deref().unwrap_or("Резервные копии"),
         };
         self.apply_title(&mut tree, title_text);
 
         let entries = match page {
             MenuPage::Main => build_main_menu(),
+            MenuPage::CreateWorld => build_create_world_menu(config),
             MenuPage::Settings => build_settings_menu(config),
             MenuPage::Advanced => build_advanced_menu(config),
+            MenuPage::Worlds => build_worlds_menu(worlds),
+            MenuPage::RenameWorld => build_rename_world_menu(),
+            MenuPage::DeleteWorld => build_delete_world_menu(worlds),
+            MenuPage::Backups => build_backups_menu(worlds),
         };
         self.apply_entries(&mut tree, &entries);
+        // Пустые строки списков миров и копий не показываем.
+        let worlds_shown = worlds.visible().len();
+        let backups_shown = worlds.backups.len();
+        Self::retain_nodes(&mut tree, &|node| {
+            !matches!(
+                node.id.as_deref().and_then(MenuAction::from_key),
+                Some(MenuAction::SelectWorld(slot)) if slot >= worlds_shown
+            ) && !matches!(
+                node.id.as_deref().and_then(MenuAction::from_key),
+                Some(MenuAction::RestoreBackup(slot)) if slot >= backups_shown
+            )
+        });
+        if page == MenuPage::RenameWorld
+            && let Some(world) = worlds.selected()
+        {
+            Self::set_input_text(&mut tree, "world_name_input", &world.name);
+        }
+        let same_page = self.page == Some(page);
+        if same_page {
+            // Страница перестраивается после переключателей — набранный текст сохраняем.
+            for (id, text) in self.tree.as_ref().map(Self::inputs).unwrap_or_default() {
+                Self::set_input_text(&mut tree, &id, &text);
+            }
+        }
         self.apply_styles(&mut tree);
 
+        self.selected = match page {
+            MenuPage::Worlds => worlds.selected_slot().map(MenuAction::SelectWorld),
+            _ => None,
+        };
+        if !same_page || self.focused_input.is_none() {
+            self.focused_input = Self::inputs(&tree).into_iter().next().map(|(id, _)| id);
+        }
+        self.page = Some(page);
+        self.hovered_input = None;
         self.tree = Some(tree);
         self.hovered = None;
         self.rebuild_layout();
         self.draw(queue);
     }
 
-    fn apply_title(&self, tree: &mut UiNode, title: &str) {
+    fn retain_nodes<F: Fn(&UiNode) -> bool>(node: &mut UiNode, keep: &F) {
+        node.children.retain(|child| keep(child));
+        for child in node.children.iter_mut() {
+            Self::retain_nodes(child, keep);
+        }
+    }
+
+    fn set_input_text(tree: &mut UiNode, id: &str, text: &str) {
         Self::visit_nodes_mut(tree, &mut |node| {
-            if node.id.as_deref() == Some("menu_title") {
-                if let Some(UiElement::Label(lbl)) = &mut node.element {
-                    lbl.text = title.to_string();
+            if node.id.as_deref() == Some(id)
+                && let Some(UiElement::TextInput(input)) = &mut node.element
+            {
+                input.text = text.to_string();
+            }
+        });
+    }
+
+    /// Поля ввода дерева по порядку: `(id, текст)`.
+    fn inputs(node: &UiNode) -> Vec<(String, String)> {
+        let mut out = Vec::new();
+        if let (Some(id), Some(UiElement::TextInput(input))) = (&node.id, &node.element) {
+            out.push((id.clone(), input.text.clone()));
+        }
+        for child in &node.children {
+            out.extend(Self::inputs(child));
+        }
+        out
+    }
+
+    /// Tab: фокус на следующее поле страницы.
+    fn focus_next_input(&mut self) {
+        let ids: Vec<String> = self
+            .tree
+            .as_ref()
+            .map(Self::inputs)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let next = match self
+            .focused_input
+            .as_ref()
+            .and_then(|f| ids.iter().position(|id| id == f))
+        {
+            Some(i) => ids.get((i + 1) % ids.len()),
+            None => ids.first(),
+        };
+        self.focused_input = next.cloned();
+    }
+
+    fn input_text(&self, id: &str) -> Option<String> {
+        fn find(node: &UiNode, id: &str) -> Option<String> {
+            if node.id.as_deref() == Some(id)
+                && let Some(UiElement::TextInput(input)) = &node.element
+            {
+                return Some(input.text.clone());
+            }
+            node.children.iter().find_map(|child| find(child, id))
+        }
+        find(self.tree.as_ref()?, id)
+    }
+
+    /// Правит текст поля в фокусе: печатные символы и Backspace; Tab переводит фокус.
+    fn edit_focused_input(&mut self, event: &winit::event::KeyEvent, queue: &wgpu::Queue) -> bool {
+        let (Some(tree), Some(focused)) = (&mut self.tree, self.focused_input.clone()) else {
+            return false;
+        };
+        if event.logical_key == Key::Named(NamedKey::Tab) {
+            self.focus_next_input();
+            self.draw(queue);
+            return true;
+        }
+        let mut changed = false;
+        let mut consumed = false;
+        Self::visit_nodes_mut(tree, &mut |node| {
+            if node.id.as_deref() != Some(focused.as_str()) {
+                return;
+            }
+            let Some(UiElement::TextInput(input)) = &mut node.element else {
+                return;
+            };
+            match &event.logical_key {
+                Key::Named(NamedKey::Backspace) => {
+                    consumed = true;
+                    changed = input.backspace();
+                }
+                Key::Named(NamedKey::Enter | NamedKey::Escape | NamedKey::Tab) => {}
+                _ => {
+                    if let Some(text) = &event.text {
+                        consumed = true;
+                        changed = input.insert(text);
+                    }
                 }
             }
         });
+        if changed {
+            self.rebuild_layout();
+            self.draw(queue);
+        }
+        consumed
+    }
+
+    fn apply_title(&self, tree: &mut UiNode, title: &str) {
+        Self::visit_nodes_mut(tree, &mut |node| {
+            if node.id.as_deref() == Some("menu_title")
+                && let Some(UiElement::Label(lbl)) = &mut node.element
+            {
+                lbl.text = title.to_string();
+            }
+        });
     }
 
     fn apply_entries(&self, tree: &mut UiNode, entries: &[MenuEntry]) {
         for entry in entries {
+            let key = entry.action.key();
             Self::visit_nodes_mut(tree, &mut |node| {
-                if node.id.as_deref() == Some(entry.action.key()) {
-                    if let Some(UiElement::Button(btn)) = &mut node.element {
-                        btn.text = entry.title.clone();
-                        btn.detail = if entry.detail.is_empty() {
-                            None
-                        } else {
-                            Some(entry.detail.clone())
-                        };
-                    }
+                if node.id.as_deref() == Some(key.as_str())
+                    && let Some(UiElement::Button(btn)) = &mut node.element
+                {
+                    btn.text = entry.title.clone();
+                    btn.detail = if entry.detail.is_empty() {
+                        None
+                    } else {
+                        Some(entry.detail.clone())
+                    };
                 }
             });
         }
@@ -765,6 +1146,10 @@ impl MenuOverlay {
                 btn.padding = s.button_padding;
                 btn.min_height = s.button_height;
             }
+            if let Some(UiElement::TextInput(input)) = &mut node.element {
+                input.padding = s.button_padding;
+                input.min_height = s.button_height;
+            }
             if let Some(UiElement::Panel { color }) = &mut node.element {
                 *color = [
                     s.panel_color.0,
@@ -814,6 +1199,7 @@ impl MenuOverlay {
         let y_max = half_h;
 
         if clip_x < x_min || clip_x > x_max || clip_y < y_min || clip_y > y_max {
+            self.hovered_input = None;
             if self.hovered.is_some() {
                 self.hovered = None;
                 self.draw(queue);
@@ -835,13 +1221,29 @@ impl MenuOverlay {
                 break;
             }
         }
+        self.hovered_input = self.resolved.iter().find_map(|node| {
+            let [x, y, w, h] = node.rect;
+            let inside = px_x >= x && px_x <= x + w && px_y >= y && px_y <= y + h;
+            match &node.element {
+                Some(UiElement::TextInput(_)) if inside => node.id.clone(),
+                _ => None,
+            }
+        });
         if new_hover != self.hovered {
             self.hovered = new_hover;
             self.draw(queue);
         }
     }
 
-    fn click(&self) -> Option<MenuAction> {
+    /// Клик по полю ввода переводит на него фокус, по кнопке — возвращает действие.
+    fn click(&mut self, queue: &wgpu::Queue) -> Option<MenuAction> {
+        if let Some(id) = self.hovered_input.clone() {
+            if self.focused_input.as_ref() != Some(&id) {
+                self.focused_input = Some(id);
+                self.draw(queue);
+            }
+            return None;
+        }
         self.hovered
     }
 
@@ -855,6 +1257,9 @@ impl MenuOverlay {
                     UiElement::Panel { color } => self.draw_panel_rect(node.rect, *color),
                     UiElement::Button(btn) => self.draw_button(node.rect, btn, node.id.as_deref()),
                     UiElement::Label(label) => self.draw_label(node.rect, label),
+                    UiElement::TextInput(input) => {
+                        self.draw_text_input(node.rect, input, node.id.as_deref())
+                    }
                     UiElement::Image { .. } | UiElement::Spacer { .. } => {}
                 }
             }
@@ -874,12 +1279,13 @@ impl MenuOverlay {
     }
 
     fn draw_button(&mut self, rect: [f32; 4], spec: &ButtonSpec, id: Option<&str>) {
-        let hovered = id
-            .and_then(MenuAction::from_key)
-            .map(|a| Some(a) == self.hovered)
-            .unwrap_or(false);
+        let action = id.and_then(MenuAction::from_key);
+        let hovered = action.is_some() && action == self.hovered;
+        let selected = action.is_some() && action == self.selected;
         let base = if hovered {
             [90, 140, 255, 220]
+        } else if selected {
+            [60, 100, 170, 220]
         } else {
             [40, 60, 90, 200]
         };
@@ -894,6 +1300,22 @@ impl MenuOverlay {
         // text rendered by TextSystem; keep background only
     }
 
+    fn draw_text_input(&mut self, rect: [f32; 4], _spec: &TextInputSpec, id: Option<&str>) {
+        let focused = id.is_some() && id == self.focused_input.as_deref();
+        let x = rect[0].round() as i32;
+        let y = rect[1].round() as i32;
+        let w = rect[2].round() as i32;
+        let h = rect[3].round() as i32;
+        self.fill_rect(x, y, w, h, [14, 18, 26, 230]);
+        let border = if focused {
+            [120, 170, 255, 255]
+        } else {
+            [60, 80, 110, 255]
+        };
+        self.stroke_rect(x, y, w, h, 2, border);
+        // text rendered by TextSystem
+    }
+
     fn draw_label(&mut self, _rect: [f32; 4], _label: &crate::ui::LabelSpec) {
         // text rendered by TextSystem; background only
     }
@@ -1089,7 +1511,7 @@ fn build_main_menu() -> Vec<MenuEntry> {
         MenuEntry {
             title: "Создать мир".to_string(),
             detail: "Новый seed и папка сохранения".to_string(),
-            action: MenuAction::CreateWorld,
+            action: MenuAction::OpenCreateWorld,
         },
         MenuEntry {
             title: "Открыть мир".to_string(),
@@ -1119,6 +1541,187 @@ fn build_main_menu() -> Vec<MenuEntry> {
     ]
 }
 
+fn build_create_world_menu(cfg: &AppConfig) -> Vec<MenuEntry> {
+    let (world_type, detail) = match &cfg.world.world_type {
+        WorldType::Default => ("обычный", "Биомы по пресету генерации".to_string()),
+        WorldType::Superflat { layers } => ("плоский", format!("Слои: {layers}")),
+        WorldType::Void => ("пустота", "Площадка у точки появления".to_string()),
+        WorldType::DebugGrid => ("сетка материалов", "Все блоки в ряд".to_string()),
+    };
+    let (game_mode, mode_detail) = match cfg.world.game_mode {
+        PlayerMode::Adventure => ("приключение", "Гравитация и столкновения"),
+        PlayerMode::Creative => ("творческий", "Свободный полёт"),
+    };
+    vec![
+        MenuEntry {
+            title: format!("Тип мира: {world_type}"),
+            detail,
+            action: MenuAction::CycleWorldType,
+        },
+        MenuEntry {
+            title: format!("Режим: {game_mode}"),
+            detail: mode_detail.to_string(),
+            action: MenuAction::CycleGameMode,
+        },
+        MenuEntry {
+            title: "Создать".to_string(),
+            detail: "Пустой seed — случайный".to_string(),
+            action: MenuAction::CreateWorld,
+        },
+        MenuEntry {
+            title: "Назад".to_string(),
+            detail: "".to_string(),
+            action: MenuAction::BackToMain,
+        },
+    ]
+}
+
+fn build_worlds_menu(worlds: &WorldBrowser) -> Vec<MenuEntry> {
+    let mut entries: Vec<MenuEntry> = worlds
+        .visible()
+        .iter()
+        .enumerate()
+        .map(|(slot, world)| {
+            let title = if world.name == worlds.current {
+                format!("{} (открыт)", world.name)
+            } else {
+                world.name.clone()
+            };
+            let detail = match (world.seed, world.last_played) {
+                (Some(seed), Some(last_played)) => format!(
+                    "Seed {seed}, {}, {}",
+                    format_timestamp(last_played),
+                    format_size(world.size_bytes)
+                ),
+                _ => format!("Нет level.ron, {}", format_size(world.size_bytes)),
+            };
+            MenuEntry {
+                title,
+                detail,
+                action: MenuAction::SelectWorld(slot),
+            }
+        })
+        .collect();
+    let selected = worlds
+        .selected()
+        .map(|w| w.name.clone())
+        .unwrap_or_else(|| "Мир не выбран".to_string());
+    let page_detail = if worlds.worlds.is_empty() {
+        "Сохранений нет".to_string()
+    } else {
+        format!("Выбран: {selected}")
+    };
+    entries.extend([
+        MenuEntry {
+            title: format!(
+                "Страница {}/{} ({} миров)",
+                worlds.page() + 1,
+                worlds.page_count(),
+                worlds.worlds.len()
+            ),
+            detail: page_detail,
+            action: MenuAction::NextWorldsPage,
+        },
+        MenuEntry {
+            title: "Открыть".to_string(),
+            detail: String::new(),
+            action: MenuAction::OpenSelectedWorld,
+        },
+        MenuEntry {
+            title: "Копия".to_string(),
+            detail: String::new(),
+            action: MenuAction::DuplicateWorld,
+        },
+        MenuEntry {
+            title: "Имя".to_string(),
+            detail: String::new(),
+            action: MenuAction::OpenRenameWorld,
+        },
+        MenuEntry {
+            title: "Удалить".to_string(),
+            detail: String::new(),
+            action: MenuAction::OpenDeleteWorld,
+        },
+        MenuEntry {
+            title: "Бэкапы".to_string(),
+            detail: String::new(),
+            action: MenuAction::OpenBackups,
+        },
+        MenuEntry {
+            title: "Папка".to_string(),
+            detail: String::new(),
+            action: MenuAction::OpenWorldFolder,
+        },
+        MenuEntry {
+            title: "Назад".to_string(),
+            detail: String::new(),
+            action: MenuAction::BackToMain,
+        },
+    ]);
+    entries
+}
+
+fn build_backups_menu(worlds: &WorldBrowser) -> Vec<MenuEntry> {
+    let name = worlds
+        .selected()
+        .map(|w| w.name.clone())
+        .unwrap_or_default();
+    let mut entries: Vec<MenuEntry> = worlds
+        .backups
+        .iter()
+        .enumerate()
+        .map(|(slot, backup)| MenuEntry {
+            title: format!("Восстановить {}", format_timestamp(backup.created)),
+            detail: format!("{name}: текущее состояние тоже сохранится"),
+            action: MenuAction::RestoreBackup(slot),
+        })
+        .collect();
+    entries.push(MenuEntry {
+        title: "Назад".to_string(),
+        detail: if worlds.backups.is_empty() {
+            "Копий этого мира ещё нет".to_string()
+        } else {
+            String::new()
+        },
+        action: MenuAction::OpenWorld,
+    });
+    entries
+}
+
+fn build_rename_world_menu() -> Vec<MenuEntry> {
+    vec![
+        MenuEntry {
+            title: "Переименовать".to_string(),
+            detail: "Enter — подтвердить".to_string(),
+            action: MenuAction::ConfirmRenameWorld,
+        },
+        MenuEntry {
+            title: "Отмена".to_string(),
+            detail: String::new(),
+            action: MenuAction::OpenWorld,
+        },
+    ]
+}
+
+fn build_delete_world_menu(worlds: &WorldBrowser) -> Vec<MenuEntry> {
+    let name = worlds
+        .selected()
+        .map(|w| w.name.clone())
+        .unwrap_or_default();
+    vec![
+        MenuEntry {
+            title: "Удалить".to_string(),
+            detail: format!("{name}: папка мира будет удалена, копии останутся"),
+            action: MenuAction::ConfirmDeleteWorld,
+        },
+        MenuEntry {
+            title: "Отмена".to_string(),
+            detail: String::new(),
+            action: MenuAction::OpenWorld,
+        },
+    ]
+}
+
 fn build_settings_menu(cfg: &AppConfig) -> Vec<MenuEntry> {
     vec![
         MenuEntry {
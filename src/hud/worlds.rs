@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use crate::core::{
+    backup::{BackupInfo, list_backups},
+    saves::{WorldSummary, list_worlds},
+};
+
+/// Сколько миров помещается на одной странице списка.
+pub const WORLDS_PER_PAGE: usize = 3;
+/// Сколько резервных копий показывает страница восстановления.
+pub const BACKUPS_SHOWN: usize = 5;
+
+/// Состояние экрана выбора мира: список сохранений, выбранный мир и страница.
+#[derive(Debug, Default)]
+pub struct WorldBrowser {
+    pub worlds: Vec<WorldSummary>,
+    selected: Option<usize>,
+    page: usize,
+    /// Мир, открытый сейчас: его нельзя удалить или переименовать.
+    pub current: String,
+    /// Сообщение об ошибке последнего действия.
+    pub status: Option<String>,
+    /// Резервные копии выбранного мира, от новых к старым.
+    pub backups: Vec<BackupInfo>,
+}
+
+impl WorldBrowser {
+    /// Перечитывает список миров; выбор сохраняется по имени, если мир остался.
+    pub fn refresh(&mut self, root: &Path, current: &str) {
+        let selected_name = self.selected().map(|w| w.name.clone());
+        self.worlds = list_worlds(root);
+        self.current = current.to_string();
+        self.selected = None;
+        if let Some(name) = selected_name {
+            self.select_name(&name);
+        }
+        self.page = self.page.min(self.page_count() - 1);
+    }
+
+    /// Перечитывает резервные копии выбранного мира.
+    pub fn refresh_backups(&mut self, backups_root: &Path) {
+        self.backups = match self.selected() {
+            Some(world) => list_backups(backups_root, &world.name),
+            None => Vec::new(),
+        };
+        self.backups.truncate(BACKUPS_SHOWN);
+    }
+
+    pub fn select_name(&mut self, name: &str) {
+        if let Some(index) = self.worlds.iter().position(|w| w.name == name) {
+            self.selected = Some(index);
+            self.page = index / WORLDS_PER_PAGE;
+        }
+    }
+
+    /// Выбор по номеру строки на текущей странице.
+    pub fn select_slot(&mut self, slot: usize) {
+        let index = self.page * WORLDS_PER_PAGE + slot;
+        if index < self.worlds.len() {
+            self.selected = Some(index);
+        }
+    }
+
+    pub fn selected(&self) -> Option<&WorldSummary> {
+        self.selected.and_then(|i| self.worlds.get(i))
+    }
+
+    /// Выбранный мир, если это не открытый сейчас.
+    pub fn selected_other(&self) -> Option<&WorldSummary> {
+        self.selected().filter(|w| w.name != self.current)
+    }
+
+    pub fn selected_slot(&self) -> Option<usize> {
+        let index = self.selected?;
+        (index / WORLDS_PER_PAGE == self.page).then_some(index % WORLDS_PER_PAGE)
+    }
+
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.worlds.len().div_ceil(WORLDS_PER_PAGE).max(1)
+    }
+
+    pub fn next_page(&mut self) {
+        self.page = (self.page + 1) % self.page_count();
+    }
+
+    /// Миры текущей страницы.
+    pub fn visible(&self) -> &[WorldSummary] {
+        let start = (self.page * WORLDS_PER_PAGE).min(self.worlds.len());
+        let end = (start + WORLDS_PER_PAGE).min(self.worlds.len());
+        &self.worlds[start..end]
+    }
+}
@@ -7,24 +7,33 @@ pub mod render;
 pub mod terrain_gen;
 pub mod text;
 pub mod ui;
+pub mod world_tool;
 
 use hud::{HUD, OverlayStats, icons_atlas::IconType};
-use player::{Player, camera::Camera, raycast::Ray};
-use std::time::{Duration, Instant};
+use player::{Player, camera::Camera, raycast::Ray, state::PlayerState};
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
 
-use core::config::AppConfig;
+use core::{
+    backup::{self, BACKUPS_DIR},
+    config::{AppConfig, DEFAULT_SUPERFLAT_LAYERS, PlayerMode, WorldType},
+    level::{LevelData, SAVES_DIR, seed_from_text, world_dir},
+    saves,
+};
 use render::{
     atlas::MaterialType,
     pipelines::{GlobalModel, Globals},
     renderer::Renderer,
 };
-use terrain_gen::{chunk::CHUNK_AREA, generator::TerrainGen};
+use terrain_gen::{chunk::CHUNK_AREA, generator::TerrainGen, mesher::FLUID_SURFACE_DROP};
 use wgpu::BindGroup;
 use winit::{
     dpi::PhysicalPosition,
     event::{self, DeviceEvent, ElementState, KeyEvent, MouseButton, WindowEvent},
     event_loop::EventLoopWindowTarget,
-    keyboard::{KeyCode, PhysicalKey},
+    keyboard::{Key, KeyCode, NamedKey, PhysicalKey},
     window::{CursorGrabMode, Window},
 };
 
@@ -44,6 +53,9 @@ pub struct State<'a> {
     pub window: &'a Window,
     renderer: Renderer<'a>,
     pub config: AppConfig,
+    /// Метаданные открытого мира и его каталог.
+    pub level: LevelData,
+    level_dir: std::path::PathBuf,
     pub data: GlobalModel,
     pub globals_bind_group: BindGroup,
     pub player: Player,
@@ -51,15 +63,27 @@ pub struct State<'a> {
     pub hud: HUD,
     state: GameState,
     last_frame_time: Instant,
+    /// Игровое время с последнего автосохранения.
+    since_autosave: Duration,
+    since_backup: Duration,
     frame_target: Option<Duration>,
     selected_block: Option<cgmath::Vector3<i32>>,
     menu_page: MenuPage,
+    /// Выход уже упирался в несохранённые чанки: следующий выход закрывает игру.
+    quit_despite_unsaved: bool,
+    /// Сдвиг текстуры воды в тайлах, по кругу в пределах периода анимации.
+    water_phase: f32,
 }
 
+/// Туман под водой: плотный к этому расстоянию, в блоках.
+const UNDERWATER_FOG_END: f32 = 24.0;
+const UNDERWATER_FOG_COLOR: [f32; 3] = [0.10, 0.25, 0.45];
+
 impl<'a> State<'a> {
-    pub fn new(window: &'a Window, config: AppConfig) -> Self {
+    pub fn new(window: &'a Window, mut config: AppConfig) -> Self {
+        let level = LevelData::open(&mut config);
         let frame_target = config.target_frame_time();
-        let mut renderer = Renderer::new(&window, config.present_mode(), config.graphics.sky_color);
+        let mut renderer = Renderer::new(window, config.present_mode(), config.graphics.sky_color);
 
         let data = GlobalModel {
             globals: renderer.create_consts(&[Globals::default()]),
@@ -84,7 +108,7 @@ impl<'a> State<'a> {
 
         let camera = Camera::new(
             &renderer,
-            (8.0, 12.0, 8.0),
+            level.spawn,
             cgmath::Deg(-90.0),
             cgmath::Deg(-20.0),
             config.graphics.render_distance_chunks,
@@ -98,11 +122,18 @@ impl<'a> State<'a> {
         player.set_mode(config.player.mode.clone(), &config);
 
         let terrain = TerrainGen::new(&renderer, &config);
+        backup::backup_in_background(
+            &config.world.world_name,
+            config.world.backups_to_keep,
+            terrain.region(),
+        );
 
-        Self {
+        let mut state = Self {
             window,
             renderer,
+            level_dir: world_dir(&config.world.world_name),
             config,
+            level,
             data,
             globals_bind_group,
             player,
@@ -110,10 +141,16 @@ impl<'a> State<'a> {
             hud,
             state: GameState::PLAYING,
             last_frame_time: Instant::now(),
+            since_autosave: Duration::ZERO,
+            since_backup: Duration::ZERO,
             frame_target,
             selected_block: None,
             menu_page: MenuPage::Main,
-        }
+            quit_despite_unsaved: false,
+            water_phase: 0.0,
+        };
+        state.restore_player();
+        state
     }
 
     pub fn handle_wait(&mut self, _elwt: &EventLoopWindowTarget<()>) {
@@ -124,7 +161,7 @@ impl<'a> State<'a> {
     pub fn handle_window_event(&mut self, event: WindowEvent, elwt: &EventLoopWindowTarget<()>) {
         if !self.handle_input_event(&event) {
             match event {
-                WindowEvent::CloseRequested => elwt.exit(),
+                WindowEvent::CloseRequested => self.quit(elwt),
 
                 WindowEvent::Resized(physical_size) => {
                     self.resize(physical_size);
@@ -133,24 +170,38 @@ impl<'a> State<'a> {
                     self.render_frame(elwt);
                 }
 
-                WindowEvent::CursorMoved { position, .. } => {
-                    if self.state == GameState::MENU {
-                        let clip_x =
-                            (position.x as f32 / self.renderer.size.width as f32) * 2.0 - 1.0;
-                        let clip_y =
-                            -((position.y as f32 / self.renderer.size.height as f32) * 2.0 - 1.0);
-                        self.hud
-                            .hover_menu(clip_x as f32, clip_y as f32, &self.renderer.queue);
+                // Ввод текста в поле меню; Enter подтверждает страницу.
+                WindowEvent::KeyboardInput {
+                    event: key_event, ..
+                } if self.state == GameState::MENU
+                    && key_event.state == ElementState::Pressed
+                    && key_event.logical_key == Key::Named(NamedKey::Enter)
+                    && self.menu_page.submit_action().is_some() =>
+                {
+                    if let Some(action) = self.menu_page.submit_action() {
+                        self.handle_menu_action(action, elwt);
                     }
                 }
+                WindowEvent::KeyboardInput {
+                    event: key_event, ..
+                } if self.state == GameState::MENU
+                    && self.hud.menu_key_input(&key_event, &self.renderer.queue) => {}
+
+                WindowEvent::CursorMoved { position, .. } if self.state == GameState::MENU => {
+                    let clip_x = (position.x as f32 / self.renderer.size.width as f32) * 2.0 - 1.0;
+                    let clip_y =
+                        -((position.y as f32 / self.renderer.size.height as f32) * 2.0 - 1.0);
+                    self.hud.hover_menu(clip_x, clip_y, &self.renderer.queue);
+                }
 
                 // Обработка событий мыши
                 WindowEvent::MouseInput { state, button, .. } => {
                     if self.state == GameState::MENU {
-                        if state == ElementState::Pressed && button == MouseButton::Left {
-                            if let Some(action) = self.hud.click_menu() {
-                                self.handle_menu_action(action, elwt);
-                            }
+                        if state == ElementState::Pressed
+                            && button == MouseButton::Left
+                            && let Some(action) = self.hud.click_menu(&self.renderer.queue)
+                        {
+                            self.handle_menu_action(action, elwt);
                         }
                         return;
                     }
@@ -218,10 +269,9 @@ impl<'a> State<'a> {
                             if let Some(hit) = ray_hit {
                                 if let Some(block) =
                                     self.terrain.chunks.get_block_material(hit.position)
+                                    && let Some(icon) = IconType::from_material(block)
                                 {
-                                    if let Some(icon) = IconType::from_material(block) {
-                                        self.hud.select_by_icon(icon, &self.renderer);
-                                    }
+                                    self.hud.select_by_icon(icon, &self.renderer);
                                 }
                             } else {
                                 println!("Нет блока для копирования");
@@ -298,11 +348,11 @@ impl<'a> State<'a> {
         let _span = span!("redraw request"); // <- Начало блока рендера
 
         let now = Instant::now();
-        if let Some(target) = self.frame_target {
-            if now - self.last_frame_time < target {
-                // Ещё рано рисовать следующий кадр; не блокируем event loop
-                return;
-            }
+        if let Some(target) = self.frame_target
+            && now - self.last_frame_time < target
+        {
+            // Ещё рано рисовать следующий кадр; не блокируем event loop
+            return;
         }
 
         let mut elapsed = now - self.last_frame_time;
@@ -312,11 +362,29 @@ impl<'a> State<'a> {
 
         self.last_frame_time = now;
         if self.state == GameState::PLAYING {
+            self.level.world_time += elapsed.as_secs_f64();
+            self.since_autosave += elapsed;
+            let interval = self.config.world.autosave_interval_secs;
+            if interval > 0 && self.since_autosave.as_secs() >= interval as u64 {
+                self.save_world(false);
+            }
+            self.since_backup += elapsed;
+            let backup_minutes = self.config.world.backup_interval_minutes;
+            if backup_minutes > 0 && self.since_backup.as_secs() >= backup_minutes as u64 * 60 {
+                self.since_backup = Duration::ZERO;
+                // Копия снимается с диска, поэтому сначала дописываем всё несохранённое.
+                self.save_world(true);
+                backup::backup_in_background(
+                    &self.config.world.world_name,
+                    self.config.world.backups_to_keep,
+                    self.terrain.region(),
+                );
+            }
             self.player.update(elapsed, &self.terrain.chunks);
             self.terrain.update(
                 &self.renderer.device,
                 &self.renderer.queue,
-                &self.player.camera.position,
+                &self.player.camera,
             );
         }
 
@@ -333,9 +401,20 @@ impl<'a> State<'a> {
             },
             frame_ms: elapsed.as_secs_f32() * 1000.0,
             chunks_loaded: self.terrain.loaded_chunks(),
+            block_memory: self.terrain.block_memory_bytes(),
+            light_memory: self.terrain.light_memory_bytes(),
             draw_calls: self.terrain.chunk_models.len() + self.hud.draw_call_count(),
         };
         self.hud.update_overlay(&self.renderer, &stats);
+        let save_progress = self.terrain.save_progress();
+        if save_progress.failed == 0 && self.quit_despite_unsaved {
+            // Повторная запись прошла: выход снова сохраняет как обычно.
+            self.quit_despite_unsaved = false;
+            self.hud.set_quit_blocked(false);
+        }
+        self.hud.set_save_progress(save_progress);
+        self.hud
+            .set_quarantined_chunks(self.terrain.quarantined_chunks());
 
         match self
             .renderer
@@ -385,7 +464,7 @@ impl<'a> State<'a> {
         self.hud.resize(&self.renderer);
     }
 
-    pub fn update(&mut self, _dt: std::time::Duration) {
+    pub fn update(&mut self, dt: std::time::Duration) {
         #[cfg(feature = "tracy")]
         let _span = span!("update state"); // <- Начало обновления игрового состояния
 
@@ -395,9 +474,19 @@ impl<'a> State<'a> {
         let max_view_distance =
             (self.config.graphics.render_distance_chunks.max(1) * CHUNK_AREA) as f32;
         // Более резкий и короткий туман: начало ~35% дальности, полная плотность к ~50%.
-        let fog_start = max_view_distance * 0.35;
-        let fog_end = max_view_distance * 0.50;
-        let sky_color = self.config.graphics.sky_color;
+        let mut fog_start = max_view_distance * 0.35;
+        let mut fog_end = max_view_distance * 0.50;
+        let mut sky_color = self.config.graphics.sky_color;
+        if self.config.graphics.underwater_fog && self.camera_underwater() {
+            fog_start = 0.0;
+            fog_end = UNDERWATER_FOG_END;
+            sky_color = UNDERWATER_FOG_COLOR;
+        }
+        self.renderer.set_clear_color(sky_color);
+        // Тайл сдвигается на (1, 0.5) за единицу фазы, за 2 единицы — на целые тайлы.
+        let graphics = &self.config.graphics;
+        self.water_phase =
+            (self.water_phase + dt.as_secs_f32() * graphics.water_animation_speed) % 2.0;
 
         self.renderer.update_consts(
             &mut self.data.globals,
@@ -411,6 +500,8 @@ impl<'a> State<'a> {
                 fog_start,
                 fog_end,
                 sky_color,
+                self.water_phase,
+                self.config.graphics.water_opacity.clamp(0.0, 1.0),
             )],
         );
 
@@ -419,7 +510,7 @@ impl<'a> State<'a> {
 
     pub fn handle_input_event(&mut self, event: &WindowEvent) -> bool {
         if self.state == GameState::PLAYING {
-            self.player.camera.input_keyboard(&event)
+            self.player.camera.input_keyboard(event)
         } else {
             false
         }
@@ -431,6 +522,25 @@ impl<'a> State<'a> {
         }
     }
 
+    /// Глаза в жидкости, с учётом опущенной поверхности верхнего блока.
+    fn camera_underwater(&self) -> bool {
+        let eye = self.player.camera.position;
+        let block = cgmath::Vector3::new(
+            eye.x.floor() as i32,
+            eye.y.floor() as i32,
+            eye.z.floor() as i32,
+        );
+        let chunks = &self.terrain.chunks;
+        let fluid_at = |pos| {
+            chunks
+                .get_block_material(pos)
+                .is_some_and(|m: MaterialType| m.is_fluid())
+        };
+        fluid_at(block)
+            && (fluid_at(block + cgmath::Vector3::unit_y())
+                || eye.y - (block.y as f32) < 1.0 - FLUID_SURFACE_DROP)
+    }
+
     fn update_block_highlight(&mut self) {
         let range = self.player.max_interact_range();
         let ray = Ray::from_camera(&self.player.camera, range);
@@ -442,11 +552,79 @@ impl<'a> State<'a> {
         }
     }
 
+    /// Сохраняет изменённые чанки, метаданные мира и игрока. С `wait`
+    /// дожидается, пока чанки будут записаны (выход и смена мира), и
+    /// возвращает `false`, если записать удалось не всё. Без `wait` занятые
+    /// фоновыми задачами чанки откладываются до следующего сохранения.
+    fn save_world(&mut self, wait: bool) -> bool {
+        self.since_autosave = Duration::ZERO;
+        let queued = self.terrain.save_modified_chunks(wait);
+        self.save_world_state();
+        if wait && let Err(err) = self.terrain.flush_saves() {
+            log::error!("World save failed: {err:?}");
+            return false;
+        }
+        log::info!("Saved world ({queued} modified chunks)");
+        true
+    }
+
+    /// Сохраняет мир и закрывает игру. Если чанки не записались, первый выход
+    /// отменяется, чтобы несохранённые правки не пропали молча; повторный
+    /// выход закрывает игру без них.
+    fn quit(&mut self, elwt: &EventLoopWindowTarget<()>) {
+        if self.save_world(true) || self.quit_despite_unsaved {
+            elwt.exit();
+            return;
+        }
+        self.quit_despite_unsaved = true;
+        self.hud.set_quit_blocked(true);
+        log::warn!("Quit cancelled: some chunks are not saved; quit again to exit anyway");
+    }
+
+    /// Сохраняет метаданные текущего мира и состояние игрока в нём.
+    fn save_world_state(&mut self) {
+        if let Err(err) = self.level.save(&self.level_dir) {
+            log::warn!("Failed to save world metadata: {err:?}");
+        }
+        let player = self.player.state(self.hud.selected_slot());
+        if let Err(err) = player.save(&self.level_dir, &self.config.multiplayer.player_name) {
+            log::warn!("Failed to save player state: {err:?}");
+        }
+    }
+
+    /// Возвращает игрока туда, где он вышел из мира; новый игрок остаётся на спавне.
+    fn restore_player(&mut self) {
+        match PlayerState::load(&self.level_dir, &self.config.multiplayer.player_name) {
+            Ok(Some(state)) => {
+                self.player.restore(&state, &self.config);
+                self.hud.select_slot(state.hotbar_slot, &self.renderer);
+            }
+            Ok(None) => {}
+            Err(err) => log::warn!("Failed to load player state: {err:?}"),
+        }
+    }
+
+    /// Сохраняет текущий мир и открывает `config.world.world_name`
+    /// (тот же самый или новый). Копия снимается только при входе в другой мир:
+    /// смена настроек перезагружает тот же.
     fn reload_world(&mut self) {
+        self.save_world(true);
+        let level_dir = world_dir(&self.config.world.world_name);
+        let other_world = level_dir != self.level_dir;
+        self.level = LevelData::open(&mut self.config);
+        self.level_dir = level_dir;
         self.terrain = TerrainGen::new(&self.renderer, &self.config);
+        if other_world {
+            backup::backup_in_background(
+                &self.config.world.world_name,
+                self.config.world.backups_to_keep,
+                self.terrain.region(),
+            );
+            self.since_backup = Duration::ZERO;
+        }
         let camera = Camera::new(
             &self.renderer,
-            (8.0, 12.0, 8.0),
+            self.level.spawn,
             cgmath::Deg(-90.0),
             cgmath::Deg(-20.0),
             self.config.graphics.render_distance_chunks,
@@ -458,6 +636,7 @@ impl<'a> State<'a> {
         self.player = Player::new(camera, self.config.input.move_speed, &self.config);
         self.player
             .set_mode(self.config.player.mode.clone(), &self.config);
+        self.restore_player();
     }
 
     fn handle_menu_action(&mut self, action: MenuAction, elwt: &EventLoopWindowTarget<()>) {
@@ -465,27 +644,211 @@ impl<'a> State<'a> {
             MenuAction::Resume => {
                 self.enter_play_mode();
             }
-            MenuAction::CreateWorld => {
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs();
-                self.config.world.world_name = format!("world_{}", now);
-                self.config.world.seed =
-                    (now as u32).wrapping_mul(1664525).wrapping_add(1013904223);
-                self.reload_world();
-                self.menu_page = MenuPage::Main;
+            MenuAction::OpenCreateWorld => {
+                self.hud.world_browser.status = None;
+                self.menu_page = MenuPage::CreateWorld;
+                self.hud
+                    .open_menu(self.menu_page, &self.config, &self.renderer.queue);
+            }
+            MenuAction::CycleWorldType => {
+                self.config.world.world_type = match self.config.world.world_type {
+                    WorldType::Default => WorldType::Superflat {
+                        layers: DEFAULT_SUPERFLAT_LAYERS.to_string(),
+                    },
+                    WorldType::Superflat { .. } => WorldType::Void,
+                    WorldType::Void => WorldType::DebugGrid,
+                    WorldType::DebugGrid => WorldType::Default,
+                };
+                self.hud
+                    .open_menu(self.menu_page, &self.config, &self.renderer.queue);
+            }
+            MenuAction::CycleGameMode => {
+                self.config.world.game_mode = match self.config.world.game_mode {
+                    PlayerMode::Adventure => PlayerMode::Creative,
+                    PlayerMode::Creative => PlayerMode::Adventure,
+                };
                 self.hud
                     .open_menu(self.menu_page, &self.config, &self.renderer.queue);
             }
+            MenuAction::CreateWorld => {
+                let now = core::level::unix_now();
+                let name = self
+                    .hud
+                    .menu_input_text("world_name_input")
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                let name = if name.is_empty() {
+                    format!("world_{now}")
+                } else {
+                    name
+                };
+                let problem = if !saves::is_valid_world_name(&name) {
+                    Some("Имя: буквы, цифры, пробел, - и _")
+                } else if world_dir(&name).exists() {
+                    Some("Мир с таким именем уже есть")
+                } else {
+                    None
+                };
+                if let Some(problem) = problem {
+                    self.hud.world_browser.status = Some(problem.to_string());
+                    self.hud
+                        .open_menu(self.menu_page, &self.config, &self.renderer.queue);
+                    return;
+                }
+                let seed_text = self
+                    .hud
+                    .menu_input_text("world_seed_input")
+                    .unwrap_or_default();
+                self.config.world.seed = seed_from_text(&seed_text)
+                    .unwrap_or_else(|| (now as u32).wrapping_mul(1664525).wrapping_add(1013904223));
+                self.config.world.world_name = name;
+                self.hud.world_browser.status = None;
+                self.reload_world();
+                self.enter_play_mode();
+            }
             MenuAction::OpenWorld => {
-                if let Some(name) = Self::pick_existing_world() {
-                    self.config.world.world_name = name;
+                self.hud.world_browser.status = None;
+                self.show_worlds_page();
+            }
+            MenuAction::SelectWorld(slot) => {
+                self.hud.world_browser.select_slot(slot);
+                self.show_worlds_page();
+            }
+            MenuAction::NextWorldsPage => {
+                self.hud.world_browser.next_page();
+                self.show_worlds_page();
+            }
+            MenuAction::OpenSelectedWorld => {
+                if let Some(world) = self.hud.world_browser.selected_other() {
+                    self.config.world.world_name = world.name.clone();
                     self.reload_world();
+                    self.enter_play_mode();
+                }
+            }
+            MenuAction::DuplicateWorld => {
+                let Some(name) = self.hud.world_browser.selected().map(|w| w.name.clone()) else {
+                    return;
+                };
+                if name == self.config.world.world_name {
+                    // Копируем открытый мир вместе с ещё не записанными изменениями.
+                    self.save_world(true);
+                }
+                match saves::duplicate_world(Path::new(SAVES_DIR), &name) {
+                    Ok(copy) => {
+                        self.refresh_worlds();
+                        self.hud.world_browser.select_name(&copy);
+                    }
+                    Err(err) => self.world_action_failed("Не удалось скопировать мир", err),
+                }
+                self.show_worlds_page();
+            }
+            MenuAction::OpenRenameWorld => {
+                if self.hud.world_browser.selected_other().is_some() {
+                    self.menu_page = MenuPage::RenameWorld;
+                    self.hud
+                        .open_menu(self.menu_page, &self.config, &self.renderer.queue);
+                }
+            }
+            MenuAction::ConfirmRenameWorld => {
+                let from = self
+                    .hud
+                    .world_browser
+                    .selected_other()
+                    .map(|w| w.name.clone());
+                let to = self.hud.menu_input_text("world_name_input");
+                if let (Some(from), Some(to)) = (from, to) {
+                    let to = to.trim().to_string();
+                    match saves::rename_world(Path::new(SAVES_DIR), &from, &to) {
+                        Ok(()) => {
+                            if let Err(err) =
+                                backup::rename_backups(Path::new(BACKUPS_DIR), &from, &to)
+                            {
+                                log::warn!("World backups were not moved: {err:?}");
+                            }
+                            self.hud.world_browser.status = None;
+                            self.refresh_worlds();
+                            self.hud.world_browser.select_name(&to);
+                        }
+                        Err(err) => self.world_action_failed("Не удалось переименовать", err),
+                    }
+                }
+                self.show_worlds_page();
+            }
+            MenuAction::OpenDeleteWorld => {
+                if self.hud.world_browser.selected_other().is_some() {
+                    self.menu_page = MenuPage::DeleteWorld;
+                    self.hud
+                        .open_menu(self.menu_page, &self.config, &self.renderer.queue);
+                }
+            }
+            MenuAction::ConfirmDeleteWorld => {
+                if let Some(name) = self
+                    .hud
+                    .world_browser
+                    .selected_other()
+                    .map(|w| w.name.clone())
+                {
+                    let deleted = saves::delete_world(Path::new(SAVES_DIR), &name)
+                        .and_then(|()| backup::trash_backups(Path::new(BACKUPS_DIR), &name));
+                    match deleted {
+                        Ok(Some(trash)) => {
+                            log::info!("Backups of {name} moved to {}", trash.display());
+                            self.hud.world_browser.status =
+                                Some("Мир удалён, копии — в backups/.deleted".to_string());
+                        }
+                        Ok(None) => {}
+                        Err(err) => self.world_action_failed("Не удалось удалить мир", err),
+                    }
+                }
+                self.refresh_worlds();
+                self.show_worlds_page();
+            }
+            MenuAction::OpenBackups => {
+                if self.hud.world_browser.selected().is_some() {
+                    self.hud.world_browser.status = None;
+                    self.hud
+                        .world_browser
+                        .refresh_backups(Path::new(BACKUPS_DIR));
+                    self.menu_page = MenuPage::Backups;
+                    self.hud
+                        .open_menu(self.menu_page, &self.config, &self.renderer.queue);
+                }
+            }
+            MenuAction::RestoreBackup(slot) => {
+                let browser = &self.hud.world_browser;
+                let Some(backup) = browser.backups.get(slot).map(|b| b.path.clone()) else {
+                    return;
+                };
+                // Открытый мир держит чанки в памяти и при выходе перезапишет
+                // восстановленные файлы, поэтому его сначала нужно закрыть.
+                let Some(name) = browser.selected_other().map(|w| w.name.clone()) else {
+                    self.hud.world_browser.status = Some("Сначала откройте другой мир".to_string());
+                    self.hud
+                        .open_menu(self.menu_page, &self.config, &self.renderer.queue);
+                    return;
+                };
+                let restored = backup::restore_backup(
+                    Path::new(SAVES_DIR),
+                    Path::new(BACKUPS_DIR),
+                    &name,
+                    &backup,
+                    self.config.world.backups_to_keep,
+                );
+                if let Err(err) = restored {
+                    self.world_action_failed("Не удалось восстановить копию", err);
+                }
+                self.show_worlds_page();
+            }
+            MenuAction::OpenWorldFolder => {
+                let dir = match self.hud.world_browser.selected() {
+                    Some(world) => world_dir(&world.name),
+                    None => Path::new(SAVES_DIR).to_path_buf(),
+                };
+                if let Err(err) = saves::open_folder(&dir) {
+                    self.world_action_failed("Не удалось открыть папку", err);
+                    self.show_worlds_page();
                 }
-                self.menu_page = MenuPage::Main;
-                self.hud
-                    .open_menu(self.menu_page, &self.config, &self.renderer.queue);
             }
             MenuAction::SaveConfig => {
                 if let Err(e) = self.config.write_to("config.json") {
@@ -619,17 +982,26 @@ impl<'a> State<'a> {
                 self.hud
                     .open_menu(self.menu_page, &self.config, &self.renderer.queue);
             }
-            MenuAction::Quit => elwt.exit(),
+            MenuAction::Quit => self.quit(elwt),
         }
     }
 
-    fn pick_existing_world() -> Option<String> {
-        let mut entries = std::fs::read_dir("saves").ok()?;
-        while let Some(Ok(entry)) = entries.next() {
-            if entry.file_type().ok()?.is_dir() {
-                return entry.file_name().into_string().ok();
-            }
-        }
-        None
+    fn refresh_worlds(&mut self) {
+        self.hud
+            .world_browser
+            .refresh(Path::new(SAVES_DIR), &self.config.world.world_name);
+    }
+
+    /// Обновляет список миров и показывает страницу выбора.
+    fn show_worlds_page(&mut self) {
+        self.refresh_worlds();
+        self.menu_page = MenuPage::Worlds;
+        self.hud
+            .open_menu(self.menu_page, &self.config, &self.renderer.queue);
+    }
+
+    fn world_action_failed(&mut self, message: &str, err: anyhow::Error) {
+        log::warn!("{message}: {err:?}");
+        self.hud.world_browser.status = Some(message.to_string());
     }
 }
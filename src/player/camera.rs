@@ -25,6 +25,33 @@ pub struct Dependants {
     pub view_proj: [[f32; 4]; 4],
 }
 
+/// Пирамида видимости камеры: плоскости `ax + by + cz + d >= 0` внутри.
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum {
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// Плоскости из матрицы `projection * view` (глубина wgpu 0..1).
+    pub fn from_view_proj(m: Matrix4<f32>) -> Self {
+        let (r0, r1, r2, r3) = (m.row(0), m.row(1), m.row(2), m.row(3));
+        Self {
+            planes: [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2],
+        }
+    }
+
+    /// Пересекает ли пирамиду параллелепипед `[min, max]`.
+    pub fn intersects_aabb(&self, min: Vector3<f32>, max: Vector3<f32>) -> bool {
+        self.planes.iter().all(|p| {
+            // Самая «внутренняя» вершина относительно плоскости.
+            let x = if p.x >= 0.0 { max.x } else { min.x };
+            let y = if p.y >= 0.0 { max.y } else { min.y };
+            let z = if p.z >= 0.0 { max.z } else { min.z };
+            p.x * x + p.y * y + p.z * z + p.w >= 0.0
+        })
+    }
+}
+
 pub struct Camera {
     pub position: Point3<f32>,
     pub yaw: Rad<f32>,
@@ -38,6 +65,7 @@ pub struct Camera {
 }
 
 impl Camera {
+    #[allow(clippy::too_many_arguments)]
     pub fn new<V: Into<Point3<f32>>, Y: Into<Rad<f32>>, P: Into<Rad<f32>>>(
         renderer: &Renderer,
         position: V,
@@ -74,7 +102,7 @@ impl Camera {
 
         camera.update_view();
 
-        return camera;
+        camera
     }
 
     pub fn calc_matrix(&self) -> Matrix4<f32> {
@@ -89,11 +117,8 @@ impl Camera {
     }
 
     pub fn input(&mut self, event: &DeviceEvent) {
-        match event {
-            DeviceEvent::MouseMotion { delta } => {
-                self.camera_controller.process_mouse(delta.0, delta.1);
-            }
-            _ => {}
+        if let DeviceEvent::MouseMotion { delta } = event {
+            self.camera_controller.process_mouse(delta.0, delta.1);
         }
     }
 
@@ -129,6 +154,10 @@ impl Camera {
         &self.dependants
     }
 
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_view_proj(self.dependants.view_proj.into())
+    }
+
     pub fn update_view(&mut self) {
         let view_proj: [[f32; 4]; 4] = (self.projection.calc_matrix() * self.calc_matrix()).into();
         self.dependants = Dependants { view_proj }
@@ -299,3 +328,29 @@ impl CameraController {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frustum_keeps_boxes_in_front_only() {
+        let proj = OPENGL_TO_WGPU_MATRIX * perspective(Deg(70.0), 1.0, 0.1, 100.0);
+        let view = Matrix4::look_to_rh(Point3::origin(), -Vector3::unit_z(), Vector3::unit_y());
+        let frustum = Frustum::from_view_proj(proj * view);
+        let cube = |z: f32| {
+            (
+                Vector3::new(-1.0, -1.0, z - 1.0),
+                Vector3::new(1.0, 1.0, z + 1.0),
+            )
+        };
+
+        let (min, max) = cube(-10.0);
+        assert!(frustum.intersects_aabb(min, max));
+        let (min, max) = cube(10.0);
+        assert!(!frustum.intersects_aabb(min, max));
+        let side = Vector3::new(50.0, 0.0, 0.0);
+        let (min, max) = cube(-10.0);
+        assert!(!frustum.intersects_aabb(min + side, max + side));
+    }
+}
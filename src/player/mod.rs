@@ -1,12 +1,14 @@
 pub mod camera;
 pub mod raycast;
+pub mod state;
 
 use crate::{
     core::config::{AppConfig, PlayerMode},
     render::atlas::MaterialType,
-    terrain_gen::chunk::ChunkManager,
+    terrain_gen::chunk::{ChunkManager, WORLD_MAX_Y, WORLD_MIN_Y},
 };
-use cgmath::{EuclideanSpace, InnerSpace, Vector3, vec3};
+use cgmath::{Deg, EuclideanSpace, InnerSpace, Vector3, vec3};
+use state::PlayerState;
 
 pub struct Player {
     pub camera: camera::Camera,
@@ -23,6 +25,8 @@ pub struct Player {
     gravity: f32,
     jump_speed: f32,
     max_fall_speed: f32,
+    /// Точку появления ещё не проверили: ждём чанк под игроком.
+    spawn_pending: bool,
 }
 
 impl Player {
@@ -43,6 +47,7 @@ impl Player {
             gravity: cfg.player.gravity,
             jump_speed: cfg.player.jump_speed,
             max_fall_speed: cfg.player.max_fall_speed,
+            spawn_pending: true,
         };
 
         player.position = player.camera.position.to_vec() - vec3(0.0, eye_height, 0.0);
@@ -86,14 +91,65 @@ impl Player {
 
     pub fn update(&mut self, dt: std::time::Duration, chunks: &ChunkManager) {
         let desired_velocity = self.camera.step_input(dt);
-        match self.mode {
-            PlayerMode::Creative => self.update_creative(dt, desired_velocity),
-            PlayerMode::Adventure => self.update_adventure(dt, desired_velocity, chunks),
+        // Пока чанк под игроком не загружен, ждём на месте, а не падаем сквозь мир.
+        if !self.spawn_pending || self.settle_spawn(chunks) {
+            match self.mode {
+                PlayerMode::Creative => self.update_creative(dt, desired_velocity),
+                PlayerMode::Adventure => self.update_adventure(dt, desired_velocity, chunks),
+            }
+        }
+        self.sync_camera();
+        self.camera.update_view();
+    }
+
+    /// Снимок состояния для сохранения вместе с миром.
+    pub fn state(&self, hotbar_slot: usize) -> PlayerState {
+        PlayerState {
+            position: self.position.into(),
+            yaw_deg: Deg::from(self.camera.yaw).0,
+            pitch_deg: Deg::from(self.camera.pitch).0,
+            mode: self.mode.clone(),
+            hotbar_slot,
         }
+    }
+
+    /// Восстанавливает сохранённое состояние. Позиция проверяется,
+    /// когда загрузится чанк под игроком.
+    pub fn restore(&mut self, state: &PlayerState, cfg: &AppConfig) {
+        self.position = state.position.into();
+        self.velocity = vec3(0.0, 0.0, 0.0);
+        self.camera.yaw = Deg(state.yaw_deg).into();
+        self.camera.pitch = Deg(state.pitch_deg).into();
+        self.set_mode(state.mode.clone(), cfg);
+        self.spawn_pending = true;
         self.sync_camera();
         self.camera.update_view();
     }
 
+    /// Безопасное появление: если игрок оказался в блоках (мир изменился
+    /// или сохранение старое), поднимаем его до первого свободного места.
+    /// `false`, пока чанк под игроком не загружен.
+    fn settle_spawn(&mut self, chunks: &ChunkManager) -> bool {
+        self.position.y = self
+            .position
+            .y
+            .clamp(WORLD_MIN_Y as f32 + 1.0, WORLD_MAX_Y as f32 - 1.0);
+        let feet = vec3(
+            self.position.x.floor() as i32,
+            self.position.y.floor() as i32,
+            self.position.z.floor() as i32,
+        );
+        if chunks.get_block_material(feet).is_none() {
+            return false;
+        }
+        while self.intersects_world(chunks) && self.position.y < WORLD_MAX_Y as f32 - 1.0 {
+            self.position.y = self.position.y.floor() + 1.0;
+        }
+        self.velocity = vec3(0.0, 0.0, 0.0);
+        self.spawn_pending = false;
+        true
+    }
+
     fn sync_camera(&mut self) {
         match self.view_mode {
             ViewMode::FirstPerson => {
@@ -204,10 +260,11 @@ impl Player {
         for x in x0..=x1 {
             for y in y0..=y1 {
                 for z in z0..=z1 {
-                    if let Some(mat) = chunks.get_block_material(vec3(x, y, z)) {
-                        if mat != MaterialType::AIR && mat != MaterialType::WATER {
-                            return true;
-                        }
+                    if let Some(mat) = chunks.get_block_material(vec3(x, y, z))
+                        && mat != MaterialType::AIR
+                        && mat != MaterialType::WATER
+                    {
+                        return true;
                     }
                 }
             }
@@ -121,14 +121,15 @@ impl Ray {
         // Алгоритм DDA по вокселям
         while traveled_distance < self.length {
             // Проверяем, что текущий блок не воздух/вода
-            if let Some(material) = chunks.get_block_material(current_block_pos) {
-                if material != MaterialType::AIR && material != MaterialType::WATER {
-                    return Some(BlockHit {
-                        position: current_block_pos,
-                        face,
-                        distance: traveled_distance,
-                    });
-                }
+            if let Some(material) = chunks.get_block_material(current_block_pos)
+                && material != MaterialType::AIR
+                && material != MaterialType::WATER
+            {
+                return Some(BlockHit {
+                    position: current_block_pos,
+                    face,
+                    distance: traveled_distance,
+                });
             }
 
             // Продвигаемся к соседнему блоку
@@ -0,0 +1,91 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::core::{atomic_file::write_atomic, config::PlayerMode};
+
+/// Подкаталог мира с состояниями игроков (`<name>.ron`).
+pub const PLAYERS_DIR: &str = "players";
+
+/// Сохраняемое состояние игрока в конкретном мире.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerState {
+    /// Позиция ног игрока.
+    pub position: [f32; 3],
+    pub yaw_deg: f32,
+    pub pitch_deg: f32,
+    pub mode: PlayerMode,
+    /// Выбранная ячейка панели блоков.
+    #[serde(default)]
+    pub hotbar_slot: usize,
+}
+
+impl PlayerState {
+    /// Файл игрока; имя очищается до символов, безопасных для пути.
+    pub fn path(world_dir: &Path, player_name: &str) -> PathBuf {
+        let mut file: String = player_name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        if file.is_empty() {
+            file.push('_');
+        }
+        world_dir.join(PLAYERS_DIR).join(format!("{file}.ron"))
+    }
+
+    /// Состояние игрока или `None`, если он ещё не заходил в этот мир.
+    pub fn load(world_dir: &Path, player_name: &str) -> Result<Option<Self>> {
+        let path = Self::path(world_dir, player_name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = fs::read_to_string(&path)?;
+        let state =
+            ron::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))?;
+        Ok(Some(state))
+    }
+
+    pub fn save(&self, world_dir: &Path, player_name: &str) -> Result<()> {
+        let path = Self::path(world_dir, player_name);
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        write_atomic(&path, serialized.as_bytes())
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_dir::TestDir;
+
+    #[test]
+    fn state_is_stored_per_player_name() {
+        let dir = TestDir::new("players");
+        let state = PlayerState {
+            position: [3.5, -20.0, 7.25],
+            yaw_deg: 45.0,
+            pitch_deg: -10.0,
+            mode: PlayerMode::Creative,
+            hotbar_slot: 3,
+        };
+        state.save(&dir, "Alice").unwrap();
+        let alice = PlayerState::load(&dir, "Alice").unwrap().unwrap();
+        let bob = PlayerState::load(&dir, "Bob").unwrap();
+        let escaped = PlayerState::path(&dir, "../evil");
+
+        assert_eq!(alice.position, state.position);
+        assert_eq!(alice.hotbar_slot, 3);
+        assert!(bob.is_none());
+        assert!(escaped.starts_with(dir.join(PLAYERS_DIR)));
+    }
+}
@@ -1,6 +1,5 @@
 use anyhow::*;
 use serde::{Deserialize, Serialize};
-use std::sync::OnceLock;
 
 use crate::render::texture::*;
 use crate::terrain_gen::block::*;
@@ -16,96 +15,116 @@ pub enum MaterialType {
     WATER,
     AIR,
     DEBUG,
+    SAND,
+    SNOW,
+    LOG,
+    LEAVES,
+    STONE,
+    /// Рудные блоки (уголь, железо, золото).
+    COAL,
+    IRON,
+    GOLD,
+    /// Светящийся блок лавы.
+    LAVA,
 }
 
 impl MaterialType {
+    /// Все материалы в порядке объявления.
+    pub const ALL: [MaterialType; 15] = [
+        MaterialType::DIRT,
+        MaterialType::GRASS,
+        MaterialType::ROCK,
+        MaterialType::WATER,
+        MaterialType::AIR,
+        MaterialType::DEBUG,
+        MaterialType::SAND,
+        MaterialType::SNOW,
+        MaterialType::LOG,
+        MaterialType::LEAVES,
+        MaterialType::STONE,
+        MaterialType::COAL,
+        MaterialType::IRON,
+        MaterialType::GOLD,
+        MaterialType::LAVA,
+    ];
+
+    /// Материал по имени без учёта регистра (`"rock"`, `"GRASS"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|m| format!("{m:?}").eq_ignore_ascii_case(name))
+    }
+
+    /// Сквозь блок видно соседей: воздух, вода и листва (рисуется с вырезом).
     pub fn is_transparent(&self) -> bool {
+        matches!(
+            self,
+            MaterialType::AIR | MaterialType::WATER | MaterialType::LEAVES
+        )
+    }
+
+    /// Жидкость: рисуется отдельным полупрозрачным проходом.
+    pub fn is_fluid(&self) -> bool {
+        *self == MaterialType::WATER
+    }
+
+    /// Уровень света, который излучает блок (0..=15).
+    pub fn light_emission(&self) -> u8 {
+        match self {
+            MaterialType::LAVA => 15,
+            _ => 0,
+        }
+    }
+
+    /// Насколько блок гасит проходящий свет сверх обычного шага в 1;
+    /// 15 — не пропускает вовсе.
+    pub fn light_opacity(&self) -> u8 {
         match self {
-            MaterialType::AIR => true,   // Возвращает true для AIR
-            MaterialType::WATER => true, // Возвращает true для WATER
-            _ => false,                  // Возвращает false для остальных материалов
+            MaterialType::AIR => 0,
+            MaterialType::WATER | MaterialType::LEAVES => 1,
+            _ => 15,
         }
     }
 }
 
+/// Тайлов по каждой стороне атласа.
+pub const ATLAS_TILES: f32 = 16.0;
+
 impl MaterialType {
-    pub fn get_texture_coordinates(
-        &self,
-        texture_corner: [u32; 2],
-        quad_side: Direction,
-    ) -> [f32; 2] {
-        let atlas_size = atlas_size_px();
-        // Сетка атласа считается 16x16 тайлов; размер тайла вычисляем динамически,
-        // чтобы поддерживать атласы 256px и 512px без ручного пересчёта UV.
-        let tile_size = atlas_size / 16.0;
+    /// Тайл атласа (столбец, строка) для грани `quad_side`.
+    fn atlas_tile(&self, quad_side: Direction) -> [f32; 2] {
         match self {
             MaterialType::GRASS => match quad_side {
-                Direction::TOP => {
-                    atlas_pos_to_coordinates([0.0, 0.0], texture_corner, tile_size, atlas_size)
-                }
-                Direction::BOTTOM => {
-                    atlas_pos_to_coordinates([2.0, 0.0], texture_corner, tile_size, atlas_size)
-                }
-                Direction::RIGHT => {
-                    atlas_pos_to_coordinates([3.0, 0.0], texture_corner, tile_size, atlas_size)
-                }
-                Direction::LEFT => {
-                    atlas_pos_to_coordinates([3.0, 0.0], texture_corner, tile_size, atlas_size)
-                }
-                Direction::FRONT => {
-                    atlas_pos_to_coordinates([3.0, 0.0], texture_corner, tile_size, atlas_size)
-                }
-                Direction::BACK => {
-                    atlas_pos_to_coordinates([3.0, 0.0], texture_corner, tile_size, atlas_size)
-                }
+                Direction::TOP => [0.0, 0.0],
+                Direction::BOTTOM => [2.0, 0.0],
+                _ => [3.0, 0.0],
+            },
+            MaterialType::DIRT => [2.0, 0.0],
+            MaterialType::ROCK => [0.0, 1.0],
+            MaterialType::WATER => [13.0, 0.0],
+            MaterialType::SAND => [2.0, 1.0],
+            MaterialType::SNOW => [3.0, 2.0],
+            MaterialType::LOG => match quad_side {
+                Direction::TOP | Direction::BOTTOM => [5.0, 1.0],
+                _ => [4.0, 1.0],
             },
-            MaterialType::DIRT => {
-                atlas_pos_to_coordinates([2.0, 0.0], texture_corner, tile_size, atlas_size)
-            }
-            MaterialType::ROCK => {
-                atlas_pos_to_coordinates([0.0, 1.0], texture_corner, tile_size, atlas_size)
-            }
-            MaterialType::WATER => {
-                atlas_pos_to_coordinates([13.0, 0.0], texture_corner, tile_size, atlas_size)
-            }
+            MaterialType::LEAVES => [6.0, 1.0],
+            MaterialType::STONE => [1.0, 0.0],
+            MaterialType::COAL => [2.0, 2.0],
+            MaterialType::IRON => [1.0, 2.0],
+            MaterialType::GOLD => [0.0, 2.0],
+            MaterialType::LAVA => [14.0, 1.0],
             MaterialType::AIR => [0.0, 0.0],
-            MaterialType::DEBUG => {
-                atlas_pos_to_coordinates([5.0, 0.0], texture_corner, tile_size, atlas_size)
-            } // match quad_side {
-              //     Direction::TOP | Direction::BOTTOM => {
-              //         atlas_pos_to_coordinates([5.0, 1.0], texture_corner, tile_size, atlas_size)
-              //     }
-              //     _ => atlas_pos_to_coordinates([4.0, 1.0], texture_corner, tile_size, atlas_size),
-              // },
+            MaterialType::DEBUG => [5.0, 0.0],
         }
     }
-}
-
-static ATLAS_SIZE_PX: OnceLock<f32> = OnceLock::new();
-const DEFAULT_ATLAS_PX: f32 = 256.0;
-
-fn atlas_size_px() -> f32 {
-    *ATLAS_SIZE_PX.get_or_init(|| DEFAULT_ATLAS_PX)
-}
-
-fn atlas_pos_to_coordinates(
-    atlas_pos: [f32; 2],
-    texture_corner: [u32; 2],
-    tile_size: f32,
-    atlas_size: f32,
-) -> [f32; 2] {
-    let mut pixel_x = atlas_pos[0] * tile_size;
-    let mut pixel_y = atlas_pos[1] * tile_size;
-
-    if texture_corner[0] == 1 {
-        pixel_x += tile_size - 1.0;
-    }
 
-    if texture_corner[1] == 1 {
-        pixel_y += tile_size;
+    /// UV левого верхнего угла тайла. Сетка атласа — 16x16 тайлов при любом
+    /// размере картинки; шейдер повторяет тайл по локальным координатам грани.
+    pub fn tile_origin(&self, quad_side: Direction) -> [f32; 2] {
+        let [column, row] = self.atlas_tile(quad_side);
+        [column / ATLAS_TILES, row / ATLAS_TILES]
     }
-
-    return [pixel_x / atlas_size, pixel_y / atlas_size];
 }
 
 pub struct Atlas {
@@ -120,8 +139,7 @@ impl Atlas {
         layouts: &GlobalsLayouts,
     ) -> Result<Self> {
         let diffuse_bytes = include_bytes!("../../assets/images/textures_atlas.png");
-        let texture = Texture::from_bytes(&device, &queue, diffuse_bytes, "blocks.png").unwrap();
-        let _ = ATLAS_SIZE_PX.set(texture.width as f32);
+        let texture = Texture::from_bytes(device, queue, diffuse_bytes, "blocks.png").unwrap();
 
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout: &layouts.atlas_layout,
@@ -24,6 +24,10 @@ impl<T: Copy + Pod> Buffer<T> {
     pub fn len(&self) -> usize {
         self.len
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 }
 
 pub struct DynamicBuffer<T: Copy + Pod>(Buffer<T>);
@@ -49,6 +53,10 @@ impl<T: Copy + Pod> DynamicBuffer<T> {
         self.0.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+
     pub fn update(&self, queue: &wgpu::Queue, vals: &[T], offset: usize) {
         if !vals.is_empty() {
             queue.write_buffer(
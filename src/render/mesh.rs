@@ -10,6 +10,12 @@ pub struct Mesh<V: Vertex> {
     pub indices: Vec<u32>,
 }
 
+impl<V: Vertex> Default for Mesh<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<V: Vertex> Mesh<V> {
     /// Создать новый `Mesh`.
     pub fn new() -> Self {
@@ -52,6 +58,7 @@ impl<V: Vertex> Mesh<V> {
         self.indices.extend(&quad.get_indices(base_index));
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn clone(&self) -> Self {
         Self {
             verts: self.verts.clone(),
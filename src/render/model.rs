@@ -33,6 +33,10 @@ impl<V: Vertex> Model<V> {
     pub fn len(&self) -> u16 {
         self.vbuf.len() as u16
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.vbuf.is_empty()
+    }
 }
 
 /// Меш, отправленный на GPU, с возможностью перевыделения буферов.
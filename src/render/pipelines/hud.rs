@@ -38,7 +38,7 @@ pub fn create_hud_pipeline(
         push_constant_ranges: &[],
     });
 
-    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: Some("Render generic Pipeline"),
         layout: Some(&pipeline_layout),
         primitive: wgpu::PrimitiveState {
@@ -88,7 +88,5 @@ pub fn create_hud_pipeline(
         },
         multiview: None,
         cache: None,
-    });
-
-    pipeline
+    })
 }
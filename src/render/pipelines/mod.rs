@@ -17,6 +17,8 @@ pub struct Globals {
     camera_pos: [f32; 4],
     /// Начало и конец тумана в мировых единицах (линейная интерполяция).
     fog: [f32; 4],
+    /// Сдвиг текстуры воды в тайлах и непрозрачность воды.
+    water: [f32; 4],
 }
 
 impl Globals {
@@ -28,11 +30,14 @@ impl Globals {
         fog_start: f32,
         fog_end: f32,
         sky_color: [f32; 3],
+        water_phase: f32,
+        water_opacity: f32,
     ) -> Self {
         Self {
             view_proj,
             camera_pos: [camera_pos[0], camera_pos[1], camera_pos[2], sky_color[2]],
             fog: [fog_start, fog_end, sky_color[0], sky_color[1]],
+            water: [water_phase, water_opacity, 0.0, 0.0],
         }
     }
 }
@@ -45,6 +50,8 @@ impl Default for Globals {
             0.0,
             1.0,
             [0.6, 0.75, 0.9],
+            0.0,
+            1.0,
         )
     }
 }
@@ -148,17 +155,15 @@ impl GlobalsLayouts {
     }
 
     pub fn bind(&self, device: &wgpu::Device, global_model: &GlobalModel) -> BindGroup {
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: None,
             layout: &self.globals,
             entries: &Self::base_global_entries(global_model),
-        });
-
-        bind_group
+        })
     }
 
     pub fn bind_atlas_texture(&self, device: &wgpu::Device, texture: &Texture) -> BindGroup {
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: None,
             layout: &self.globals,
             entries: &[
@@ -171,9 +176,7 @@ impl GlobalsLayouts {
                     resource: wgpu::BindingResource::Sampler(&texture.sampler),
                 },
             ],
-        });
-
-        bind_group
+        })
     }
 
     // Создание bind group для HUD
@@ -8,12 +8,28 @@ use crate::render::{Vertex, texture::Texture};
 #[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct BlockVertex {
     pub pos: [f32; 3],
+    /// Координаты внутри грани в блоках: склеенная грань 4x2 получает `0..4, 0..2`,
+    /// и тайл повторяется на каждом блоке.
     pub texture_coordinates: [f32; 2],
+    /// UV левого верхнего угла тайла в атласе.
+    pub tile: [f32; 2],
+    /// Затенение угла соседними блоками (AO), 1.0 — без затенения.
+    pub ao: f32,
+    /// Яркость грани по её направлению.
+    pub face_shade: f32,
+    /// Свет неба и блоков у вершины, `0.0..=1.0`.
+    pub light: [f32; 2],
 }
 
 impl BlockVertex {
-    const ATTRIBS: [wgpu::VertexAttribute; 2] =
-        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2];
+    const ATTRIBS: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+        0 => Float32x3,
+        1 => Float32x2,
+        2 => Float32x2,
+        3 => Float32,
+        4 => Float32,
+        5 => Float32x2,
+    ];
 }
 
 impl Vertex for BlockVertex {
@@ -32,6 +48,60 @@ pub fn create_terrain_pipeline(
     global_layout: &GlobalsLayouts,
     shader: wgpu::ShaderModule,
     config: &wgpu::SurfaceConfiguration,
+) -> RenderPipeline {
+    block_pipeline(
+        device,
+        global_layout,
+        &shader,
+        config,
+        BlockPass {
+            label: "Render generic Pipeline",
+            fragment: "fs_main",
+            blend: wgpu::BlendState::REPLACE,
+            depth_write: true,
+            cull_mode: Some(wgpu::Face::Back),
+        },
+    )
+}
+
+/// Полупрозрачные жидкости поверх непрозрачного террейна: смешивание по альфе,
+/// глубина только читается, поверхность видна и снизу.
+pub fn create_fluid_pipeline(
+    device: &wgpu::Device,
+    global_layout: &GlobalsLayouts,
+    shader: &wgpu::ShaderModule,
+    config: &wgpu::SurfaceConfiguration,
+) -> RenderPipeline {
+    block_pipeline(
+        device,
+        global_layout,
+        shader,
+        config,
+        BlockPass {
+            label: "Fluid Pipeline",
+            fragment: "fs_fluid",
+            blend: wgpu::BlendState::ALPHA_BLENDING,
+            depth_write: false,
+            cull_mode: None,
+        },
+    )
+}
+
+/// Чем проходы блоков отличаются друг от друга.
+struct BlockPass {
+    label: &'static str,
+    fragment: &'static str,
+    blend: wgpu::BlendState,
+    depth_write: bool,
+    cull_mode: Option<wgpu::Face>,
+}
+
+fn block_pipeline(
+    device: &wgpu::Device,
+    global_layout: &GlobalsLayouts,
+    shader: &wgpu::ShaderModule,
+    config: &wgpu::SurfaceConfiguration,
+    pass: BlockPass,
 ) -> RenderPipeline {
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Render Figure Pipeline Layout"),
@@ -43,14 +113,14 @@ pub fn create_terrain_pipeline(
         push_constant_ranges: &[],
     });
 
-    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label: Some("Render generic Pipeline"),
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(pass.label),
         layout: Some(&pipeline_layout),
         primitive: wgpu::PrimitiveState {
             topology: wgpu::PrimitiveTopology::TriangleList,
             strip_index_format: None,
             front_face: wgpu::FrontFace::Ccw,
-            cull_mode: Some(wgpu::Face::Back),
+            cull_mode: pass.cull_mode,
             // Значения, отличные от Fill, требуют Features::NON_FILL_POLYGON_MODE
             polygon_mode: wgpu::PolygonMode::Fill,
             // Требуется Features::DEPTH_CLIP_CONTROL
@@ -59,24 +129,24 @@ pub fn create_terrain_pipeline(
             conservative: false,
         },
         vertex: wgpu::VertexState {
-            module: &shader,
+            module: shader,
             entry_point: Some("vs_main"),
             buffers: &[BlockVertex::desc()],
             compilation_options: Default::default(),
         },
         fragment: Some(wgpu::FragmentState {
-            module: &shader,
-            entry_point: Some("fs_main"),
+            module: shader,
+            entry_point: Some(pass.fragment),
             targets: &[Some(wgpu::ColorTargetState {
                 format: config.format,
-                blend: Some(wgpu::BlendState::REPLACE),
+                blend: Some(pass.blend),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
             compilation_options: Default::default(),
         }),
         depth_stencil: Some(wgpu::DepthStencilState {
             format: Texture::DEPTH_FORMAT,
-            depth_write_enabled: true,
+            depth_write_enabled: pass.depth_write,
             depth_compare: wgpu::CompareFunction::Less,
             stencil: wgpu::StencilState::default(),
             bias: wgpu::DepthBiasState::default(),
@@ -88,7 +158,5 @@ pub fn create_terrain_pipeline(
         },
         multiview: None,
         cache: None,
-    });
-
-    pipeline
+    })
 }
@@ -88,8 +88,7 @@ impl<'a> Renderer<'a> {
             .formats
             .iter()
             .copied()
-            .filter(|f| f.is_srgb())
-            .next()
+            .find(|f| f.is_srgb())
             .unwrap_or(surface_caps.formats[0]);
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
@@ -176,6 +175,16 @@ impl<'a> Renderer<'a> {
         }
     }
 
+    /// Цвет очистки кадра; совпадает с цветом тумана.
+    pub fn set_clear_color(&mut self, color: [f32; 3]) {
+        self.clear_color = wgpu::Color {
+            r: color[0] as f64,
+            g: color[1] as f64,
+            b: color[2] as f64,
+            a: 1.0,
+        };
+    }
+
     pub fn bind_globals(&self, global_model: &GlobalModel) -> BindGroup {
         self.layouts.global.bind(&self.device, global_model)
     }
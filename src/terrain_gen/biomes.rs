@@ -1,10 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use crate::render::atlas::MaterialType;
+
+use super::{features::FeatureDensity, noise::NoiseGenerator, preset::WorldGenPreset};
+
+/// Форма рельефа, которую даёт фрактальный шум биома.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TerrainShape {
+    /// Классический fBm: сумма октав Перлина, мягкие холмы.
+    Fbm,
+    /// Ridged multifractal: острые хребты, подходит для гор.
+    Ridged,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct BiomeParameters {
     pub base_height: f32,
     pub frequency: f32,
     pub amplitude: f32,
+    /// Количество октав фрактального шума (минимум 1).
     pub octaves: u32,
+    /// Во сколько раз падает амплитуда каждой следующей октавы.
     pub persistence: f32,
+    /// Во сколько раз растёт частота каждой следующей октавы.
     pub lacunarity: f32,
+    pub shape: TerrainShape,
+    /// Сила доменного искажения в блоках; `0.0` отключает warping.
+    pub warp_strength: f32,
+    /// Толщина почвы под поверхностью; глубже начинается камень.
+    pub soil_depth: usize,
 }
 
 pub const PRAIRIE_PARAMS: BiomeParameters = BiomeParameters {
@@ -12,8 +36,11 @@ pub const PRAIRIE_PARAMS: BiomeParameters = BiomeParameters {
     frequency: 0.05,
     amplitude: 7.0,
     octaves: 3,
-    persistence: 0.05,
+    persistence: 0.45,
     lacunarity: 2.0,
+    shape: TerrainShape::Fbm,
+    warp_strength: 0.0,
+    soil_depth: 5,
 };
 
 pub const MOUNTAIN_PARAMS: BiomeParameters = BiomeParameters {
@@ -21,6 +48,187 @@ pub const MOUNTAIN_PARAMS: BiomeParameters = BiomeParameters {
     frequency: 0.03,
     amplitude: 35.0,
     octaves: 4,
-    persistence: 0.05,
+    persistence: 0.5,
     lacunarity: 2.0,
+    shape: TerrainShape::Ridged,
+    warp_strength: 12.0,
+    soil_depth: 2,
 };
+
+/// Описание биома в пресете генерации.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BiomeDef {
+    pub name: String,
+    /// Опорная точка биома в пространстве (температура, влажность), оба в `[-1, 1]`.
+    pub climate: (f32, f32),
+    pub terrain: BiomeParameters,
+    /// Верхний блок колонки.
+    pub surface: MaterialType,
+    /// Блок под поверхностью (несколько слоёв), ниже — земля и камень.
+    pub filler: MaterialType,
+    /// Заменять ли поверхность у воды пляжным блоком.
+    #[serde(default = "default_beaches")]
+    pub beaches: bool,
+    #[serde(default)]
+    pub features: FeatureDensity,
+}
+
+fn default_beaches() -> bool {
+    true
+}
+
+/// Результат выборки карты биомов для одной колонки мира.
+#[derive(Copy, Clone, Debug)]
+pub struct ColumnSample {
+    /// Высота поверхности, смешанная между соседними биомами.
+    pub height: f32,
+    /// Индекс биома с наибольшим весом в `WorldGenPreset::biomes` —
+    /// от него берутся блоки поверхности.
+    pub biome: usize,
+    /// Уровень воды над колонкой: уровень моря или собственный уровень озера.
+    pub water_level: usize,
+}
+
+impl ColumnSample {
+    /// Верхний блок с учётом снеговой линии и пляжей у воды (моря, рек и озёр).
+    pub fn surface_block(&self, preset: &WorldGenPreset, surface_y: i32) -> MaterialType {
+        let biome = &preset.biomes[self.biome];
+        if surface_y >= preset.surface.snow_line as i32 {
+            preset.surface.snow_block
+        } else if surface_y <= self.water_level as i32 + 1 && biome.beaches {
+            preset.surface.beach_block
+        } else {
+            biome.surface
+        }
+    }
+}
+
+/// Выбирает биом по низкочастотному шуму температуры и влажности
+/// и плавно смешивает высоты соседних биомов на границах.
+/// Реки и озёра сюда не входят — их добавляет `WaterMap::sample`.
+pub fn sample_column(
+    preset: &WorldGenPreset,
+    noise: &NoiseGenerator,
+    x: f32,
+    z: f32,
+) -> ColumnSample {
+    let climate = &preset.climate;
+    let (temperature, humidity) = noise.get_climate(x, z, climate.frequency);
+    let sigma2 = climate.blend_sigma * climate.blend_sigma;
+
+    let mut weights = Vec::with_capacity(preset.biomes.len());
+    let mut total = 0.0;
+    let mut nearest = (0, f32::INFINITY);
+    for (i, biome) in preset.biomes.iter().enumerate() {
+        let (t, h) = biome.climate;
+        let d2 = (temperature - t).powi(2) + (humidity - h).powi(2);
+        if d2 < nearest.1 {
+            nearest = (i, d2);
+        }
+        let w = (-d2 / sigma2).exp();
+        weights.push(w);
+        total += w;
+    }
+
+    // Все веса ушли в ноль (точка далеко от всех биомов или нулевая ширина
+    // перехода): без смешивания берём ближайший по климату биом.
+    if total <= 0.0 || !total.is_finite() {
+        let params = &preset.biomes[nearest.0].terrain;
+        return ColumnSample {
+            height: params.base_height + noise.get_height(x, z, params),
+            biome: nearest.0,
+            water_level: preset.sea_level(),
+        };
+    }
+
+    let mut height = 0.0;
+    let mut used = 0.0;
+    let mut dominant = (0, 0.0);
+    for (i, biome) in preset.biomes.iter().enumerate() {
+        let w = weights[i] / total;
+        if w > dominant.1 {
+            dominant = (i, w);
+        }
+        if w < climate.min_blend_weight {
+            continue;
+        }
+        let params = &biome.terrain;
+        height += (params.base_height + noise.get_height(x, z, params)) * w;
+        used += w;
+    }
+
+    // Если веса совсем вырождены, берём чистый доминирующий биом.
+    if used <= 0.0 {
+        let params = &preset.biomes[dominant.0].terrain;
+        height = params.base_height + noise.get_height(x, z, params);
+        used = 1.0;
+    }
+
+    ColumnSample {
+        height: height / used,
+        biome: dominant.0,
+        water_level: preset.sea_level(),
+    }
+}
+
+/// Максимально возможная высота поверхности среди всех биомов пресета.
+pub fn max_surface_height(preset: &WorldGenPreset) -> usize {
+    preset
+        .biomes
+        .iter()
+        .map(|b| b.terrain.base_height + b.terrain.amplitude)
+        .fold(0.0f32, f32::max)
+        .ceil() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_seed_contains_several_biomes() {
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(10);
+        let mut seen = Vec::new();
+        for i in -40..40 {
+            for j in -40..40 {
+                let sample = sample_column(&preset, &noise, i as f32 * 32.0, j as f32 * 32.0);
+                if !seen.contains(&sample.biome) {
+                    seen.push(sample.biome);
+                }
+            }
+        }
+        assert!(seen.len() >= 3, "only {:?} found", seen);
+    }
+
+    #[test]
+    fn heights_blend_smoothly_across_borders() {
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(10);
+        let mut borders = 0;
+        for i in -2000..2000 {
+            let x = i as f32;
+            let a = sample_column(&preset, &noise, x, 0.0);
+            let b = sample_column(&preset, &noise, x + 1.0, 0.0);
+            if a.biome != b.biome {
+                borders += 1;
+                let step = (a.height - b.height).abs();
+                assert!(step < 6.0, "cliff at biome border x={x}: {step}");
+            }
+        }
+        assert!(borders > 0);
+    }
+
+    #[test]
+    fn vanishing_weights_fall_back_to_the_nearest_biome() {
+        let noise = NoiseGenerator::new(10);
+        for sigma in [0.0, 1e-6] {
+            let mut preset = WorldGenPreset::default();
+            preset.climate.blend_sigma = sigma;
+            for i in -50..50 {
+                let sample = sample_column(&preset, &noise, i as f32 * 97.0, 13.0);
+                assert!(sample.height.is_finite(), "sigma {sigma} at {i}");
+            }
+        }
+    }
+}
@@ -4,21 +4,32 @@ use crate::render::atlas::MaterialType;
 
 use crate::render::pipelines::terrain::BlockVertex;
 
+/// Вершина грани размера `size` (в блоках) с углом в `position`. Единичные
+/// `pos` и `texture_corners` растягиваются на размер грани, так что тайл
+/// атласа повторяется на каждом блоке склеенной грани.
 pub fn quad_vertex(
     pos: [i8; 3],
     material_type: MaterialType,
     texture_corners: [u32; 2],
     position: [i32; 3],
+    size: [i32; 3],
     quad_side: Direction,
 ) -> BlockVertex {
-    let tc = material_type.get_texture_coordinates(texture_corners, quad_side);
+    let (u_axis, v_axis) = quad_side.texture_axes();
     BlockVertex {
         pos: [
-            pos[0] as f32 + position[0] as f32,
-            pos[1] as f32 + position[1] as f32,
-            pos[2] as f32 + position[2] as f32,
+            (pos[0] as i32 * size[0] + position[0]) as f32,
+            (pos[1] as i32 * size[1] + position[1]) as f32,
+            (pos[2] as i32 * size[2] + position[2]) as f32,
         ],
-        texture_coordinates: [tc[0] as f32, tc[1] as f32],
+        texture_coordinates: [
+            (texture_corners[0] as i32 * size[u_axis]) as f32,
+            (texture_corners[1] as i32 * size[v_axis]) as f32,
+        ],
+        tile: material_type.tile_origin(quad_side),
+        ao: 1.0,
+        face_shade: 1.0,
+        light: [1.0, 0.0],
     }
 }
 
@@ -53,63 +64,117 @@ impl Direction {
         }
     }
 
-    pub fn get_vertices(self, material_type: MaterialType, position: [i32; 3]) -> [BlockVertex; 4] {
+    /// Оси мира, вдоль которых на грани идут текстурные `u` и `v`.
+    pub fn texture_axes(self) -> (usize, usize) {
+        match self {
+            Direction::TOP | Direction::BOTTOM => (0, 2),
+            Direction::RIGHT | Direction::LEFT => (2, 1),
+            Direction::FRONT | Direction::BACK => (0, 1),
+        }
+    }
+
+    /// Яркость грани по направлению: сверху светлее всего, снизу темнее.
+    pub fn brightness(self) -> f32 {
+        match self {
+            Direction::TOP => 1.0,
+            Direction::BOTTOM => 0.5,
+            Direction::RIGHT | Direction::LEFT => 0.6,
+            Direction::FRONT | Direction::BACK => 0.8,
+        }
+    }
+
+    /// Углы грани в порядке обхода: смещение вершины внутри блока и угол текстуры.
+    pub fn corners(self) -> [([i8; 3], [u32; 2]); 4] {
         match self {
             Direction::TOP => [
-                quad_vertex([0, 1, 0], material_type, [0, 0], position, self),
-                quad_vertex([0, 1, 1], material_type, [0, 1], position, self),
-                quad_vertex([1, 1, 1], material_type, [1, 1], position, self),
-                quad_vertex([1, 1, 0], material_type, [1, 0], position, self),
+                ([0, 1, 0], [0, 0]),
+                ([0, 1, 1], [0, 1]),
+                ([1, 1, 1], [1, 1]),
+                ([1, 1, 0], [1, 0]),
             ],
             Direction::BOTTOM => [
-                quad_vertex([0, 0, 1], material_type, [0, 0], position, self),
-                quad_vertex([0, 0, 0], material_type, [0, 1], position, self),
-                quad_vertex([1, 0, 0], material_type, [1, 1], position, self),
-                quad_vertex([1, 0, 1], material_type, [1, 0], position, self),
+                ([0, 0, 1], [0, 0]),
+                ([0, 0, 0], [0, 1]),
+                ([1, 0, 0], [1, 1]),
+                ([1, 0, 1], [1, 0]),
             ],
             Direction::RIGHT => [
-                quad_vertex([1, 1, 1], material_type, [0, 0], position, self),
-                quad_vertex([1, 0, 1], material_type, [0, 1], position, self),
-                quad_vertex([1, 0, 0], material_type, [1, 1], position, self),
-                quad_vertex([1, 1, 0], material_type, [1, 0], position, self),
+                ([1, 1, 1], [0, 0]),
+                ([1, 0, 1], [0, 1]),
+                ([1, 0, 0], [1, 1]),
+                ([1, 1, 0], [1, 0]),
             ],
             Direction::LEFT => [
-                quad_vertex([0, 1, 0], material_type, [0, 0], position, self),
-                quad_vertex([0, 0, 0], material_type, [0, 1], position, self),
-                quad_vertex([0, 0, 1], material_type, [1, 1], position, self),
-                quad_vertex([0, 1, 1], material_type, [1, 0], position, self),
+                ([0, 1, 0], [0, 0]),
+                ([0, 0, 0], [0, 1]),
+                ([0, 0, 1], [1, 1]),
+                ([0, 1, 1], [1, 0]),
             ],
             Direction::FRONT => [
-                quad_vertex([0, 1, 1], material_type, [0, 0], position, self),
-                quad_vertex([0, 0, 1], material_type, [0, 1], position, self),
-                quad_vertex([1, 0, 1], material_type, [1, 1], position, self),
-                quad_vertex([1, 1, 1], material_type, [1, 0], position, self),
+                ([0, 1, 1], [0, 0]),
+                ([0, 0, 1], [0, 1]),
+                ([1, 0, 1], [1, 1]),
+                ([1, 1, 1], [1, 0]),
             ],
             Direction::BACK => [
-                quad_vertex([1, 1, 0], material_type, [0, 0], position, self),
-                quad_vertex([1, 0, 0], material_type, [0, 1], position, self),
-                quad_vertex([0, 0, 0], material_type, [1, 1], position, self),
-                quad_vertex([0, 1, 0], material_type, [1, 0], position, self),
+                ([1, 1, 0], [0, 0]),
+                ([1, 0, 0], [0, 1]),
+                ([0, 0, 0], [1, 1]),
+                ([0, 1, 0], [1, 0]),
             ],
         }
     }
+
+    pub fn get_vertices(
+        self,
+        material_type: MaterialType,
+        position: [i32; 3],
+        size: [i32; 3],
+    ) -> [BlockVertex; 4] {
+        self.corners()
+            .map(|(pos, texture)| quad_vertex(pos, material_type, texture, position, size, self))
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
 pub struct Quad {
     pub vertices: [BlockVertex; 4],
     pub side: Direction,
+    /// Делить грань по диагонали 1-3 вместо 0-2, чтобы AO не тянулся полосой.
+    pub flipped: bool,
 }
 
 impl Quad {
     pub fn new(material_type: MaterialType, quad_side: Direction, position: [i32; 3]) -> Self {
+        Self::sized(material_type, quad_side, position, [1, 1, 1])
+    }
+
+    /// Грань, склеенная из нескольких блоков: `size` — протяжённость по x, y, z,
+    /// вдоль нормали всегда 1.
+    pub fn sized(
+        material_type: MaterialType,
+        quad_side: Direction,
+        position: [i32; 3],
+        size: [i32; 3],
+    ) -> Self {
         Self {
-            vertices: quad_side.get_vertices(material_type, position),
+            vertices: quad_side.get_vertices(material_type, position, size),
             side: quad_side,
+            flipped: false,
         }
     }
 
     pub fn get_indices_v(&self, vertex_offset: u32) -> [u32; 6] {
+        if self.flipped {
+            return [
+                vertex_offset + 1,
+                vertex_offset + 2,
+                vertex_offset + 3,
+                vertex_offset + 3,
+                vertex_offset,
+                vertex_offset + 1,
+            ];
+        }
         [
             vertex_offset,
             vertex_offset + 1,
@@ -123,12 +188,12 @@ impl Quad {
     pub fn get_indices(&self, i: u32) -> [u32; 6] {
         let displacement = i * 4;
         [
-            0 + displacement,
+            displacement,
             1 + displacement,
             2 + displacement,
             2 + displacement,
             3 + displacement,
-            0 + displacement,
+            displacement,
         ]
     }
 }
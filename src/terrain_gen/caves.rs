@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use super::noise::NoiseGenerator;
+
+/// Параметры прохода вырезания пещер.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct CaveParameters {
+    /// Частота двух 3D-полей, на пересечении нулей которых идут тоннели-«спагетти».
+    pub spaghetti_frequency: f32,
+    /// Толщина тоннеля: порог по модулю обоих полей.
+    pub spaghetti_thickness: f32,
+    /// Частота поля крупных полостей («сыр»).
+    pub cheese_frequency: f32,
+    /// Порог поля полостей; чем ближе к 1.0, тем реже пустоты.
+    pub cheese_threshold: f32,
+    /// Сжатие шума по вертикали (>1 — пещеры вытянуты по горизонтали).
+    pub vertical_squash: f32,
+    /// Ниже этой высоты пещеры не вырезаются, чтобы мир не имел дыр в дне.
+    pub min_y: i32,
+    /// Сколько блоков под поверхностью защищено рядом с водой,
+    /// чтобы озёра не висели над пещерами.
+    pub water_margin: usize,
+}
+
+pub const DEFAULT_CAVES: CaveParameters = CaveParameters {
+    spaghetti_frequency: 0.045,
+    spaghetti_thickness: 0.08,
+    cheese_frequency: 0.03,
+    cheese_threshold: 0.6,
+    vertical_squash: 1.6,
+    min_y: -56,
+    water_margin: 4,
+};
+
+impl Default for CaveParameters {
+    fn default() -> Self {
+        DEFAULT_CAVES
+    }
+}
+
+/// Решает, вырезается ли блок в мировой точке. Результат зависит только от
+/// сида и мировых координат, поэтому паддинг соседних чанков совпадает.
+pub fn is_carved(
+    noise: &NoiseGenerator,
+    params: &CaveParameters,
+    world_x: f32,
+    y: i32,
+    world_z: f32,
+    surface_height: i32,
+    water_level: usize,
+) -> bool {
+    if y < params.min_y || y > surface_height {
+        return false;
+    }
+    // Колонка у воды или под ней: не вскрываем поверхность, иначе вода повиснет.
+    if surface_height <= water_level as i32 + 1 && y + params.water_margin as i32 > surface_height {
+        return false;
+    }
+
+    let y = y as f32 * params.vertical_squash;
+    let (a, b) = noise.get_spaghetti(world_x, y, world_z, params.spaghetti_frequency);
+    if a.abs() < params.spaghetti_thickness && b.abs() < params.spaghetti_thickness {
+        return true;
+    }
+    noise.get_cheese(world_x, y, world_z, params.cheese_frequency) > params.cheese_threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn carved_fraction(seed: u32) -> (usize, usize) {
+        let noise = NoiseGenerator::new(seed);
+        let mut carved = 0;
+        let mut total = 0;
+        for x in 0..64 {
+            for z in 0..64 {
+                for y in -40..40 {
+                    total += 1;
+                    if is_carved(&noise, &DEFAULT_CAVES, x as f32, y, z as f32, 40, 9) {
+                        carved += 1;
+                    }
+                }
+            }
+        }
+        (carved, total)
+    }
+
+    #[test]
+    fn carving_is_deterministic_and_sparse() {
+        let (carved, total) = carved_fraction(10);
+        assert_eq!((carved, total), carved_fraction(10));
+        assert!(carved > 0, "no caves at all");
+        assert!(carved * 3 < total, "too much carved: {carved}/{total}");
+    }
+
+    #[test]
+    fn bottom_and_air_are_never_carved() {
+        let noise = NoiseGenerator::new(3);
+        for x in 0..64 {
+            let below = DEFAULT_CAVES.min_y - 1;
+            assert!(!is_carved(
+                &noise,
+                &DEFAULT_CAVES,
+                x as f32,
+                below,
+                0.0,
+                40,
+                9
+            ));
+            assert!(!is_carved(&noise, &DEFAULT_CAVES, x as f32, 41, 0.0, 40, 9));
+        }
+    }
+}
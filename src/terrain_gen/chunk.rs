@@ -1,33 +1,61 @@
 use std::{
     fs,
+    ops::Range,
     path::Path,
     sync::{Arc, RwLock},
 };
 
-use anyhow::{Result, bail};
+use anyhow::Result;
 use cgmath::Vector3;
 use std::collections::HashMap;
 #[cfg(feature = "tracy")]
 use tracy_client::span;
 
-use crate::render::{atlas::MaterialType, mesh::Mesh, pipelines::terrain::BlockVertex};
+use crate::{
+    core::config::WorldType,
+    render::{atlas::MaterialType, mesh::Mesh, pipelines::terrain::BlockVertex},
+};
 
-use super::{biomes::BiomeParameters, block::Block, noise::NoiseGenerator};
+use super::{
+    biomes::{ColumnSample, max_surface_height},
+    caves::is_carved,
+    features::{can_replace, features_for_cell},
+    flat::{debug_grid_block, parse_layers, void_block},
+    format,
+    light::{self, LightMap, PendingLight},
+    mesher::{LayerMesh, MeshPass, MeshSettings, greedy_layer},
+    noise::NoiseGenerator,
+    preset::WorldGenPreset,
+    storage::{BlockMut, BlockStorage, SECTION_COUNT, SECTION_HEIGHT},
+    water::WaterMap,
+};
 
 pub const CHUNK_Y_SIZE: usize = 512;
+/// Мировая высота нижнего слоя колонки: мир уходит ниже нуля.
+pub const WORLD_MIN_Y: i32 = -64;
+/// Верхняя граница мира (не включительно).
+pub const WORLD_MAX_Y: i32 = WORLD_MIN_Y + CHUNK_Y_SIZE as i32;
 pub const CHUNK_AREA: usize = 16;
 pub const CHUNK_AREA_WITH_PADDING: usize = CHUNK_AREA + 2; // +1 с каждой стороны для паддинга
 pub const TOTAL_CHUNK_SIZE: usize =
     CHUNK_Y_SIZE * CHUNK_AREA_WITH_PADDING * CHUNK_AREA_WITH_PADDING;
 
 pub struct Chunk {
-    pub blocks: Vec<Block>,
+    pub blocks: BlockStorage,
     pub offset: [i32; 3],
     pub mesh: Mesh<BlockVertex>,
+    /// Полупрозрачные жидкости целиком; перезаливается только при изменениях.
+    pub fluid_mesh: Mesh<BlockVertex>,
     pub dirty: bool,
     /// Флаг, что содержимое нужно сохранить на диск.
     pub needs_save: bool,
+    /// Свет неба и блоков по тем же индексам, что и блоки.
+    pub light: LightMap,
+    pending_light: PendingLight,
+    light_edge_changed: bool,
     layer_meshes: Vec<LayerMesh>,
+    fluid_layers: Vec<LayerMesh>,
+    fluid_changed: bool,
     layer_dirty: Vec<bool>,
     layer_spans: Vec<LayerSpan>,
     layout_changed: bool,
@@ -35,12 +63,6 @@ pub struct Chunk {
     dirty_y_range: Option<(usize, usize)>,
 }
 
-#[derive(Default, Clone)]
-struct LayerMesh {
-    verts: Vec<BlockVertex>,
-    indices: Vec<u32>,
-}
-
 #[derive(Default, Clone, Copy, PartialEq, Eq)]
 pub struct LayerSpan {
     pub v_start: u32,
@@ -51,31 +73,25 @@ pub struct LayerSpan {
 
 impl Chunk {
     pub fn new(offset: [i32; 3]) -> Self {
-        let mut blocks = Vec::with_capacity(TOTAL_CHUNK_SIZE);
-
-        for y in 0..CHUNK_Y_SIZE {
-            for _x in 0..CHUNK_AREA_WITH_PADDING {
-                for _z in 0..CHUNK_AREA_WITH_PADDING {
-                    let material_type = if y < 12 {
-                        MaterialType::DEBUG
-                    } else if y == 12 {
-                        MaterialType::DEBUG
-                    } else {
-                        MaterialType::AIR
-                    };
-
-                    blocks.push(Block::new(material_type));
-                }
-            }
+        let mut blocks = BlockStorage::new(MaterialType::AIR);
+        let debug_layers = 13 * CHUNK_AREA_WITH_PADDING * CHUNK_AREA_WITH_PADDING;
+        for index in 0..debug_layers {
+            blocks.set(index, MaterialType::DEBUG);
         }
         let mesh = Mesh::new();
         Chunk {
             blocks,
             offset,
             mesh,
+            fluid_mesh: Mesh::new(),
             dirty: false,
             needs_save: false,
+            light: LightMap::default(),
+            pending_light: PendingLight::full(),
+            light_edge_changed: false,
             layer_meshes: vec![LayerMesh::default(); CHUNK_Y_SIZE],
+            fluid_layers: vec![LayerMesh::default(); CHUNK_Y_SIZE],
+            fluid_changed: false,
             layer_dirty: vec![true; CHUNK_Y_SIZE],
             layer_spans: vec![LayerSpan::default(); CHUNK_Y_SIZE],
             layout_changed: true,
@@ -93,17 +109,26 @@ impl Chunk {
     pub fn get_block(&self, y: usize, x: usize, z: usize) -> Option<MaterialType> {
         if y < CHUNK_Y_SIZE && x < CHUNK_AREA_WITH_PADDING && z < CHUNK_AREA_WITH_PADDING {
             let index = self.calculate_index(y, x, z);
-            self.blocks.get(index).copied().map(|b| b.material_type)
+            Some(self.blocks.get(index))
         } else {
             None
         }
     }
 
-    /// Получить изменяемый блок
-    pub fn get_block_mut(&mut self, y: usize, x: usize, z: usize) -> Option<&mut MaterialType> {
+    /// Упакованный свет клетки: старшие 4 бита — небо, младшие — блоки.
+    pub fn get_light(&self, y: usize, x: usize, z: usize) -> Option<u8> {
+        if y < CHUNK_Y_SIZE && x < CHUNK_AREA_WITH_PADDING && z < CHUNK_AREA_WITH_PADDING {
+            Some(self.light.get(self.calculate_index(y, x, z)))
+        } else {
+            None
+        }
+    }
+
+    /// Получить изменяемый блок (записывается в хранилище при drop)
+    pub fn get_block_mut(&mut self, y: usize, x: usize, z: usize) -> Option<BlockMut<'_>> {
         if y < CHUNK_Y_SIZE && x < CHUNK_AREA_WITH_PADDING && z < CHUNK_AREA_WITH_PADDING {
             let index = self.calculate_index(y, x, z);
-            return self.blocks.get_mut(index).map(|b| &mut b.material_type);
+            return Some(self.blocks.get_mut(index));
         }
         None
     }
@@ -112,74 +137,243 @@ impl Chunk {
         &mut self,
         offset: [i32; 3],
         noise_generator: &NoiseGenerator,
-        biome: &BiomeParameters,
-        land_level: usize,
+        preset: &WorldGenPreset,
     ) {
         #[cfg(feature = "tracy")]
         let _span = span!("generate chunk: full scope"); // Замер генерации чанка
 
         self.offset = offset; // Сохраняем смещение чанка
+        // Чанк переиспользуется: начинаем с пустого хранилища, а не со старых палитр.
+        self.blocks = BlockStorage::new(MaterialType::AIR);
+
+        match &preset.world_type {
+            WorldType::Default => self.generate_terrain(noise_generator, preset),
+            WorldType::Superflat { layers } => {
+                // Пресет проверяет слои при создании мира, здесь ошибок уже не ждём.
+                // Слои кладутся от y = 0, ниже — пустота.
+                let column = parse_layers(layers).unwrap_or_default();
+                self.fill_blocks(|_, y, _| {
+                    usize::try_from(y)
+                        .ok()
+                        .and_then(|y| column.get(y).copied())
+                        .unwrap_or(MaterialType::AIR)
+                });
+            }
+            WorldType::Void => self.fill_blocks(void_block),
+            WorldType::DebugGrid => self.fill_blocks(debug_grid_block),
+        }
+        self.blocks.compact();
+        self.pending_light = PendingLight::full();
+
+        self.dirty = true;
+        self.dirty_y_range = Some((0, CHUNK_Y_SIZE - 1));
+        self.layer_dirty.iter_mut().for_each(|d| *d = true);
+        self.rebuilt_layers = (0..CHUNK_Y_SIZE).collect();
+    }
 
-        let max_biome_height = (biome.base_height + biome.amplitude) as usize;
+    /// Рельеф по пресету: биомы, пещеры, руды и структуры.
+    fn generate_terrain(&mut self, noise_generator: &NoiseGenerator, preset: &WorldGenPreset) {
+        // Сначала считаем карту биомов, высоты и воды для каждой колонки (с паддингом),
+        // чтобы не пересчитывать шум на каждом блоке по y.
+        let origin = local_pos_to_world(self.offset, Vector3::new(-1, 0, -1));
+        let (min_x, min_z) = (origin.x as i32, origin.z as i32);
+        let last = CHUNK_AREA_WITH_PADDING as i32 - 1;
+        let water = WaterMap::around(
+            preset,
+            noise_generator,
+            [min_x, min_z],
+            [min_x + last, min_z + last],
+        );
+        let mut columns = Vec::with_capacity(CHUNK_AREA_WITH_PADDING * CHUNK_AREA_WITH_PADDING);
+        for x in 0..CHUNK_AREA_WITH_PADDING {
+            for z in 0..CHUNK_AREA_WITH_PADDING {
+                let world_pos =
+                    local_pos_to_world(self.offset, Vector3::new(x as i32 - 1, 0, z as i32 - 1));
+                let sample = water.sample(preset, noise_generator, world_pos.x, world_pos.z);
+                let height = (sample.height.round() as i32).clamp(WORLD_MIN_Y + 1, WORLD_MAX_Y - 1);
+                columns.push((sample, height));
+            }
+        }
 
-        for y in 0..CHUNK_Y_SIZE {
+        let max_water_level = columns.iter().map(|(s, _)| s.water_level).max();
+        let max_biome_height = max_surface_height(preset).max(max_water_level.unwrap_or(0)) as i32;
+
+        for local_y in 0..CHUNK_Y_SIZE {
+            let y = local_y as i32 + WORLD_MIN_Y;
+            if y > max_biome_height {
+                // Выше рельефа остаётся воздух свежего хранилища.
+                break;
+            }
             for x in 0..CHUNK_AREA_WITH_PADDING {
                 for z in 0..CHUNK_AREA_WITH_PADDING {
-                    // Чанк может переиспользоваться: обязательно обнуляем старые данные
-                    // на высотах, куда новый биом не дотягивается.
-                    let block_type = if y > max_biome_height {
-                        MaterialType::AIR
-                    } else {
-                        #[cfg(feature = "tracy")]
-                        let _inner_span = span!(" creating single block");
+                    #[cfg(feature = "tracy")]
+                    let _inner_span = span!(" creating single block");
+
+                    let (sample, new_height) = columns[x * CHUNK_AREA_WITH_PADDING + z];
+                    let soil_depth = preset.biomes[sample.biome].terrain.soil_depth as i32;
 
-                        if y < (biome.base_height - 1.0) as usize {
-                            MaterialType::DIRT
+                    let block_type = if y > new_height {
+                        if y <= sample.water_level as i32 {
+                            MaterialType::WATER
                         } else {
-                            let local_x = x as i32 - 1;
-                            let local_z = z as i32 - 1;
-                            let world_pos = local_pos_to_world(
-                                self.offset,
-                                Vector3::new(local_x, y as i32, local_z),
-                            );
-                            let height_variation = noise_generator.get_height(
-                                world_pos.x as f32,
-                                world_pos.z as f32,
-                                biome.frequency,
-                                biome.amplitude,
-                            );
-                            let new_height =
-                                (biome.base_height + height_variation).round() as usize;
-
-                            if y > new_height {
-                                if y <= land_level {
-                                    MaterialType::WATER
-                                } else {
-                                    MaterialType::AIR
-                                }
-                            } else if y == new_height {
-                                MaterialType::GRASS
-                            } else if y == 0 {
-                                MaterialType::ROCK
-                            } else {
-                                MaterialType::DIRT
-                            }
+                            MaterialType::AIR
                         }
+                    } else if y == new_height {
+                        sample.surface_block(preset, new_height)
+                    } else if y == WORLD_MIN_Y {
+                        MaterialType::ROCK
+                    } else if y + soil_depth < new_height {
+                        MaterialType::STONE
+                    } else if y + preset.surface.filler_depth as i32 >= new_height {
+                        preset.biomes[sample.biome].filler
+                    } else {
+                        MaterialType::DIRT
                     };
 
-                    if let Some(block) = self.get_block_mut(y, x, z) {
+                    if let Some(mut block) = self.get_block_mut(local_y, x, z) {
                         *block = block_type;
                     }
                 }
             }
         }
-        self.dirty = true;
-        self.dirty_y_range = Some((0, CHUNK_Y_SIZE - 1));
-        self.layer_dirty.iter_mut().for_each(|d| *d = true);
-        self.rebuilt_layers = (0..CHUNK_Y_SIZE).collect();
+
+        self.carve_caves(noise_generator, preset, &columns);
+        self.place_ores(noise_generator, preset);
+        if preset.decorations {
+            self.place_features(noise_generator, preset);
+        }
     }
 
-    pub fn update_mesh(&mut self, _biome: BiomeParameters, y_range: Option<(usize, usize)>) {
+    /// Заполняет весь чанк (с паддингом) функцией от мировых координат.
+    fn fill_blocks(&mut self, block_at: impl Fn(i32, i32, i32) -> MaterialType) {
+        for x in 0..CHUNK_AREA_WITH_PADDING {
+            for z in 0..CHUNK_AREA_WITH_PADDING {
+                let world_pos =
+                    local_pos_to_world(self.offset, Vector3::new(x as i32 - 1, 0, z as i32 - 1));
+                let (world_x, world_z) = (world_pos.x as i32, world_pos.z as i32);
+                for y in 0..CHUNK_Y_SIZE {
+                    if let Some(mut block) = self.get_block_mut(y, x, z) {
+                        *block = block_at(world_x, y as i32 + WORLD_MIN_Y, world_z);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Проход вырезания пещер поверх карты высот. Обрабатывает и паддинг,
+    /// поэтому соседние чанки видят одинаковые пустоты на общей границе.
+    fn carve_caves(
+        &mut self,
+        noise_generator: &NoiseGenerator,
+        preset: &WorldGenPreset,
+        columns: &[(ColumnSample, i32)],
+    ) {
+        let caves = &preset.caves;
+        #[cfg(feature = "tracy")]
+        let _span = span!("carve caves");
+
+        for x in 0..CHUNK_AREA_WITH_PADDING {
+            for z in 0..CHUNK_AREA_WITH_PADDING {
+                let (sample, surface) = columns[x * CHUNK_AREA_WITH_PADDING + z];
+                let world_pos =
+                    local_pos_to_world(self.offset, Vector3::new(x as i32 - 1, 0, z as i32 - 1));
+                for y in caves.min_y.max(WORLD_MIN_Y + 1)..=surface {
+                    if is_carved(
+                        noise_generator,
+                        caves,
+                        world_pos.x,
+                        y,
+                        world_pos.z,
+                        surface,
+                        sample.water_level,
+                    ) && let Some(mut block) =
+                        self.get_block_mut((y - WORLD_MIN_Y) as usize, x, z)
+                    {
+                        *block = MaterialType::AIR;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Рассыпает рудные жилы по камню. Жилы берутся из соседних ячеек,
+    /// как и структуры, чтобы совпадать на границе чанков.
+    fn place_ores(&mut self, noise_generator: &NoiseGenerator, preset: &WorldGenPreset) {
+        #[cfg(feature = "tracy")]
+        let _span = span!("place ores");
+
+        let origin = column_origin(self.offset);
+        for (rule_index, rule) in preset.ores().iter().enumerate() {
+            for cell_x in (self.offset[0] - 1)..=(self.offset[0] + 1) {
+                for cell_z in (self.offset[2] - 1)..=(self.offset[2] + 1) {
+                    let cell = [cell_x, cell_z];
+                    for world_pos in rule.veins_for_cell(noise_generator.seed(), rule_index, cell) {
+                        let local = world_pos - origin;
+                        if !pos_in_chunk_bounds(local) {
+                            continue;
+                        }
+                        let (x, y, z) = (
+                            (local.x + 1) as usize,
+                            local.y as usize,
+                            (local.z + 1) as usize,
+                        );
+                        if let Some(mut block) = self.get_block_mut(y, x, z)
+                            && *block == MaterialType::STONE
+                        {
+                            *block = rule.material;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Расставляет деревья, валуны и руины. Структуры берутся из всех соседних
+    /// ячеек, поэтому перешагнувшая границу структура попадает в каждый чанк,
+    /// какой бы из них ни сгенерировался первым.
+    fn place_features(&mut self, noise_generator: &NoiseGenerator, preset: &WorldGenPreset) {
+        #[cfg(feature = "tracy")]
+        let _span = span!("place features");
+
+        let origin = column_origin(self.offset);
+        for cell_x in (self.offset[0] - 1)..=(self.offset[0] + 1) {
+            for cell_z in (self.offset[2] - 1)..=(self.offset[2] + 1) {
+                for feature in features_for_cell(noise_generator, preset, [cell_x, cell_z]) {
+                    for (world_pos, material) in feature.blocks() {
+                        let local = world_pos - origin;
+                        if !pos_in_chunk_bounds(local) {
+                            continue;
+                        }
+                        let (x, y, z) = (
+                            (local.x + 1) as usize,
+                            local.y as usize,
+                            (local.z + 1) as usize,
+                        );
+                        if let Some(mut block) = self.get_block_mut(y, x, z)
+                            && can_replace(*block, material)
+                        {
+                            *block = material;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn update_mesh(&mut self, settings: &MeshSettings, y_range: Option<(usize, usize)>) {
+        // Свет считается там же, где меш, и расширяет диапазон слоёв на ремеш.
+        let y_range = if settings.lighting {
+            self.update_light();
+            match (y_range, self.dirty_y_range) {
+                (Some((lo, hi)), Some((light_lo, light_hi))) => {
+                    Some((lo.min(light_lo), hi.max(light_hi)))
+                }
+                (range, _) => range,
+            }
+        } else {
+            self.pending_light = PendingLight::default();
+            y_range
+        };
         let (y_start, y_end) = match y_range {
             Some((lo, hi)) => (lo.min(CHUNK_Y_SIZE - 1), hi.min(CHUNK_Y_SIZE - 1)),
             None => {
@@ -199,46 +393,29 @@ impl Chunk {
             }
             rebuilt.push(y);
             self.layer_dirty[y] = false;
-            let mut layer = LayerMesh::default();
-
-            for x in 1..=CHUNK_AREA {
-                for z in 1..=CHUNK_AREA {
-                    #[cfg(feature = "tracy")]
-                    let _inner_span = span!("processing block vertices"); // Замер вершин блока
-
-                    let local_pos = Vector3::new(x as i32 - 1, y as i32, z as i32 - 1);
-                    let block = self.get_block(y, x, z).unwrap();
-                    if block == MaterialType::AIR {
-                        continue;
-                    }
-
-                    let mut block_indices: Vec<u32> = Vec::with_capacity(6 * 6);
-                    let mut quad_counter = 0;
-
-                    for side in crate::terrain_gen::block::Direction::ALL {
-                        let neighbor_pos: Vector3<i32> = local_pos + side.to_vec();
-                        let visible = self.is_quad_visible(&neighbor_pos);
-
-                        if visible {
-                            let world_pos = [
-                                local_pos.x + (self.offset[0] * CHUNK_AREA as i32),
-                                local_pos.y,
-                                local_pos.z + (self.offset[2] * CHUNK_AREA as i32),
-                            ];
-                            let quad = crate::terrain_gen::block::Quad::new(block, side, world_pos);
-                            layer.verts.extend_from_slice(&quad.vertices);
-                            block_indices.extend_from_slice(&quad.get_indices(quad_counter));
-                            quad_counter += 1;
-                        }
-                    }
-
-                    let base = layer.verts.len() as u32 - (quad_counter * 4);
-                    block_indices = block_indices.iter().map(|i| i + base).collect();
-                    layer.indices.extend(block_indices);
-                }
+            let (opaque, fluid) = if self.layer_may_have_faces(y) {
+                (
+                    greedy_layer(self, y, settings, MeshPass::Opaque),
+                    greedy_layer(self, y, settings, MeshPass::Fluid),
+                )
+            } else {
+                (LayerMesh::default(), LayerMesh::default())
+            };
+            self.layer_meshes[y] = opaque;
+            if !(fluid.is_empty() && self.fluid_layers[y].is_empty()) {
+                self.fluid_changed = true;
             }
+            self.fluid_layers[y] = fluid;
+        }
 
-            self.layer_meshes[y] = layer;
+        if self.fluid_changed {
+            let mut fluid = Mesh::new();
+            for layer in &self.fluid_layers {
+                let base = fluid.verts.len() as u32;
+                fluid.indices.extend(layer.indices.iter().map(|i| i + base));
+                fluid.verts.extend_from_slice(&layer.verts);
+            }
+            self.fluid_mesh = fluid;
         }
 
         let mut verts = Vec::new();
@@ -275,9 +452,38 @@ impl Chunk {
         self.dirty_y_range = None;
     }
 
+    /// Может ли в слое быть хоть одна грань. Пустые секции пропускаются целиком,
+    /// у однородных непрозрачных граням есть место только на верхнем и нижнем слое:
+    /// секция включает паддинг, так что соседи по x/z внутри неё те же.
+    fn layer_may_have_faces(&self, y: usize) -> bool {
+        match self.blocks.uniform_section(y / SECTION_HEIGHT) {
+            Some(MaterialType::AIR) => false,
+            Some(_) => y.is_multiple_of(SECTION_HEIGHT) || y % SECTION_HEIGHT == SECTION_HEIGHT - 1,
+            None => true,
+        }
+    }
+
+    /// Диапазоны индексов меша по секциям; у пустой секции диапазон пустой.
+    pub fn section_ranges(&self) -> Vec<Range<u32>> {
+        self.layer_spans
+            .chunks(SECTION_HEIGHT)
+            .map(|layers| {
+                let start = layers.first().map_or(0, |s| s.i_start);
+                let end = layers.last().map_or(start, |s| s.i_start + s.i_len);
+                start..end
+            })
+            .collect()
+    }
+
     fn mark_dirty_y(&mut self, y: usize) {
-        let y0 = y.saturating_sub(1);
-        let y1 = (y + 1).min(CHUNK_Y_SIZE - 1);
+        self.mark_dirty_layers(y, y);
+    }
+
+    /// Помечает слои `lo..=hi` и по слою вокруг: грани соседних слоёв
+    /// смотрят на эти блоки и их свет.
+    fn mark_dirty_layers(&mut self, lo: usize, hi: usize) {
+        let y0 = lo.saturating_sub(1);
+        let y1 = (hi + 1).min(CHUNK_Y_SIZE - 1);
         self.dirty_y_range = match self.dirty_y_range {
             Some((lo, hi)) => Some((lo.min(y0), hi.max(y1))),
             None => Some((y0, y1)),
@@ -305,26 +511,93 @@ impl Chunk {
         std::mem::take(&mut self.rebuilt_layers)
     }
 
-    pub fn layer_mesh(&self, y: usize) -> Option<(&[BlockVertex], &[u32])> {
-        self.layer_meshes
-            .get(y)
-            .map(|lm| (lm.verts.as_slice(), lm.indices.as_slice()))
+    /// Поменялся ли `fluid_mesh` с прошлого вызова.
+    pub fn take_fluid_changed(&mut self) -> bool {
+        std::mem::take(&mut self.fluid_changed)
     }
 
-    fn is_quad_visible(&self, neighbor_pos: &Vector3<i32>) -> bool {
-        if pos_in_chunk_bounds(*neighbor_pos) {
-            // Преобразуем координаты (-1..16) в индексы массива (0..17)
+    /// Применяет накопленные изменения света и помечает задетые слои на ремеш.
+    pub fn update_light(&mut self) {
+        if self.pending_light.is_empty() {
+            return;
+        }
+        let pending = std::mem::take(&mut self.pending_light);
+        let change = light::update(&self.blocks, &mut self.light, &pending);
+        if let Some((lo, hi)) = change.layers {
+            self.mark_dirty_layers(lo, hi);
+        }
+        self.light_edge_changed |= change.edge;
+    }
+
+    /// Запоминает изменённый блок, чтобы пересчитать вокруг него свет при ремеше.
+    fn queue_light_update(&mut self, y: usize, x: usize, z: usize) {
+        let index = self.calculate_index(y, x, z);
+        self.pending_light.edits.push(index);
+    }
 
-            let x_index = (neighbor_pos.x + 1) as usize;
-            let y_index = neighbor_pos.y as usize;
-            let z_index = (neighbor_pos.z + 1) as usize;
+    /// Менялся ли свет у края чанка с прошлого вызова: тогда соседям нужна сшивка.
+    pub fn take_light_edge_changed(&mut self) -> bool {
+        std::mem::take(&mut self.light_edge_changed)
+    }
 
-            let neighbor_block = self.get_block(y_index, x_index, z_index).unwrap();
-            return neighbor_block as u16 == MaterialType::AIR as u16;
-        } else {
-            // Нет соседа в этом чанке — считаем грань видимой, чтобы не пропадали блоки на границах.
-            return true;
+    /// Переписывает паддинг со стороны `dir` (смещение соседа по x и z, диагональ
+    /// тоже) настоящими блоками и светом соседа. Изменившиеся слои помечаются
+    /// на ремеш, пришедший свет — на растекание внутрь при следующем ремеше.
+    pub fn copy_border_from(&mut self, neighbour: &Chunk, dir: [i32; 2]) -> bool {
+        let padding = |d: i32| match d {
+            -1 => 0..1,
+            1 => CHUNK_AREA + 1..CHUNK_AREA + 2,
+            _ => 1..CHUNK_AREA + 1,
+        };
+        // Паддинг x = 0 у нас — это x = 16 у соседа слева, и наоборот.
+        let shift = |p: usize, d: i32| (p as i32 + d * CHUNK_AREA as i32) as usize;
+        let mut changed = false;
+        for section in 0..SECTION_COUNT {
+            let ours = self.blocks.uniform_section(section);
+            let our_light = self.light.uniform_section(section);
+            if ours.is_some()
+                && ours == neighbour.blocks.uniform_section(section)
+                && our_light.is_some()
+                && our_light == neighbour.light.uniform_section(section)
+            {
+                continue;
+            }
+            for y in section * SECTION_HEIGHT..(section + 1) * SECTION_HEIGHT {
+                let mut layer_changed = false;
+                for x in padding(dir[0]) {
+                    for z in padding(dir[1]) {
+                        let source =
+                            neighbour.calculate_index(y, shift(x, -dir[0]), shift(z, -dir[1]));
+                        let index = self.calculate_index(y, x, z);
+                        let material = neighbour.blocks.get(source);
+                        if self.blocks.get(index) != material {
+                            self.blocks.set(index, material);
+                            layer_changed = true;
+                        }
+                        let (old, light) = (self.light.get(index), neighbour.light.get(source));
+                        if old != light {
+                            self.light.set(index, light);
+                            self.pending_light.border.push((index, old));
+                            layer_changed = true;
+                        }
+                    }
+                }
+                if layer_changed {
+                    self.mark_dirty_y(y);
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            self.dirty = true;
         }
+        changed
+    }
+
+    pub fn layer_mesh(&self, y: usize) -> Option<(&[BlockVertex], &[u32])> {
+        self.layer_meshes
+            .get(y)
+            .map(|lm| (lm.verts.as_slice(), lm.indices.as_slice()))
     }
 }
 
@@ -334,6 +607,12 @@ pub struct ChunkManager {
     index_offset: Vec<[i32; 3]>,
 }
 
+impl Default for ChunkManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ChunkManager {
     pub fn new() -> Self {
         ChunkManager {
@@ -349,9 +628,9 @@ impl ChunkManager {
         self.chunks.push(Arc::new(RwLock::new(chunk)));
         // offset_index_map будет заполнен при update_chunk_offset
         debug_assert!(
-            self.offset_index_map
-                .get(&[i32::MIN, i32::MIN, i32::MIN])
-                .is_none()
+            !self
+                .offset_index_map
+                .contains_key(&[i32::MIN, i32::MIN, i32::MIN])
         );
     }
 
@@ -367,6 +646,57 @@ impl ChunkManager {
         self.offset_index_map.get(offset).copied()
     }
 
+    /// Загруженные соседи колонки `index`, включая диагональных, со смещением к ним.
+    pub fn neighbours(&self, index: usize) -> Vec<(usize, [i32; 2])> {
+        let Some(offset) = self.index_offset.get(index).copied() else {
+            return Vec::new();
+        };
+        if self.offset_index_map.get(&offset) != Some(&index) {
+            return Vec::new();
+        }
+        let mut found = Vec::new();
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                let neighbour = [offset[0] + dx, offset[1], offset[2] + dz];
+                if (dx, dz) != (0, 0)
+                    && let Some(n) = self.get_chunk_index_by_offset(&neighbour)
+                {
+                    found.push((n, [dx, dz]));
+                }
+            }
+        }
+        found
+    }
+
+    /// Сшивает чанк с загруженными соседями: паддинг каждой стороны получает
+    /// настоящие блоки и свет соседа, а паддинг соседей — этого чанка. Так правки
+    /// и сохранённые соседи не оставляют лишних или пропавших граней на стыке,
+    /// а свет перетекает через границу.
+    /// Возвращает индексы чанков, чей паддинг изменился и меш нужно пересобрать.
+    pub fn sync_borders(&self, index: usize) -> Vec<usize> {
+        let mut touched = Vec::new();
+        let chunk = &self.chunks[index];
+        for (n, dir) in self.neighbours(index) {
+            let neighbour = &self.chunks[n];
+            if chunk
+                .write()
+                .unwrap()
+                .copy_border_from(&neighbour.read().unwrap(), dir)
+                && !touched.contains(&index)
+            {
+                touched.push(index);
+            }
+            if neighbour
+                .write()
+                .unwrap()
+                .copy_border_from(&chunk.read().unwrap(), [-dir[0], -dir[1]])
+            {
+                touched.push(n);
+            }
+        }
+        touched
+    }
+
     // Получить материал блока в мировых координатах
     pub fn get_block_material(&self, world_pos: Vector3<i32>) -> Option<MaterialType> {
         let (chunk_offset, local_pos) = world_pos_to_chunk_and_local(world_pos);
@@ -417,14 +747,25 @@ impl ChunkManager {
         } else if local_pos.z == (CHUNK_AREA as i32 - 1) {
             neighbor_offsets.push([chunk_offset[0], chunk_offset[1], chunk_offset[2] + 1]);
         }
+        // Угловой блок попадает и в угол паддинга диагонального соседа (для AO).
+        if let [[x, _, _], [_, _, z]] = neighbor_offsets[..] {
+            neighbor_offsets.push([x, chunk_offset[1], z]);
+        }
 
         if let Some(index) = self.get_chunk_index_by_offset(&chunk_offset) {
             let mut chunk = self.chunks[index].write().unwrap();
-            if let Some(block) = chunk.get_block_mut(y as usize, x as usize, z as usize) {
-                *block = material;
+            let updated = match chunk.get_block_mut(y as usize, x as usize, z as usize) {
+                Some(mut block) => {
+                    *block = material;
+                    true
+                }
+                None => false,
+            };
+            if updated {
                 chunk.dirty = true;
                 chunk.needs_save = true;
                 chunk.mark_dirty_y(y as usize);
+                chunk.queue_light_update(y as usize, x as usize, z as usize);
                 println!("Block updated at world position: {:?}", world_pos);
                 touched.push(index);
             }
@@ -435,12 +776,7 @@ impl ChunkManager {
                 if let Some(nidx) = self.get_chunk_index_by_offset(&neigh_off) {
                     if let Ok(mut neigh_chunk) = self.chunks[nidx].write() {
                         // Обновляем паддинг соседа, чтобы его грань стала видимой/скрытой корректно.
-                        let origin = Vector3::new(
-                            neigh_off[0] * CHUNK_AREA as i32,
-                            neigh_off[1] * CHUNK_Y_SIZE as i32,
-                            neigh_off[2] * CHUNK_AREA as i32,
-                        );
-                        let local_in_neigh = world_pos - origin;
+                        let local_in_neigh = world_pos - column_origin(neigh_off);
                         let on_padding = local_in_neigh.x == -1
                             || local_in_neigh.x == CHUNK_AREA as i32
                             || local_in_neigh.z == -1
@@ -449,10 +785,11 @@ impl ChunkManager {
                             let nx = (local_in_neigh.x + 1) as usize;
                             let nz = (local_in_neigh.z + 1) as usize;
                             let ny = local_in_neigh.y as usize;
-                            if let Some(pad_block) = neigh_chunk.get_block_mut(ny, nx, nz) {
+                            if let Some(mut pad_block) = neigh_chunk.get_block_mut(ny, nx, nz) {
                                 *pad_block = material;
                             }
                             neigh_chunk.mark_dirty_y(ny);
+                            neigh_chunk.queue_light_update(ny, nx, nz);
                             neigh_chunk.needs_save = true;
                         }
                         neigh_chunk.dirty = true;
@@ -475,10 +812,10 @@ impl ChunkManager {
             self.index_offset[index] = new_offset;
         }
         self.offset_index_map.insert(new_offset, index);
-        if let Some(chunk) = self.chunks.get(index) {
-            if let Ok(mut chunk) = chunk.write() {
-                chunk.offset = new_offset;
-            }
+        if let Some(chunk) = self.chunks.get(index)
+            && let Ok(mut chunk) = chunk.write()
+        {
+            chunk.offset = new_offset;
         }
     }
 
@@ -502,30 +839,36 @@ pub fn pos_in_chunk_bounds(pos: Vector3<i32>) -> bool {
         && pos.z <= CHUNK_AREA as i32
 }
 
+/// Колонка и локальная позиция в ней. Колонка покрывает всю высоту мира,
+/// поэтому `y` чанка всегда 0, а локальный `y` вне мира выходит за `0..CHUNK_Y_SIZE`.
 fn world_pos_to_chunk_and_local(world_pos: Vector3<i32>) -> ([i32; 3], Vector3<i32>) {
     let chunk_x = world_pos.x.div_euclid(CHUNK_AREA as i32);
-    let chunk_y = world_pos.y.div_euclid(CHUNK_Y_SIZE as i32);
     let chunk_z = world_pos.z.div_euclid(CHUNK_AREA as i32);
 
     let local_x = world_pos.x.rem_euclid(CHUNK_AREA as i32);
-    let local_y = world_pos.y.rem_euclid(CHUNK_Y_SIZE as i32);
+    let local_y = world_pos.y - WORLD_MIN_Y;
     let local_z = world_pos.z.rem_euclid(CHUNK_AREA as i32);
 
     (
-        [chunk_x, chunk_y, chunk_z],
+        [chunk_x, 0, chunk_z],
         Vector3::new(local_x, local_y, local_z),
     )
 }
 
-pub fn local_pos_to_world(offset: [i32; 3], local_pos: Vector3<i32>) -> Vector3<f32> {
+/// Мировая точка локального нуля колонки.
+fn column_origin(offset: [i32; 3]) -> Vector3<i32> {
     Vector3::new(
-        local_pos.x as f32 + (offset[0] as f32 * CHUNK_AREA as f32),
-        local_pos.y as f32 + (offset[1] as f32 * CHUNK_Y_SIZE as f32),
-        local_pos.z as f32 + (offset[2] as f32 * CHUNK_AREA as f32),
+        offset[0] * CHUNK_AREA as i32,
+        WORLD_MIN_Y,
+        offset[2] * CHUNK_AREA as i32,
     )
 }
 
-fn material_to_u8(mat: MaterialType) -> u8 {
+pub fn local_pos_to_world(offset: [i32; 3], local_pos: Vector3<i32>) -> Vector3<f32> {
+    (local_pos + column_origin(offset)).cast().unwrap()
+}
+
+pub(crate) fn material_to_u8(mat: MaterialType) -> u8 {
     match mat {
         MaterialType::DIRT => 0,
         MaterialType::GRASS => 1,
@@ -533,42 +876,56 @@ fn material_to_u8(mat: MaterialType) -> u8 {
         MaterialType::WATER => 3,
         MaterialType::AIR => 4,
         MaterialType::DEBUG => 5,
+        MaterialType::SAND => 6,
+        MaterialType::SNOW => 7,
+        MaterialType::LOG => 8,
+        MaterialType::LEAVES => 9,
+        MaterialType::STONE => 10,
+        MaterialType::COAL => 11,
+        MaterialType::IRON => 12,
+        MaterialType::GOLD => 13,
+        MaterialType::LAVA => 14,
     }
 }
 
-fn material_from_u8(v: u8) -> MaterialType {
-    match v {
+/// `None` для байта, которому не соответствует ни один материал.
+pub(crate) fn material_from_u8(v: u8) -> Option<MaterialType> {
+    Some(match v {
         0 => MaterialType::DIRT,
         1 => MaterialType::GRASS,
         2 => MaterialType::ROCK,
         3 => MaterialType::WATER,
         4 => MaterialType::AIR,
         5 => MaterialType::DEBUG,
-        _ => MaterialType::AIR,
-    }
+        6 => MaterialType::SAND,
+        7 => MaterialType::SNOW,
+        8 => MaterialType::LOG,
+        9 => MaterialType::LEAVES,
+        10 => MaterialType::STONE,
+        11 => MaterialType::COAL,
+        12 => MaterialType::IRON,
+        13 => MaterialType::GOLD,
+        14 => MaterialType::LAVA,
+        _ => return None,
+    })
 }
 
 impl Chunk {
-    pub fn save_to(&self, path: &Path) -> Result<()> {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let mut buf = Vec::with_capacity(self.blocks.len());
-        for block in &self.blocks {
-            buf.push(material_to_u8(block.material_type));
-        }
-        fs::write(path, buf)?;
-        Ok(())
+    /// Блоки в формате файла сохранения, с заголовком версии.
+    pub fn block_bytes(&self) -> Vec<u8> {
+        format::encode_blocks(&self.blocks)
     }
 
+    /// Загружает старый файл `chunk_X_Y_Z.bin` (для миграции в регионы).
     pub fn load_from(&mut self, path: &Path, offset: [i32; 3]) -> Result<()> {
         let data = fs::read(path)?;
-        if data.len() != self.blocks.len() {
-            bail!("chunk file has wrong size");
-        }
-        for (b, val) in self.blocks.iter_mut().zip(data.into_iter()) {
-            b.update(material_from_u8(val));
-        }
+        self.load_bytes(&data, offset)
+    }
+
+    /// Восстанавливает блоки из данных [`Chunk::block_bytes`] любой известной версии.
+    pub fn load_bytes(&mut self, data: &[u8], offset: [i32; 3]) -> Result<()> {
+        self.blocks = format::decode_blocks(data)?;
+        self.pending_light = PendingLight::full();
         self.offset = offset;
         self.dirty = false;
         self.needs_save = false;
@@ -576,3 +933,122 @@ impl Chunk {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain_gen::light::LightChannel;
+
+    fn block_at(chunk: &Chunk, world_y: i32) -> MaterialType {
+        chunk
+            .get_block((world_y - WORLD_MIN_Y) as usize, 5, 5)
+            .unwrap()
+    }
+
+    #[test]
+    fn terrain_reaches_below_zero_and_round_trips() {
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(10);
+        let mut chunk = Chunk::new([0, 0, 0]);
+        chunk.update_blocks([0, 0, 0], &noise, &preset);
+        assert_eq!(block_at(&chunk, WORLD_MIN_Y), MaterialType::ROCK);
+        assert_ne!(block_at(&chunk, -10), MaterialType::AIR);
+        // Верхние секции пустые и не дают граней.
+        chunk.update_mesh(&MeshSettings::default(), None);
+        assert!(chunk.section_ranges().last().unwrap().is_empty());
+
+        let mut loaded = Chunk::new([0, 0, 0]);
+        loaded.load_bytes(&chunk.block_bytes(), [0, 0, 0]).unwrap();
+        assert!(loaded.blocks.iter().eq(chunk.blocks.iter()));
+
+        // Старый формат без секций: колонка начиналась с y = 0.
+        let mut legacy = vec![material_to_u8(MaterialType::AIR); TOTAL_CHUNK_SIZE];
+        legacy[..CHUNK_AREA_WITH_PADDING * CHUNK_AREA_WITH_PADDING]
+            .fill(material_to_u8(MaterialType::GRASS));
+        loaded.load_bytes(&legacy, [0, 0, 0]).unwrap();
+        assert_eq!(block_at(&loaded, 0), MaterialType::GRASS);
+        assert_eq!(block_at(&loaded, 1), MaterialType::AIR);
+        assert_eq!(block_at(&loaded, -1), MaterialType::STONE);
+    }
+
+    #[test]
+    fn sync_borders_copies_real_neighbour_blocks() {
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(4);
+        let mut manager = ChunkManager::new();
+        for (index, offset) in [[0, 0, 0], [1, 0, 0], [1, 0, 1]].into_iter().enumerate() {
+            let mut chunk = Chunk::new(offset);
+            chunk.update_blocks(offset, &noise, &preset);
+            chunk.update_mesh(&MeshSettings::default(), None);
+            chunk.dirty = false;
+            manager.add_chunk(chunk);
+            manager.update_chunk_offset(index, offset);
+        }
+        // Свежая генерация сходится на стыках: сшивать нечего.
+        assert!(manager.sync_borders(0).is_empty());
+
+        // Сохранённый сосед отличается от того, что нагенерировал паддинг.
+        let y = 400;
+        {
+            let mut right = manager.chunks[1].write().unwrap();
+            *right.get_block_mut(y, 1, 5).unwrap() = MaterialType::STONE;
+            *right.get_block_mut(y, 1, 16).unwrap() = MaterialType::LOG;
+        }
+        {
+            let mut corner = manager.chunks[2].write().unwrap();
+            *corner.get_block_mut(y, 1, 1).unwrap() = MaterialType::SAND;
+        }
+        let touched = manager.sync_borders(0);
+        assert_eq!(touched, vec![0]);
+        let left = manager.chunks[0].read().unwrap();
+        assert!(left.dirty);
+        assert_eq!(left.get_block(y, 17, 5), Some(MaterialType::STONE));
+        assert_eq!(left.get_block(y, 17, 16), Some(MaterialType::LOG));
+        assert_eq!(left.get_block(y, 17, 17), Some(MaterialType::SAND));
+        assert_eq!(left.dirty_y_range(), Some((y - 1, y + 1)));
+    }
+
+    #[test]
+    fn lava_light_crosses_the_chunk_border_and_goes_out() {
+        let mut manager = ChunkManager::new();
+        for (index, offset) in [[0, 0, 0], [1, 0, 0]].into_iter().enumerate() {
+            let mut chunk = Chunk::new(offset);
+            chunk.blocks = BlockStorage::new(MaterialType::STONE);
+            chunk.pending_light = PendingLight::full();
+            manager.add_chunk(chunk);
+            manager.update_chunk_offset(index, offset);
+        }
+        // Туннель сквозь стык, лава в трёх блоках от него.
+        for x in 12..=20 {
+            manager.set_block_material(Vector3::new(x, 0, 5), MaterialType::AIR);
+        }
+        let settle = |manager: &ChunkManager| {
+            let mut edge = vec![0, 1];
+            while !edge.is_empty() {
+                for index in 0..2 {
+                    manager.chunks[index]
+                        .write()
+                        .unwrap()
+                        .update_mesh(&MeshSettings::default(), None);
+                }
+                edge.retain(|&i| manager.chunks[i].write().unwrap().take_light_edge_changed());
+                for &index in &edge {
+                    manager.sync_borders(index);
+                }
+            }
+        };
+        let block_light = |manager: &ChunkManager, x: usize| {
+            let chunk = manager.chunks[1].read().unwrap();
+            LightChannel::Block.get(chunk.get_light((-WORLD_MIN_Y) as usize, x, 6).unwrap())
+        };
+
+        manager.set_block_material(Vector3::new(13, 0, 5), MaterialType::LAVA);
+        settle(&manager);
+        // Мировой x = 18 — третья клетка соседа, в пяти шагах от лавы.
+        assert_eq!(block_light(&manager, 3), 10);
+
+        manager.set_block_material(Vector3::new(13, 0, 5), MaterialType::AIR);
+        settle(&manager);
+        assert_eq!(block_light(&manager, 3), 0);
+    }
+}
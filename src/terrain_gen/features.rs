@@ -0,0 +1,299 @@
+use cgmath::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::render::atlas::MaterialType;
+
+use super::{
+    biomes::sample_column,
+    caves::is_carved,
+    chunk::{CHUNK_AREA, WORLD_MAX_Y},
+    noise::NoiseGenerator,
+    preset::WorldGenPreset,
+    water::WaterMap,
+};
+
+/// Максимальный горизонтальный радиус любой структуры от точки установки.
+/// Должен быть меньше `CHUNK_AREA`, иначе одного кольца соседних ячеек не хватит.
+pub const MAX_FEATURE_RADIUS: i32 = 2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FeatureKind {
+    Tree { trunk_height: i32 },
+    Boulder { radius: i32 },
+    Ruin,
+}
+
+/// Структура, привязанная к мировой точке на поверхности.
+#[derive(Copy, Clone, Debug)]
+pub struct Feature {
+    pub origin: Vector3<i32>,
+    pub kind: FeatureKind,
+    /// Собственный сид структуры — для детерминированных «случайных» деталей.
+    seed: u64,
+}
+
+/// Сколько попыток поставить структуру в одной ячейке 16x16 для биома.
+/// Дробная часть — вероятность ещё одной попытки.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FeatureDensity {
+    #[serde(default)]
+    pub trees: f32,
+    #[serde(default)]
+    pub boulders: f32,
+    #[serde(default)]
+    pub ruins: f32,
+}
+
+/// Простой детерминированный генератор (SplitMix64), чтобы не тянуть `rand`.
+pub struct FeatureRng(u64);
+
+impl FeatureRng {
+    pub fn for_cell(world_seed: u32, cell_x: i32, cell_z: i32) -> Self {
+        let mut h = world_seed as u64;
+        h = hash64(h ^ (cell_x as u32 as u64));
+        h = hash64(h ^ ((cell_z as u32 as u64) << 32));
+        Self(h)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        hash64(self.0)
+    }
+
+    /// Случайное число в полуинтервале `[lo, hi)`.
+    pub fn range(&mut self, lo: i32, hi: i32) -> i32 {
+        lo + (self.next_u64() % (hi - lo).max(1) as u64) as i32
+    }
+
+    /// Случайное число в `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Целое число попыток по дробной плотности.
+    pub fn attempts(&mut self, density: f32) -> u32 {
+        let whole = density.max(0.0).floor();
+        whole as u32 + u32::from(self.next_f32() < density - whole)
+    }
+}
+
+fn hash64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// Все структуры, чья точка установки попадает в ячейку `cell` (в координатах чанков).
+/// Результат зависит только от сида и координат ячейки — порядок генерации
+/// чанков на него не влияет.
+pub fn features_for_cell(
+    noise: &NoiseGenerator,
+    preset: &WorldGenPreset,
+    cell: [i32; 2],
+) -> Vec<Feature> {
+    let mut rng = FeatureRng::for_cell(noise.seed(), cell[0], cell[1]);
+    let base_x = cell[0] * CHUNK_AREA as i32;
+    let base_z = cell[1] * CHUNK_AREA as i32;
+
+    // Плотность берём по биому центра ячейки: биомы крупные, этого достаточно.
+    let center = sample_column(
+        preset,
+        noise,
+        (base_x + CHUNK_AREA as i32 / 2) as f32,
+        (base_z + CHUNK_AREA as i32 / 2) as f32,
+    );
+    let density = preset.biomes[center.biome].features;
+    let last = CHUNK_AREA as i32 - 1;
+    let water = WaterMap::around(
+        preset,
+        noise,
+        [base_x, base_z],
+        [base_x + last, base_z + last],
+    );
+
+    let mut features = Vec::new();
+    let kinds = [
+        (rng.attempts(density.trees), 0u8),
+        (rng.attempts(density.boulders), 1u8),
+        (rng.attempts(density.ruins), 2u8),
+    ];
+    for (attempts, kind) in kinds {
+        for _ in 0..attempts {
+            let x = base_x + rng.range(0, CHUNK_AREA as i32);
+            let z = base_z + rng.range(0, CHUNK_AREA as i32);
+            let seed = rng.next_u64();
+            let kind = match kind {
+                0 => FeatureKind::Tree {
+                    trunk_height: 4 + (seed % 3) as i32,
+                },
+                1 => FeatureKind::Boulder {
+                    radius: 1 + (seed % 2) as i32,
+                },
+                _ => FeatureKind::Ruin,
+            };
+
+            let sample = water.sample(preset, noise, x as f32, z as f32);
+            let surface = sample.height.round() as i32;
+            if surface <= sample.water_level as i32 || surface + 12 >= WORLD_MAX_Y {
+                continue;
+            }
+            if is_carved(
+                noise,
+                &preset.caves,
+                x as f32,
+                surface,
+                z as f32,
+                surface,
+                sample.water_level,
+            ) {
+                continue;
+            }
+            let ground = sample.surface_block(preset, surface);
+            if matches!(kind, FeatureKind::Tree { .. }) && ground != MaterialType::GRASS {
+                continue;
+            }
+
+            features.push(Feature {
+                origin: Vector3::new(x, surface + 1, z),
+                kind,
+                seed,
+            });
+        }
+    }
+    features
+}
+
+impl Feature {
+    /// Блоки структуры в мировых координатах. Порядок фиксирован:
+    /// при пересечении структур побеждает записанный позже блок.
+    pub fn blocks(&self) -> Vec<(Vector3<i32>, MaterialType)> {
+        let mut out = Vec::new();
+        let o = self.origin;
+        match self.kind {
+            FeatureKind::Tree { trunk_height } => {
+                let top = trunk_height - 1;
+                for dy in (top - 2)..=(top + 1) {
+                    let radius: i32 = if dy >= top { 1 } else { 2 };
+                    for dx in -radius..=radius {
+                        for dz in -radius..=radius {
+                            let corner = dx.abs() == radius && dz.abs() == radius;
+                            // Углы кроны срезаем «случайно», но детерминированно.
+                            if corner && (radius == 1 || self.bit(dx, dy, dz)) {
+                                continue;
+                            }
+                            out.push((o + Vector3::new(dx, dy, dz), MaterialType::LEAVES));
+                        }
+                    }
+                }
+                for dy in 0..trunk_height {
+                    out.push((o + Vector3::new(0, dy, 0), MaterialType::LOG));
+                }
+            }
+            FeatureKind::Boulder { radius } => {
+                let r2 = radius * radius + 1;
+                for dx in -radius..=radius {
+                    for dy in -1..=radius {
+                        for dz in -radius..=radius {
+                            if dx * dx + dy * dy + dz * dz <= r2 {
+                                out.push((o + Vector3::new(dx, dy, dz), MaterialType::ROCK));
+                            }
+                        }
+                    }
+                }
+            }
+            FeatureKind::Ruin => {
+                for dx in -2..=2i32 {
+                    for dz in -2..=2i32 {
+                        if dx.abs() != 2 && dz.abs() != 2 {
+                            continue;
+                        }
+                        // Полуразрушенные стены разной высоты.
+                        let height = 1 + (self.bits(dx, 0, dz) % 3) as i32;
+                        for dy in 0..height {
+                            out.push((o + Vector3::new(dx, dy, dz), MaterialType::ROCK));
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn bits(&self, dx: i32, dy: i32, dz: i32) -> u64 {
+        let key = ((dx + 8) as u64) | (((dy + 8) as u64) << 8) | (((dz + 8) as u64) << 16);
+        hash64(self.seed ^ key)
+    }
+
+    fn bit(&self, dx: i32, dy: i32, dz: i32) -> bool {
+        self.bits(dx, dy, dz) & 1 == 1
+    }
+}
+
+/// Может ли блок структуры заменить уже стоящий блок.
+pub fn can_replace(existing: MaterialType, placed: MaterialType) -> bool {
+    match placed {
+        MaterialType::LEAVES => existing == MaterialType::AIR,
+        _ => matches!(existing, MaterialType::AIR | MaterialType::LEAVES),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_features_are_deterministic() {
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(10);
+        for cx in -4..4 {
+            for cz in -4..4 {
+                let a = features_for_cell(&noise, &preset, [cx, cz]);
+                let b = features_for_cell(&noise, &preset, [cx, cz]);
+                assert_eq!(a.len(), b.len());
+                for (fa, fb) in a.iter().zip(b.iter()) {
+                    assert_eq!(fa.origin, fb.origin);
+                    assert_eq!(fa.kind, fb.kind);
+                    assert_eq!(fa.blocks(), fb.blocks());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn features_stay_within_max_radius() {
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(10);
+        for cx in -8..8 {
+            for cz in -8..8 {
+                for f in features_for_cell(&noise, &preset, [cx, cz]) {
+                    for (pos, _) in f.blocks() {
+                        assert!((pos.x - f.origin.x).abs() <= MAX_FEATURE_RADIUS);
+                        assert!((pos.z - f.origin.z).abs() <= MAX_FEATURE_RADIUS);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neighbouring_chunks_agree_on_shared_border() {
+        use crate::terrain_gen::chunk::{CHUNK_AREA_WITH_PADDING, Chunk};
+
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(10);
+        for (a_off, b_off) in [([0, 0, 0], [1, 0, 0]), ([3, 0, -2], [4, 0, -2])] {
+            let mut a = Chunk::new(a_off);
+            let mut b = Chunk::new(b_off);
+            // Генерируем в разном порядке — результат не должен зависеть от него.
+            b.update_blocks(b_off, &noise, &preset);
+            a.update_blocks(a_off, &noise, &preset);
+            for y in 0..64 {
+                for z in 0..CHUNK_AREA_WITH_PADDING {
+                    // Паддинг A справа (x=17) — это первая колонка B (x=1), и наоборот.
+                    assert_eq!(a.get_block(y, CHUNK_AREA + 1, z), b.get_block(y, 1, z));
+                    assert_eq!(a.get_block(y, CHUNK_AREA, z), b.get_block(y, 0, z));
+                }
+            }
+        }
+    }
+}
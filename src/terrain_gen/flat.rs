@@ -0,0 +1,119 @@
+use anyhow::{Context, Result, bail};
+
+use crate::render::atlas::MaterialType;
+
+use super::chunk::CHUNK_Y_SIZE;
+
+/// Высота площадки в пустом мире.
+pub const VOID_PLATFORM_Y: i32 = 8;
+/// Площадка пустого мира: квадрат вокруг точки появления (8, 8).
+const VOID_PLATFORM: std::ops::RangeInclusive<i32> = 5..=11;
+/// Высота, на которой стоят материалы отладочной сетки (над полом y = 0).
+pub const DEBUG_GRID_Y: i32 = 1;
+/// Шаг сетки: между материалами остаётся по блоку воздуха.
+const DEBUG_GRID_STEP: i32 = 2;
+
+/// Разбирает описание слоёв `"1*rock,3*dirt,1*grass"` в колонку снизу вверх.
+/// Количество можно опустить: `"rock,grass"` — по одному блоку.
+pub fn parse_layers(spec: &str) -> Result<Vec<MaterialType>> {
+    let mut column = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (count, name) = match part.split_once('*') {
+            Some((count, name)) => (
+                count
+                    .trim()
+                    .parse::<usize>()
+                    .with_context(|| format!("bad layer count in '{part}'"))?,
+                name.trim(),
+            ),
+            None => (1, part),
+        };
+        let Some(material) = MaterialType::from_name(name) else {
+            bail!("unknown material '{name}'");
+        };
+        column.extend(std::iter::repeat_n(material, count));
+    }
+    if column.len() > CHUNK_Y_SIZE {
+        bail!("{} layers do not fit into {CHUNK_Y_SIZE}", column.len());
+    }
+    Ok(column)
+}
+
+/// Блок пустого мира в мировой точке.
+pub fn void_block(x: i32, y: i32, z: i32) -> MaterialType {
+    if y == VOID_PLATFORM_Y && VOID_PLATFORM.contains(&x) && VOID_PLATFORM.contains(&z) {
+        MaterialType::ROCK
+    } else {
+        MaterialType::AIR
+    }
+}
+
+/// Материалы сетки отладочного мира: все, кроме воздуха.
+const DEBUG_GRID_MATERIALS: [MaterialType; MaterialType::ALL.len() - 1] = {
+    let mut materials = [MaterialType::ROCK; MaterialType::ALL.len() - 1];
+    let (mut i, mut n) = (0, 0);
+    while i < MaterialType::ALL.len() {
+        if !matches!(MaterialType::ALL[i], MaterialType::AIR) {
+            materials[n] = MaterialType::ALL[i];
+            n += 1;
+        }
+        i += 1;
+    }
+    materials
+};
+
+/// Блок отладочного мира: каменный пол и по одному блоку каждого материала.
+pub fn debug_grid_block(x: i32, y: i32, z: i32) -> MaterialType {
+    if y == 0 {
+        return MaterialType::ROCK;
+    }
+    if y != DEBUG_GRID_Y || x < 0 || z < 0 || x % DEBUG_GRID_STEP != 0 || z % DEBUG_GRID_STEP != 0 {
+        return MaterialType::AIR;
+    }
+    let columns = (DEBUG_GRID_MATERIALS.len() as f32).sqrt().ceil() as i32;
+    let (col, row) = (x / DEBUG_GRID_STEP, z / DEBUG_GRID_STEP);
+    if col >= columns {
+        return MaterialType::AIR;
+    }
+    DEBUG_GRID_MATERIALS
+        .get((row * columns + col) as usize)
+        .copied()
+        .unwrap_or(MaterialType::AIR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_layer_list() {
+        let column = parse_layers("1*rock, 3*dirt,grass").unwrap();
+        assert_eq!(
+            column,
+            vec![
+                MaterialType::ROCK,
+                MaterialType::DIRT,
+                MaterialType::DIRT,
+                MaterialType::DIRT,
+                MaterialType::GRASS,
+            ]
+        );
+        assert!(parse_layers("2*unobtainium").is_err());
+        assert!(parse_layers("x*dirt").is_err());
+    }
+
+    #[test]
+    fn debug_grid_contains_every_material_once() {
+        let mut seen = Vec::new();
+        for x in -4..32 {
+            for z in -4..32 {
+                let m = debug_grid_block(x, DEBUG_GRID_Y, z);
+                if m != MaterialType::AIR {
+                    assert!(!seen.contains(&m), "{m:?} placed twice");
+                    seen.push(m);
+                }
+            }
+        }
+        assert_eq!(seen.len(), MaterialType::ALL.len() - 1);
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::{Result, bail};
+
+use crate::render::atlas::MaterialType;
+
+use super::{
+    chunk::{CHUNK_AREA_WITH_PADDING, CHUNK_Y_SIZE, WORLD_MIN_Y},
+    storage::{BlockStorage, SECTION_COUNT, SECTION_HEIGHT, decode_material},
+};
+
+/// Метка данных чанка с заголовком версии.
+const CHUNK_MAGIC: &[u8; 4] = b"WCCK";
+/// Текущая версия формата чанка. При любом изменении раскладки данных версия
+/// растёт, а чтение старых версий идёт через миграции в [`decode_blocks`].
+pub const CHUNK_FORMAT_VERSION: u16 = 2;
+/// Метка, длина, дно колонки, число секций, высота секции и сторона колонки.
+const HEADER_LEN: usize = CHUNK_MAGIC.len() + 8;
+
+/// Версия 1: секции без заголовка, высота мира -64..448.
+const V1_MAGIC: &[u8; 4] = b"SECT";
+const V1_MIN_Y: i32 = -64;
+const V1_SECTION_COUNT: usize = 32;
+/// Версия 0: по байту на блок, колонка из 512 слоёв начиналась с y = 0.
+const V0_HEIGHT: usize = 512;
+
+/// Сериализует блоки в текущем формате.
+pub fn encode_blocks(blocks: &BlockStorage) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + SECTION_COUNT * 2);
+    out.extend_from_slice(CHUNK_MAGIC);
+    out.extend_from_slice(&CHUNK_FORMAT_VERSION.to_le_bytes());
+    out.extend_from_slice(&(WORLD_MIN_Y as i16).to_le_bytes());
+    out.extend_from_slice(&(SECTION_COUNT as u16).to_le_bytes());
+    out.push(SECTION_HEIGHT as u8);
+    out.push(CHUNK_AREA_WITH_PADDING as u8);
+    blocks.write_sections(&mut out);
+    out
+}
+
+/// Читает данные чанка любой известной версии и приводит их к текущей высоте мира.
+/// Ошибка означает, что данные повреждены или записаны более новой версией игры.
+pub fn decode_blocks(data: &[u8]) -> Result<BlockStorage> {
+    if let Some(rest) = data.strip_prefix(CHUNK_MAGIC) {
+        return decode_current(rest);
+    }
+    if let Some(rest) = data.strip_prefix(V1_MAGIC) {
+        return BlockStorage::read_sections(rest, V1_SECTION_COUNT, V1_MIN_Y);
+    }
+    if data.len() == V0_HEIGHT * CHUNK_AREA_WITH_PADDING * CHUNK_AREA_WITH_PADDING {
+        return decode_v0(data);
+    }
+    bail!("unrecognized chunk data ({} bytes)", data.len())
+}
+
+fn decode_current(rest: &[u8]) -> Result<BlockStorage> {
+    if rest.len() < HEADER_LEN - CHUNK_MAGIC.len() {
+        bail!("chunk header is truncated");
+    }
+    let (header, body) = rest.split_at(HEADER_LEN - CHUNK_MAGIC.len());
+    let version = u16::from_le_bytes([header[0], header[1]]);
+    if version > CHUNK_FORMAT_VERSION {
+        bail!("chunk format {version} is newer than supported {CHUNK_FORMAT_VERSION}");
+    }
+    let min_y = i16::from_le_bytes([header[2], header[3]]) as i32;
+    let sections = u16::from_le_bytes([header[4], header[5]]) as usize;
+    let (section_height, area) = (header[6] as usize, header[7] as usize);
+    if section_height != SECTION_HEIGHT || area != CHUNK_AREA_WITH_PADDING {
+        bail!("unsupported chunk layout: sections of {section_height}, side {area}");
+    }
+    BlockStorage::read_sections(body, sections, min_y)
+}
+
+/// Поднимает колонку версии 0 на место в мире и подкладывает камень снизу
+/// с коренной породой в нижнем слое; всё выше мира отбрасывается.
+fn decode_v0(data: &[u8]) -> Result<BlockStorage> {
+    let layer = CHUNK_AREA_WITH_PADDING * CHUNK_AREA_WITH_PADDING;
+    let shift = (-WORLD_MIN_Y) as usize * layer;
+    let mut blocks = BlockStorage::new(MaterialType::STONE);
+    for index in 0..layer {
+        blocks.set(index, MaterialType::ROCK);
+    }
+    let fits = (CHUNK_Y_SIZE * layer).saturating_sub(shift);
+    for (index, value) in data.iter().take(fits).enumerate() {
+        blocks.set(index + shift, decode_material(*value)?);
+    }
+    blocks.compact();
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain_gen::storage::SECTION_VOLUME;
+
+    #[test]
+    fn current_and_v1_data_decode() {
+        let mut blocks = BlockStorage::new(MaterialType::AIR);
+        for i in 0..SECTION_VOLUME + 5 {
+            blocks.set(i, MaterialType::ALL[i % MaterialType::ALL.len()]);
+        }
+        blocks.compact();
+        let encoded = encode_blocks(&blocks);
+        assert!(decode_blocks(&encoded).unwrap().iter().eq(blocks.iter()));
+
+        let mut v1 = V1_MAGIC.to_vec();
+        blocks.write_sections(&mut v1);
+        assert!(decode_blocks(&v1).unwrap().iter().eq(blocks.iter()));
+    }
+
+    #[test]
+    fn newer_or_damaged_data_is_rejected() {
+        let blocks = BlockStorage::new(MaterialType::DIRT);
+        let mut newer = encode_blocks(&blocks);
+        newer[4..6].copy_from_slice(&(CHUNK_FORMAT_VERSION + 1).to_le_bytes());
+        assert!(decode_blocks(&newer).is_err());
+
+        let mut damaged = encode_blocks(&blocks);
+        let last = damaged.len() - 1;
+        damaged[last] = 0xEE;
+        assert!(decode_blocks(&damaged).is_err());
+        assert!(decode_blocks(&[1, 2, 3]).is_err());
+    }
+}
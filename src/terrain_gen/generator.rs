@@ -1,34 +1,37 @@
 use std::{
     collections::HashSet,
     collections::VecDeque,
-    sync::{Arc, RwLock},
-    thread,
+    ops::Range,
+    sync::{Arc, Mutex, RwLock},
 };
 
-use crate::core::config::AppConfig;
+use crate::core::{config::AppConfig, level::world_dir};
+use crate::player::camera::{Camera, Frustum};
 use crate::render::pipelines::GlobalsLayouts;
-use crate::terrain_gen::chunk::{CHUNK_AREA, Chunk, ChunkManager};
+use crate::terrain_gen::chunk::{CHUNK_AREA, CHUNK_Y_SIZE, Chunk, ChunkManager, WORLD_MIN_Y};
+use crate::terrain_gen::storage::SECTION_HEIGHT;
 use crate::{
     render::{
         Vertex,
-        atlas::{Atlas, MaterialType},
+        atlas::Atlas,
         mesh::Mesh,
         model::{DynamicModel, Model},
-        pipelines::terrain::{BlockVertex, create_terrain_pipeline},
+        pipelines::terrain::{BlockVertex, create_fluid_pipeline, create_terrain_pipeline},
         renderer::{Draw, Renderer},
     },
-    terrain_gen::biomes::PRAIRIE_PARAMS,
+    terrain_gen::mesher::MeshSettings,
 };
 
 use bytemuck::cast_slice;
-use cgmath::{EuclideanSpace, Point3, Vector3};
+use cgmath::{EuclideanSpace, Vector3};
 use crossbeam_channel::{Receiver, Sender};
-use std::path::PathBuf;
 #[cfg(feature = "tracy")]
 use tracy_client::span;
 use wgpu::Queue;
 
-use super::noise::NoiseGenerator;
+use super::region::RegionStore;
+use super::saver::{ChunkSaver, SaveProgress};
+use super::source::ChunkSource;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -48,6 +51,7 @@ impl Vertex for OutlineVertex {
 
 pub struct TerrainGen {
     pipeline: wgpu::RenderPipeline,
+    fluid_pipeline: wgpu::RenderPipeline,
     highlight_pipeline: wgpu::RenderPipeline,
     atlas: Atlas,
     pub chunks: ChunkManager,
@@ -57,21 +61,38 @@ pub struct TerrainGen {
     center_offset: Vector3<i32>,
     chunks_origin: Vector3<i32>,
     pub chunk_models: Vec<Arc<RwLock<DynamicModel<BlockVertex>>>>,
+    /// Полупрозрачные жидкости чанков, рисуются после всего непрозрачного.
+    fluid_models: Vec<Arc<RwLock<DynamicModel<BlockVertex>>>>,
+    /// Диапазоны индексов по секциям для каждой модели: рисуем только
+    /// непустые секции, попавшие в пирамиду видимости.
+    section_draws: Vec<SectionDraws>,
+    frustum: Option<Frustum>,
+    /// Позиция камеры для сортировки жидкостей от дальних к ближним.
+    camera_pos: Vector3<f32>,
     gen_job_tx: Sender<ChunkJob>,
     remesh_job_tx: Sender<ChunkJob>,
     ready_rx: Receiver<usize>,
     pending_jobs: HashSet<usize>,
-    save_dir: PathBuf,
     dirty_queue: VecDeque<usize>,
     dirty_set: HashSet<usize>,
-    save_tx: Sender<(PathBuf, Vec<u8>)>,
+    /// Чанки, которые надо сшить с соседями: только что пришедшие
+    /// и те, у кого поменялся свет у края.
+    border_sync: HashSet<usize>,
+    saver: ChunkSaver,
+    source: ChunkSource,
     highlight_model: Option<Model<OutlineVertex>>,
     highlight_pos: Option<Vector3<i32>>,
     max_jobs_in_flight: usize,
     max_dirty_per_frame: usize,
     min_vertex_cap: usize,
     min_index_cap: usize,
-    land_level: usize,
+    mesh_settings: MeshSettings,
+}
+
+#[derive(Default, Clone)]
+struct SectionDraws {
+    offset: [i32; 3],
+    ranges: Vec<Range<u32>>,
 }
 
 enum JobKind {
@@ -83,30 +104,25 @@ struct ChunkJob {
     chunk_index: usize,
     chunk: Arc<RwLock<Chunk>>,
     kind: JobKind,
-    save_dir: PathBuf,
-    land_level: usize,
 }
 
 impl TerrainGen {
     pub fn new(renderer: &Renderer, config: &AppConfig) -> Self {
         let render_distance_chunks = config.graphics.render_distance_chunks;
-        let seed = config.world.seed;
         let world_name = &config.world.world_name;
         let tuning = &config.terrain;
 
-        let save_dir = PathBuf::from(format!("saves/{world_name}"));
-        let _ = std::fs::create_dir_all(&save_dir);
+        let save_dir = world_dir(world_name);
         let global_layouts = GlobalsLayouts::new(&renderer.device);
         let atlas = Atlas::new(&renderer.device, &renderer.queue, &global_layouts).unwrap();
         let mut chunk_models = vec![];
+        let mut fluid_models = vec![];
         let mut chunks = ChunkManager::new();
         let chunks_view_size = render_distance_chunks.max(2);
         let chunk_capacity = chunks_view_size * chunks_view_size;
         let chunk_indices: Vec<Option<usize>> = vec![None; chunk_capacity];
         let mut free_chunk_indices = VecDeque::new();
 
-        let noise_gen = NoiseGenerator::new(seed);
-
         for x in 0..chunk_capacity {
             chunks.add_chunk(Chunk::new([0, 0, 0]));
             // Начинаем с небольшого GPU-буфера и при необходимости растим его.
@@ -121,6 +137,12 @@ impl TerrainGen {
                 &chunks.get_chunk(x).unwrap().read().unwrap().mesh,
             );
             chunk_models.push(Arc::new(RwLock::new(chunk_model)));
+            // Воды в чанке обычно мало: буфер растёт по мере надобности.
+            fluid_models.push(Arc::new(RwLock::new(DynamicModel::new(
+                &renderer.device,
+                0,
+                0,
+            ))));
             free_chunk_indices.push_back(x);
         }
 
@@ -134,6 +156,8 @@ impl TerrainGen {
             shader.clone(),
             &renderer.config,
         );
+        let fluid_pipeline =
+            create_fluid_pipeline(&renderer.device, &global_layouts, &shader, &renderer.config);
         let highlight_shader = create_highlight_shader(&renderer.device);
         let highlight_pipeline = create_highlight_pipeline(
             &renderer.device,
@@ -149,43 +173,30 @@ impl TerrainGen {
         let (gen_job_tx, gen_job_rx) = crossbeam_channel::unbounded::<ChunkJob>();
         let (remesh_job_tx, remesh_job_rx) = crossbeam_channel::unbounded::<ChunkJob>();
         let (ready_tx, ready_rx) = crossbeam_channel::unbounded::<usize>();
-        let (save_tx, save_rx) = crossbeam_channel::unbounded::<(PathBuf, Vec<u8>)>();
-        let noise_for_worker = noise_gen.clone();
+        let source = ChunkSource::open(&save_dir, &config.world, tuning.land_level);
         let worker_count = tuning.jobs_in_flight.max(1);
+        let mesh_settings = MeshSettings::from_config(&config.graphics);
 
         for _ in 0..worker_count {
             let remesh_job_rx = remesh_job_rx.clone();
             let gen_job_rx = gen_job_rx.clone();
             let ready_tx = ready_tx.clone();
-            let noise_for_worker = noise_for_worker.clone();
+            let source = source.clone();
 
             std::thread::spawn(move || {
                 let process_job = |job: ChunkJob| {
                     match job.kind {
                         JobKind::Generate { offset } => {
                             if let Ok(mut chunk) = job.chunk.write() {
-                                let path = job.save_dir.join(format!(
-                                    "chunk_{}_{}_{}.bin",
-                                    offset.x, offset.y, offset.z
-                                ));
-                                let loaded =
-                                    path.exists() && chunk.load_from(&path, offset.into()).is_ok();
-                                if !loaded {
-                                    chunk.update_blocks(
-                                        offset.into(),
-                                        &noise_for_worker,
-                                        &PRAIRIE_PARAMS,
-                                        job.land_level,
-                                    );
-                                }
-                                chunk.update_mesh(PRAIRIE_PARAMS, None);
+                                source.load_or_generate(&mut chunk, offset.into());
+                                chunk.update_mesh(&mesh_settings, None);
                                 chunk.dirty = false;
                             }
                         }
                         JobKind::Remesh => {
                             if let Ok(mut chunk) = job.chunk.write() {
                                 let y_range = chunk.dirty_y_range();
-                                chunk.update_mesh(PRAIRIE_PARAMS, y_range);
+                                chunk.update_mesh(&mesh_settings, y_range);
                                 chunk.dirty = false;
                             }
                         }
@@ -208,21 +219,19 @@ impl TerrainGen {
             });
         }
 
-        thread::spawn(move || {
-            while let Ok((path, materials)) = save_rx.recv() {
-                if let Some(parent) = path.parent() {
-                    let _ = std::fs::create_dir_all(parent);
-                }
-                let _ = std::fs::write(path, materials);
-            }
-        });
+        let saver = ChunkSaver::new(source.region());
 
         let mut world = Self {
             pipeline: world_pipeline,
+            fluid_pipeline,
             highlight_pipeline,
             atlas,
             chunks,
+            section_draws: vec![SectionDraws::default(); chunk_capacity],
+            frustum: None,
+            camera_pos: Vector3::new(0.0, 0.0, 0.0),
             chunk_models,
+            fluid_models,
             chunks_view_size,
             center_offset,
             chunks_origin,
@@ -232,17 +241,18 @@ impl TerrainGen {
             remesh_job_tx,
             ready_rx,
             pending_jobs: HashSet::new(),
-            save_dir,
             dirty_queue: VecDeque::new(),
             dirty_set: HashSet::new(),
-            save_tx,
+            border_sync: HashSet::new(),
+            saver,
+            source,
             highlight_model: None,
             highlight_pos: None,
             max_jobs_in_flight: tuning.jobs_in_flight,
             max_dirty_per_frame: tuning.dirty_chunks_per_frame,
             min_vertex_cap: tuning.min_vertex_cap,
             min_index_cap: tuning.min_index_cap,
-            land_level: tuning.land_level,
+            mesh_settings,
         };
 
         println!("about to load first chunks");
@@ -309,11 +319,13 @@ impl TerrainGen {
     }
 
     // вызывается каждый кадр
-    pub fn update(&mut self, device: &wgpu::Device, queue: &Queue, player_position: &Point3<f32>) {
+    pub fn update(&mut self, device: &wgpu::Device, queue: &Queue, camera: &Camera) {
         #[cfg(feature = "tracy")]
         let _span = span!("update_world"); // <- Отметка начала блока
 
-        let new_center_offset = Self::world_pos_to_chunk_offset(player_position.to_vec());
+        self.frustum = Some(camera.frustum());
+        self.camera_pos = camera.position.to_vec();
+        let new_center_offset = Self::world_pos_to_chunk_offset(camera.position.to_vec());
         let new_chunk_origin = new_center_offset
             - Vector3::new(
                 self.chunks_view_size as i32 / 2,
@@ -329,11 +341,11 @@ impl TerrainGen {
             let chunk_indices_copy = self.chunk_indices.read().unwrap().clone();
             let mut new_indices = vec![None; self.chunk_indices.read().unwrap().len()];
 
-            for i in 0..chunk_indices_copy.len() {
-                if let Some(chunk_index) = chunk_indices_copy[i] {
+            for (i, entry) in chunk_indices_copy.iter().enumerate() {
+                if let Some(chunk_index) = *entry {
                     let chunk_offset = self.get_chunk_offset_from_origin(old_origin, i);
-                    if self.chunk_in_bounds(chunk_offset.into()) {
-                        let new_chunk_world_index = self.get_chunk_world_index(chunk_offset.into());
+                    if self.chunk_in_bounds(chunk_offset) {
+                        let new_chunk_world_index = self.get_chunk_world_index(chunk_offset);
                         new_indices[new_chunk_world_index] = Some(chunk_index);
                     } else {
                         if !self.pending_jobs.contains(&chunk_index) {
@@ -347,6 +359,7 @@ impl TerrainGen {
         }
 
         self.process_ready_chunks(device, queue);
+        self.sync_chunk_borders();
         self.process_dirty_chunks(device, queue);
 
         if moved_to_new_chunk || self.has_missing_chunks() {
@@ -362,7 +375,14 @@ impl TerrainGen {
                 .read()
                 .unwrap()
                 .iter()
-                .any(|entry| entry.map_or(false, |idx| idx == chunk_index));
+                .any(|entry| entry.is_some_and(|idx| idx == chunk_index));
+            // Свет у края поменялся — соседям нужен новый паддинг.
+            if mapped
+                && let Some(chunk_arc) = self.chunks.get_chunk(chunk_index)
+                && chunk_arc.write().unwrap().take_light_edge_changed()
+            {
+                self.border_sync.insert(chunk_index);
+            }
 
             if let Some(chunk_model) = self.chunk_models.get(chunk_index) {
                 let (
@@ -373,8 +393,11 @@ impl TerrainGen {
                     offset,
                     layer_data,
                     total_indices,
+                    section_ranges,
+                    fluid_mesh,
                 ) = if let Some(chunk_arc) = self.chunks.get_chunk(chunk_index) {
                     let mut chunk = chunk_arc.write().unwrap();
+                    let fluid_mesh = chunk.take_fluid_changed().then(|| chunk.fluid_mesh.clone());
                     let layout_changed = chunk.layout_changed();
                     let rebuilt_layers = chunk.take_rebuilt_layers();
                     let spans = chunk.layer_spans().to_vec();
@@ -389,10 +412,10 @@ impl TerrainGen {
                     let mut layer_data = Vec::new();
                     if !layout_changed {
                         for &ly in &rebuilt_layers {
-                            if let Some(span) = spans.get(ly).copied() {
-                                if let Some((verts, inds)) = chunk.layer_mesh(ly) {
-                                    layer_data.push((span, verts.to_vec(), inds.to_vec()));
-                                }
+                            if let Some(span) = spans.get(ly).copied()
+                                && let Some((verts, inds)) = chunk.layer_mesh(ly)
+                            {
+                                layer_data.push((span, verts.to_vec(), inds.to_vec()));
                             }
                         }
                     }
@@ -405,6 +428,8 @@ impl TerrainGen {
                         offset,
                         layer_data,
                         total_indices,
+                        chunk.section_ranges(),
+                        fluid_mesh,
                     )
                 } else {
                     (
@@ -415,8 +440,22 @@ impl TerrainGen {
                         [0, 0, 0],
                         Vec::new(),
                         0,
+                        Vec::new(),
+                        None,
                     )
                 };
+                if let Some(fluid_mesh) = fluid_mesh
+                    && let Some(fluid_model) = self.fluid_models.get(chunk_index)
+                {
+                    fluid_model
+                        .write()
+                        .unwrap()
+                        .update(device, queue, &fluid_mesh);
+                }
+                self.section_draws[chunk_index] = SectionDraws {
+                    offset,
+                    ranges: section_ranges,
+                };
 
                 if let Some(mesh) = mesh {
                     let mut chunk_model = chunk_model.write().unwrap();
@@ -495,8 +534,6 @@ impl TerrainGen {
                 chunk_index: idx,
                 chunk: chunk_arc,
                 kind: JobKind::Remesh,
-                save_dir: self.save_dir.clone(),
-                land_level: self.land_level,
             };
 
             self.pending_jobs.insert(idx);
@@ -506,6 +543,30 @@ impl TerrainGen {
         }
     }
 
+    /// Сшивает границы пришедших чанков с соседями и ставит на ремеш тех,
+    /// чей паддинг поменялся. Чанк ждёт, пока ни он, ни его соседи не заняты
+    /// воркерами, чтобы не ждать их блокировок в кадре.
+    fn sync_chunk_borders(&mut self) {
+        let ready: Vec<usize> = self
+            .border_sync
+            .iter()
+            .copied()
+            .filter(|idx| {
+                !self.pending_jobs.contains(idx)
+                    && self
+                        .chunks
+                        .neighbours(*idx)
+                        .iter()
+                        .all(|(n, _)| !self.pending_jobs.contains(n))
+            })
+            .collect();
+        for idx in ready {
+            self.border_sync.remove(&idx);
+            let touched = self.chunks.sync_borders(idx);
+            self.mark_chunks_dirty(&touched);
+        }
+    }
+
     pub fn mark_chunks_dirty(&mut self, indices: &[usize]) {
         for &idx in indices {
             if self.dirty_set.insert(idx) {
@@ -531,15 +592,31 @@ impl TerrainGen {
                     continue;
                 }
                 let y_range = chunk.dirty_y_range();
-                chunk.update_mesh(PRAIRIE_PARAMS, y_range);
+                chunk.update_mesh(&self.mesh_settings, y_range);
                 chunk.dirty = false;
+                if chunk.take_light_edge_changed() {
+                    self.border_sync.insert(idx);
+                }
                 let mesh = chunk.mesh.clone();
+                let fluid_mesh = chunk.take_fluid_changed().then(|| chunk.fluid_mesh.clone());
+                self.section_draws[idx] = SectionDraws {
+                    offset: chunk.offset,
+                    ranges: chunk.section_ranges(),
+                };
                 drop(chunk);
 
                 if let Some(chunk_model) = self.chunk_models.get(idx) {
                     let mut model = chunk_model.write().unwrap();
                     model.update(device, queue, &mesh);
                 }
+                if let Some(fluid_mesh) = fluid_mesh
+                    && let Some(fluid_model) = self.fluid_models.get(idx)
+                {
+                    fluid_model
+                        .write()
+                        .unwrap()
+                        .update(device, queue, &fluid_mesh);
+                }
             }
         }
     }
@@ -576,6 +653,7 @@ impl TerrainGen {
             if let Some(new_index) = self.free_chunk_indices.write().unwrap().pop_front() {
                 self.chunk_indices.write().unwrap()[world_index] = Some(new_index);
                 self.pending_jobs.insert(new_index);
+                self.border_sync.insert(new_index);
                 if let Some(chunk_arc) = self.chunks.get_chunk(new_index) {
                     let job = ChunkJob {
                         chunk_index: new_index,
@@ -583,8 +661,6 @@ impl TerrainGen {
                         kind: JobKind::Generate {
                             offset: chunk_offset,
                         },
-                        save_dir: self.save_dir.clone(),
-                        land_level: self.land_level,
                     };
                     let _ = self.gen_job_tx.send(job);
                 }
@@ -603,36 +679,19 @@ impl TerrainGen {
     }
 
     fn shrink_and_free_chunk(&mut self, device: &wgpu::Device, chunk_index: usize) {
-        if let Some(chunk_arc) = self.chunks.get_chunk(chunk_index) {
-            if let Ok(mut chunk) = chunk_arc.write() {
-                if chunk.needs_save {
-                    let path = self.save_dir.join(format!(
-                        "chunk_{}_{}_{}.bin",
-                        chunk.offset[0], chunk.offset[1], chunk.offset[2]
-                    ));
-                    let materials: Vec<u8> = chunk
-                        .blocks
-                        .iter()
-                        .map(|b| match b.material_type {
-                            MaterialType::DIRT => 0,
-                            MaterialType::GRASS => 1,
-                            MaterialType::ROCK => 2,
-                            MaterialType::WATER => 3,
-                            MaterialType::AIR => 4,
-                            MaterialType::DEBUG => 5,
-                        })
-                        .collect();
-                    let _ = self.save_tx.send((path, materials));
-                    chunk.needs_save = false;
-                }
-            }
-        }
+        self.save_chunk(chunk_index, true);
 
-        if let Some(chunk_model) = self.chunk_models.get(chunk_index) {
-            if let Ok(mut model) = chunk_model.write() {
-                model.shrink_to(device, self.min_vertex_cap, self.min_index_cap);
-            }
+        if let Some(chunk_model) = self.chunk_models.get(chunk_index)
+            && let Ok(mut model) = chunk_model.write()
+        {
+            model.shrink_to(device, self.min_vertex_cap, self.min_index_cap);
+        }
+        if let Some(fluid_model) = self.fluid_models.get(chunk_index)
+            && let Ok(mut model) = fluid_model.write()
+        {
+            model.shrink_to(device, 0, 0);
         }
+        self.section_draws[chunk_index] = SectionDraws::default();
         self.chunks.remove_chunk_from_map(chunk_index);
         self.free_chunk_indices
             .write()
@@ -640,6 +699,67 @@ impl TerrainGen {
             .push_back(chunk_index);
     }
 
+    /// Ставит чанк в очередь записи, если он изменён с последнего сохранения.
+    /// Без `wait` занятый чанк пропускается: `needs_save` остаётся
+    /// до следующего сохранения.
+    fn save_chunk(&self, chunk_index: usize, wait: bool) -> bool {
+        let Some(chunk_arc) = self.chunks.get_chunk(chunk_index) else {
+            return false;
+        };
+        let chunk = if wait {
+            chunk_arc.write().ok()
+        } else {
+            chunk_arc.try_write().ok()
+        };
+        if let Some(mut chunk) = chunk
+            && chunk.needs_save
+        {
+            let column = [chunk.offset[0], chunk.offset[2]];
+            self.saver.queue(column, chunk.block_bytes());
+            chunk.needs_save = false;
+            return true;
+        }
+        false
+    }
+
+    /// Отправляет на запись все изменённые загруженные чанки. Возвращает их число.
+    /// Без `wait` (автосохранение) чанки, занятые фоновыми задачами, ждут
+    /// следующего раза, чтобы кадр не блокировался.
+    pub fn save_modified_chunks(&self, wait: bool) -> usize {
+        let mapped: Vec<usize> = self
+            .chunk_indices
+            .read()
+            .unwrap()
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        mapped
+            .into_iter()
+            .filter(|&idx| wait || !self.pending_jobs.contains(&idx))
+            .filter(|&idx| self.save_chunk(idx, wait))
+            .count()
+    }
+
+    /// Ждёт, пока все поставленные в очередь чанки окажутся на диске.
+    /// Ошибка, если часть чанков записать не удалось.
+    pub fn flush_saves(&self) -> anyhow::Result<()> {
+        self.saver.flush()
+    }
+
+    /// Хранилище регионов мира; его замок держат все, кто пишет регион-файлы.
+    pub fn region(&self) -> Arc<Mutex<RegionStore>> {
+        self.source.region()
+    }
+
+    pub fn quarantined_chunks(&self) -> usize {
+        self.source.quarantined()
+    }
+
+    pub fn save_progress(&self) -> SaveProgress {
+        self.saver.progress()
+    }
+
     pub fn world_pos_in_bounds(&self, world_pos: Vector3<f32>) -> bool {
         let chunk_offset = Self::world_pos_to_chunk_offset(world_pos);
         self.chunk_in_bounds(chunk_offset)
@@ -654,14 +774,32 @@ impl TerrainGen {
             .count()
     }
 
+    /// Память под блоки всех чанков пула. Чанки, занятые воркерами, пропускаем.
+    pub fn block_memory_bytes(&self) -> usize {
+        self.chunks
+            .chunks
+            .iter()
+            .filter_map(|chunk| chunk.try_read().ok().map(|c| c.blocks.memory_bytes()))
+            .sum()
+    }
+
+    /// Память под свет всех чанков пула, как [`Self::block_memory_bytes`].
+    pub fn light_memory_bytes(&self) -> usize {
+        self.chunks
+            .chunks
+            .iter()
+            .filter_map(|chunk| chunk.try_read().ok().map(|c| c.light.memory_bytes()))
+            .sum()
+    }
+
     // индекс массива мировых чанков -> смещение чанка
     fn get_chunk_offset(&self, i: usize) -> Vector3<i32> {
-        return self.chunks_origin
+        self.chunks_origin
             + Vector3::new(
                 i as i32 % self.chunks_view_size as i32,
                 0,
                 i as i32 / self.chunks_view_size as i32,
-            );
+            )
     }
 
     fn get_chunk_offset_from_origin(&self, origin: Vector3<i32>, i: usize) -> Vector3<i32> {
@@ -682,7 +820,7 @@ impl TerrainGen {
         {
             return true;
         }
-        return false;
+        false
     }
 
     fn world_pos_to_chunk_offset(world_pos: Vector3<f32>) -> Vector3<i32> {
@@ -699,6 +837,58 @@ impl TerrainGen {
     }
 }
 
+impl TerrainGen {
+    /// Попадает ли колонка чанка целиком в пирамиду видимости.
+    fn column_visible(&self, offset: [i32; 3]) -> bool {
+        let min = Vector3::new(
+            (offset[0] * CHUNK_AREA as i32) as f32,
+            WORLD_MIN_Y as f32,
+            (offset[2] * CHUNK_AREA as i32) as f32,
+        );
+        let max = min + Vector3::new(CHUNK_AREA as f32, CHUNK_Y_SIZE as f32, CHUNK_AREA as f32);
+        self.frustum
+            .is_none_or(|frustum| frustum.intersects_aabb(min, max))
+    }
+
+    /// Квадрат расстояния по горизонтали от камеры до центра колонки.
+    fn column_distance_sq(&self, offset: [i32; 3]) -> f32 {
+        let half = CHUNK_AREA as f32 / 2.0;
+        let dx = (offset[0] * CHUNK_AREA as i32) as f32 + half - self.camera_pos.x;
+        let dz = (offset[2] * CHUNK_AREA as i32) as f32 + half - self.camera_pos.z;
+        dx * dx + dz * dz
+    }
+
+    /// Непустые секции в пирамиде видимости; соседние секции склеиваются
+    /// в один вызов отрисовки.
+    fn visible_section_runs(&self, sections: &SectionDraws) -> Vec<Range<u32>> {
+        let mut runs: Vec<Range<u32>> = Vec::new();
+        let [ox, _, oz] = sections.offset;
+        for (section, range) in sections.ranges.iter().enumerate() {
+            if range.is_empty() {
+                continue;
+            }
+            let min = Vector3::new(
+                (ox * CHUNK_AREA as i32) as f32,
+                (WORLD_MIN_Y + (section * SECTION_HEIGHT) as i32) as f32,
+                (oz * CHUNK_AREA as i32) as f32,
+            );
+            let max =
+                min + Vector3::new(CHUNK_AREA as f32, SECTION_HEIGHT as f32, CHUNK_AREA as f32);
+            if self
+                .frustum
+                .is_some_and(|frustum| !frustum.intersects_aabb(min, max))
+            {
+                continue;
+            }
+            match runs.last_mut() {
+                Some(last) if last.end == range.start => last.end = range.end,
+                _ => runs.push(range.clone()),
+            }
+        }
+        runs
+    }
+}
+
 impl Draw for TerrainGen {
     fn draw<'a>(
         &'a self,
@@ -715,16 +905,51 @@ impl Draw for TerrainGen {
         // Рисуем только те модели, что реально привязаны к видимым слотам.
         let chunk_indices = self.chunk_indices.read().unwrap();
         for idx_opt in chunk_indices.iter().copied().flatten() {
-            if let Some(chunk_model) = self.chunk_models.get(idx_opt) {
-                let chunk_model = chunk_model.read().unwrap();
-
-                let vertex_buffer = chunk_model.vbuf().slice(..);
-                let index_buffer = chunk_model.ibuf().slice(..);
-                let num_indices = chunk_model.num_indices;
+            let (Some(chunk_model), Some(sections)) = (
+                self.chunk_models.get(idx_opt),
+                self.section_draws.get(idx_opt),
+            ) else {
+                continue;
+            };
+            let runs = self.visible_section_runs(sections);
+            if runs.is_empty() {
+                continue;
+            }
+            let chunk_model = chunk_model.read().unwrap();
+            render_pass.set_vertex_buffer(0, chunk_model.vbuf().slice(..));
+            render_pass.set_index_buffer(chunk_model.ibuf().slice(..), wgpu::IndexFormat::Uint32);
+            for run in runs {
+                render_pass.draw_indexed(run, 0, 0..1);
+            }
+        }
 
-                render_pass.set_vertex_buffer(0, vertex_buffer);
-                render_pass.set_index_buffer(index_buffer, wgpu::IndexFormat::Uint32);
-                render_pass.draw_indexed(0..num_indices, 0, 0..1 as _);
+        // Жидкости после непрозрачного, от дальних чанков к ближним,
+        // чтобы смешивание по альфе шло в правильном порядке.
+        let mut fluids: Vec<(f32, usize)> = chunk_indices
+            .iter()
+            .copied()
+            .flatten()
+            .filter(|&idx| {
+                let has_fluid = self
+                    .fluid_models
+                    .get(idx)
+                    .is_some_and(|model| model.read().unwrap().num_indices > 0);
+                has_fluid
+                    && self
+                        .section_draws
+                        .get(idx)
+                        .is_some_and(|sections| self.column_visible(sections.offset))
+            })
+            .map(|idx| (self.column_distance_sq(self.section_draws[idx].offset), idx))
+            .collect();
+        fluids.sort_by(|a, b| b.0.total_cmp(&a.0));
+        if !fluids.is_empty() {
+            render_pass.set_pipeline(&self.fluid_pipeline);
+            for (_, idx) in fluids {
+                let model = self.fluid_models[idx].read().unwrap();
+                render_pass.set_vertex_buffer(0, model.vbuf().slice(..));
+                render_pass.set_index_buffer(model.ibuf().slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..model.num_indices, 0, 0..1);
             }
         }
 
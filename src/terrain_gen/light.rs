@@ -0,0 +1,487 @@
+use std::collections::VecDeque;
+
+use super::{
+    chunk::{CHUNK_AREA, CHUNK_AREA_WITH_PADDING, CHUNK_Y_SIZE},
+    storage::{BlockStorage, SECTION_COUNT, SECTION_HEIGHT, SECTION_VOLUME},
+};
+
+/// Наибольший уровень света в канале.
+pub const MAX_LIGHT: u8 = 15;
+/// Открытое небо без света блоков.
+pub const FULL_SKY: u8 = MAX_LIGHT << 4;
+
+const LAYER: usize = CHUNK_AREA_WITH_PADDING * CHUNK_AREA_WITH_PADDING;
+
+/// Канал света: небо или светящиеся блоки.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LightChannel {
+    Sky,
+    Block,
+}
+
+impl LightChannel {
+    const ALL: [Self; 2] = [Self::Sky, Self::Block];
+
+    /// Уровень канала из упакованного значения.
+    pub fn get(self, packed: u8) -> u8 {
+        match self {
+            LightChannel::Sky => packed >> 4,
+            LightChannel::Block => packed & 0x0F,
+        }
+    }
+
+    fn with(self, packed: u8, level: u8) -> u8 {
+        match self {
+            LightChannel::Sky => (packed & 0x0F) | (level << 4),
+            LightChannel::Block => (packed & 0xF0) | level,
+        }
+    }
+}
+
+/// Свет колонки с паддингом, индексы те же, что у блоков (`y * 18 * 18 + x * 18 + z`).
+/// Старшие 4 бита — небо, младшие — свет блоков. Однородные секции (открытое небо
+/// над рельефом, темнота в толще камня) хранят одно значение.
+#[derive(Clone)]
+pub struct LightMap {
+    sections: Vec<LightSection>,
+}
+
+#[derive(Clone)]
+enum LightSection {
+    Uniform(u8),
+    Data(Box<[u8]>),
+}
+
+impl LightMap {
+    pub fn new(fill: u8) -> Self {
+        Self {
+            sections: vec![LightSection::Uniform(fill); SECTION_COUNT],
+        }
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        match &self.sections[index / SECTION_VOLUME] {
+            LightSection::Uniform(value) => *value,
+            LightSection::Data(data) => data[index % SECTION_VOLUME],
+        }
+    }
+
+    pub fn set(&mut self, index: usize, value: u8) {
+        let slot = &mut self.sections[index / SECTION_VOLUME];
+        match slot {
+            LightSection::Uniform(current) if *current == value => {}
+            LightSection::Uniform(current) => {
+                let mut data = vec![*current; SECTION_VOLUME].into_boxed_slice();
+                data[index % SECTION_VOLUME] = value;
+                *slot = LightSection::Data(data);
+            }
+            LightSection::Data(data) => data[index % SECTION_VOLUME] = value,
+        }
+    }
+
+    /// Однороден ли свет секции целиком, и каким значением.
+    pub fn uniform_section(&self, section: usize) -> Option<u8> {
+        match self.sections.get(section)? {
+            LightSection::Uniform(value) => Some(*value),
+            LightSection::Data(_) => None,
+        }
+    }
+
+    /// Сворачивает секции, ставшие однородными.
+    pub fn compact(&mut self) {
+        for slot in &mut self.sections {
+            if let LightSection::Data(data) = slot
+                && data.iter().all(|&v| v == data[0])
+            {
+                *slot = LightSection::Uniform(data[0]);
+            }
+        }
+    }
+
+    /// Примерный объём памяти под свет, в байтах.
+    pub fn memory_bytes(&self) -> usize {
+        self.sections
+            .iter()
+            .map(|s| match s {
+                LightSection::Uniform(_) => std::mem::size_of::<LightSection>(),
+                LightSection::Data(data) => std::mem::size_of::<LightSection>() + data.len(),
+            })
+            .sum()
+    }
+}
+
+impl Default for LightMap {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Отложенные обновления света чанка. Копятся между ремешами и применяются
+/// там же, где строится меш, — на воркере.
+#[derive(Clone, Default)]
+pub struct PendingLight {
+    /// Пересчитать всё с нуля (новые блоки после генерации или загрузки).
+    pub full: bool,
+    /// Индексы изменённых блоков.
+    pub edits: Vec<usize>,
+    /// Клетки паддинга, куда пришёл свет соседа, со старым значением.
+    pub border: Vec<(usize, u8)>,
+}
+
+impl PendingLight {
+    pub fn full() -> Self {
+        Self {
+            full: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.full && self.edits.is_empty() && self.border.is_empty()
+    }
+}
+
+/// Что поменял пересчёт: диапазон слоёв и задет ли свет у края чанка,
+/// который видят соседи в своём паддинге.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LightChange {
+    pub layers: Option<(usize, usize)>,
+    pub edge: bool,
+}
+
+/// Применяет отложенные обновления к свету колонки.
+pub fn update(blocks: &BlockStorage, light: &mut LightMap, pending: &PendingLight) -> LightChange {
+    let mut flood = Flood::new(blocks, light);
+    if pending.full {
+        flood.relight();
+    } else {
+        for &index in &pending.edits {
+            flood.block_changed(index);
+        }
+        for &(index, old) in &pending.border {
+            flood.border_changed(index, old);
+        }
+    }
+    let change = flood.change;
+    light.compact();
+    change
+}
+
+/// Заливка света в одной колонке с паддингом. Паддинг считается как обычные
+/// клетки; настоящие значения соседей приходят в него при сшивке границ
+/// (`ChunkManager::sync_borders`) и расходятся отсюда как обновления границы.
+struct Flood<'a> {
+    blocks: &'a BlockStorage,
+    light: &'a mut LightMap,
+    change: LightChange,
+}
+
+impl<'a> Flood<'a> {
+    fn new(blocks: &'a BlockStorage, light: &'a mut LightMap) -> Self {
+        Self {
+            blocks,
+            light,
+            change: LightChange::default(),
+        }
+    }
+
+    /// Полный пересчёт: столбы неба сверху до первого непрозрачного блока,
+    /// затем растекание неба вбок и вниз и свет от излучающих блоков.
+    fn relight(&mut self) {
+        *self.light = LightMap::new(0);
+        let mut tops = Vec::with_capacity(LAYER);
+        for column in 0..LAYER {
+            let top = (0..CHUNK_Y_SIZE)
+                .rev()
+                .find(|&y| self.opacity(y * LAYER + column) > 0);
+            tops.push(top);
+        }
+        // Выше самого высокого блока — открытое небо целыми секциями.
+        let open_from = tops
+            .iter()
+            .map(|top| top.map_or(0, |y| y + 1))
+            .max()
+            .unwrap_or(0);
+        let open_section = open_from.div_ceil(SECTION_HEIGHT);
+        for section in open_section..SECTION_COUNT {
+            self.light.sections[section] = LightSection::Uniform(FULL_SKY);
+        }
+        let open_y = open_section * SECTION_HEIGHT;
+
+        let mut sky = VecDeque::new();
+        for (column, top) in tops.iter().enumerate() {
+            let bottom = top.map_or(0, |y| y + 1);
+            for y in bottom..open_y {
+                let index = y * LAYER + column;
+                self.light.set(index, FULL_SKY);
+                sky.push_back(index);
+            }
+        }
+        self.increase(LightChannel::Sky, sky);
+
+        let mut emitters = VecDeque::new();
+        for section in 0..SECTION_COUNT {
+            if self
+                .blocks
+                .uniform_section(section)
+                .is_some_and(|m| m.light_emission() == 0)
+            {
+                continue;
+            }
+            for index in section * SECTION_VOLUME..(section + 1) * SECTION_VOLUME {
+                let emission = self.blocks.get(index).light_emission();
+                if emission > 0 {
+                    self.set_level(LightChannel::Block, index, emission);
+                    emitters.push_back(index);
+                }
+            }
+        }
+        self.increase(LightChannel::Block, emitters);
+        self.change = LightChange {
+            layers: Some((0, CHUNK_Y_SIZE - 1)),
+            edge: true,
+        };
+    }
+
+    /// Блок `index` поменялся: убираем его старый свет и заново собираем
+    /// свет от соседей и от самого блока, если он светится.
+    fn block_changed(&mut self, index: usize) {
+        for channel in LightChannel::ALL {
+            let old = self.level(channel, index);
+            let emission = match channel {
+                LightChannel::Sky => 0,
+                LightChannel::Block => self.blocks.get(index).light_emission(),
+            };
+            self.set_level(channel, index, emission);
+            let mut sources = if old > 0 {
+                self.decrease(channel, vec![(index, old)])
+            } else {
+                VecDeque::new()
+            };
+            if emission > 0 {
+                sources.push_back(index);
+            }
+            for (neighbour, _) in neighbours(index) {
+                if self.level(channel, neighbour) > 0 {
+                    sources.push_back(neighbour);
+                }
+            }
+            self.increase(channel, sources);
+        }
+    }
+
+    /// В клетку паддинга пришло значение соседа вместо `old`.
+    fn border_changed(&mut self, index: usize, old: u8) {
+        for channel in LightChannel::ALL {
+            let (old, new) = (channel.get(old), self.level(channel, index));
+            let mut sources = if new < old {
+                self.decrease(channel, vec![(index, old)])
+            } else {
+                VecDeque::new()
+            };
+            if new > 0 {
+                sources.push_back(index);
+            }
+            self.increase(channel, sources);
+        }
+    }
+
+    /// Растекание света от клеток очереди.
+    fn increase(&mut self, channel: LightChannel, mut queue: VecDeque<usize>) {
+        while let Some(index) = queue.pop_front() {
+            let level = self.level(channel, index);
+            for (neighbour, down) in neighbours(index) {
+                let opacity = self.opacity(neighbour);
+                if opacity >= MAX_LIGHT {
+                    continue;
+                }
+                // Небо в полную силу опускается по прозрачному столбу без потерь.
+                let spread =
+                    if channel == LightChannel::Sky && down && level == MAX_LIGHT && opacity == 0 {
+                        MAX_LIGHT
+                    } else {
+                        level.saturating_sub(1 + opacity)
+                    };
+                if spread > self.level(channel, neighbour) {
+                    self.set_level(channel, neighbour, spread);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+
+    /// Гасит свет, пришедший от клеток `seeds` с их прежними уровнями.
+    /// Возвращает клетки, светящиеся сами по себе, — от них свет надо разлить заново.
+    fn decrease(&mut self, channel: LightChannel, seeds: Vec<(usize, u8)>) -> VecDeque<usize> {
+        let mut sources = VecDeque::new();
+        let mut queue: VecDeque<(usize, u8)> = seeds.into();
+        while let Some((index, old)) = queue.pop_front() {
+            for (neighbour, down) in neighbours(index) {
+                let level = self.level(channel, neighbour);
+                if level == 0 {
+                    continue;
+                }
+                let lit_from_here = level < old
+                    || (channel == LightChannel::Sky
+                        && down
+                        && old == MAX_LIGHT
+                        && level == MAX_LIGHT);
+                if !lit_from_here {
+                    sources.push_back(neighbour);
+                    continue;
+                }
+                let emission = match channel {
+                    LightChannel::Sky => 0,
+                    LightChannel::Block => self.blocks.get(neighbour).light_emission(),
+                };
+                self.set_level(channel, neighbour, emission);
+                queue.push_back((neighbour, level));
+                if emission > 0 {
+                    sources.push_back(neighbour);
+                }
+            }
+        }
+        sources
+    }
+
+    fn opacity(&self, index: usize) -> u8 {
+        self.blocks.get(index).light_opacity()
+    }
+
+    fn level(&self, channel: LightChannel, index: usize) -> u8 {
+        channel.get(self.light.get(index))
+    }
+
+    fn set_level(&mut self, channel: LightChannel, index: usize, level: u8) {
+        let packed = self.light.get(index);
+        if channel.get(packed) == level {
+            return;
+        }
+        self.light.set(index, channel.with(packed, level));
+        let y = index / LAYER;
+        self.change.layers = Some(match self.change.layers {
+            Some((lo, hi)) => (lo.min(y), hi.max(y)),
+            None => (y, y),
+        });
+        let (x, z) = (
+            (index % LAYER) / CHUNK_AREA_WITH_PADDING,
+            index % CHUNK_AREA_WITH_PADDING,
+        );
+        if [x, z].iter().any(|&c| c == 1 || c == CHUNK_AREA) {
+            self.change.edge = true;
+        }
+    }
+}
+
+/// Соседи клетки внутри колонки с паддингом и флаг «сосед снизу».
+fn neighbours(index: usize) -> impl Iterator<Item = (usize, bool)> {
+    let y = index / LAYER;
+    let x = (index % LAYER) / CHUNK_AREA_WITH_PADDING;
+    let z = index % CHUNK_AREA_WITH_PADDING;
+    let last = CHUNK_AREA_WITH_PADDING - 1;
+    [
+        (y + 1 < CHUNK_Y_SIZE).then(|| (index + LAYER, false)),
+        (y > 0).then(|| (index - LAYER, true)),
+        (x < last).then(|| (index + CHUNK_AREA_WITH_PADDING, false)),
+        (x > 0).then(|| (index - CHUNK_AREA_WITH_PADDING, false)),
+        (z < last).then(|| (index + 1, false)),
+        (z > 0).then(|| (index - 1, false)),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::atlas::MaterialType;
+
+    fn index(y: usize, x: usize, z: usize) -> usize {
+        y * LAYER + x * CHUNK_AREA_WITH_PADDING + z
+    }
+
+    /// Каменный пол до y = 100 с запечатанной пещерой внутри.
+    fn ground() -> BlockStorage {
+        let mut blocks = BlockStorage::new(MaterialType::AIR);
+        for y in 0..=100 {
+            for column in 0..LAYER {
+                blocks.set(y * LAYER + column, MaterialType::STONE);
+            }
+        }
+        for y in 80..84 {
+            for x in 4..12 {
+                for z in 4..12 {
+                    blocks.set(index(y, x, z), MaterialType::AIR);
+                }
+            }
+        }
+        blocks
+    }
+
+    fn relit(blocks: &BlockStorage) -> LightMap {
+        let mut light = LightMap::default();
+        update(blocks, &mut light, &PendingLight::full());
+        light
+    }
+
+    #[test]
+    fn sky_fills_open_air_and_lava_lights_a_sealed_cave() {
+        let mut blocks = ground();
+        let light = relit(&blocks);
+        assert_eq!(light.get(index(101, 5, 5)), FULL_SKY);
+        assert_eq!(light.get(index(CHUNK_Y_SIZE - 1, 0, 17)), FULL_SKY);
+        assert_eq!(light.get(index(81, 6, 6)), 0);
+
+        blocks.set(index(80, 4, 4), MaterialType::LAVA);
+        let light = relit(&blocks);
+        assert_eq!(LightChannel::Block.get(light.get(index(80, 4, 4))), 15);
+        assert_eq!(LightChannel::Block.get(light.get(index(80, 5, 4))), 14);
+        assert_eq!(LightChannel::Block.get(light.get(index(81, 6, 5))), 11);
+        assert_eq!(LightChannel::Sky.get(light.get(index(81, 6, 5))), 0);
+    }
+
+    #[test]
+    fn incremental_updates_match_full_relight() {
+        let mut blocks = ground();
+        let mut light = relit(&blocks);
+        let edits: [(usize, usize, usize, MaterialType); 6] = [
+            // Лава в пещере, потом дыра в потолке к небу.
+            (82, 10, 10, MaterialType::LAVA),
+            (84, 8, 8, MaterialType::AIR),
+            (85, 8, 8, MaterialType::AIR),
+            (99, 8, 8, MaterialType::LEAVES),
+            // Гасим лаву и закрываем шахту.
+            (82, 10, 10, MaterialType::AIR),
+            (84, 8, 8, MaterialType::STONE),
+        ];
+        for y in 86..=100 {
+            blocks.set(index(y, 8, 8), MaterialType::AIR);
+        }
+        let mut pending = PendingLight::default();
+        pending.edits.extend((86..=100).map(|y| index(y, 8, 8)));
+        update(&blocks, &mut light, &pending);
+        for (y, x, z, material) in edits {
+            blocks.set(index(y, x, z), material);
+            let pending = PendingLight {
+                edits: vec![index(y, x, z)],
+                ..PendingLight::default()
+            };
+            let before = light.clone();
+            let change = update(&blocks, &mut light, &pending);
+            let changed = (0..CHUNK_Y_SIZE * LAYER).any(|i| light.get(i) != before.get(i));
+            assert_eq!(
+                change.layers.is_some(),
+                changed,
+                "{material:?} at {y},{x},{z}"
+            );
+            let full = relit(&blocks);
+            for i in 0..CHUNK_Y_SIZE * LAYER {
+                assert_eq!(
+                    light.get(i),
+                    full.get(i),
+                    "after {material:?} at {y},{x},{z}: {i}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use image::{GrayImage, Luma, Rgb, RgbImage};
+use rayon::prelude::*;
+
+use crate::render::atlas::MaterialType;
+
+use super::{
+    chunk::{CHUNK_AREA, Chunk},
+    noise::NoiseGenerator,
+    preset::WorldGenPreset,
+    storage::{SECTION_COUNT, SECTION_HEIGHT},
+    water::WaterMap,
+};
+
+/// Чем раскрашивается карта сверху.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MapColoring {
+    /// Цвет верхнего непрозрачного блока; вода темнеет с глубиной.
+    Surface,
+    /// Цвет доминирующего биома колонки, см. [`biome_color`].
+    Biome,
+}
+
+/// Прямоугольник мира в блоках: `min` включительно, `size` по x и z.
+#[derive(Copy, Clone, Debug)]
+pub struct MapArea {
+    pub min: [i32; 2],
+    pub size: [u32; 2],
+}
+
+/// Карта участка: цвет и высота поверхности. В карте высот один уровень
+/// серого на блок: чёрный — `y = -64`, белый — `y = 191` и выше, чтобы
+/// карты разных сидов и версий генератора можно было сравнивать попиксельно.
+pub struct WorldMap {
+    pub colors: RgbImage,
+    pub heights: GrayImage,
+}
+
+/// Рисует участок мира теми же чанками, что строит игра (`Chunk::update_blocks`),
+/// поэтому пещеры, деревья и вода на карте совпадают с миром.
+pub fn render_map(
+    noise: &NoiseGenerator,
+    preset: &WorldGenPreset,
+    area: MapArea,
+    coloring: MapColoring,
+) -> WorldMap {
+    let side = CHUNK_AREA as i32;
+    let max = [
+        area.min[0] + area.size[0] as i32 - 1,
+        area.min[1] + area.size[1] as i32 - 1,
+    ];
+    let chunks: Vec<[i32; 3]> = (area.min[0].div_euclid(side)..=max[0].div_euclid(side))
+        .flat_map(|cx| {
+            (area.min[1].div_euclid(side)..=max[1].div_euclid(side)).map(move |cz| [cx, 0, cz])
+        })
+        .collect();
+
+    let columns: Vec<([i32; 2], Rgb<u8>, u8)> = chunks
+        .par_iter()
+        .flat_map_iter(|&offset| {
+            let mut chunk = Chunk::new(offset);
+            chunk.update_blocks(offset, noise, preset);
+            let origin = [offset[0] * side, offset[2] * side];
+            let water = (coloring == MapColoring::Biome).then(|| {
+                WaterMap::around(
+                    preset,
+                    noise,
+                    origin,
+                    [origin[0] + side - 1, origin[1] + side - 1],
+                )
+            });
+            let mut out = Vec::with_capacity(CHUNK_AREA * CHUNK_AREA);
+            for x in 0..side {
+                for z in 0..side {
+                    let pos = [origin[0] + x, origin[1] + z];
+                    if pos[0] < area.min[0] || pos[0] > max[0] {
+                        continue;
+                    }
+                    if pos[1] < area.min[1] || pos[1] > max[1] {
+                        continue;
+                    }
+                    let (x, z) = (x as usize + 1, z as usize + 1);
+                    let (top, material) = surface(&chunk, x, z);
+                    let color = match &water {
+                        Some(water) => {
+                            let sample = water.sample(preset, noise, pos[0] as f32, pos[1] as f32);
+                            biome_color(sample.biome)
+                        }
+                        None if material == MaterialType::WATER => {
+                            let depth = (top - floor_below(&chunk, x, z, top)) as f32;
+                            shade(material_color(material), 1.0 - (depth / 24.0).min(0.6))
+                        }
+                        None => material_color(material),
+                    };
+                    // Локальная высота и есть отсчёт от дна мира.
+                    out.push((pos, color, top.min(255) as u8));
+                }
+            }
+            out
+        })
+        .collect();
+
+    let mut colors = RgbImage::new(area.size[0], area.size[1]);
+    let mut heights = GrayImage::new(area.size[0], area.size[1]);
+    for (pos, color, height) in columns {
+        let px = (pos[0] - area.min[0]) as u32;
+        let pz = (pos[1] - area.min[1]) as u32;
+        colors.put_pixel(px, pz, color);
+        heights.put_pixel(px, pz, Luma([height]));
+    }
+    WorldMap { colors, heights }
+}
+
+/// Локальная высота и материал верхнего не-воздушного блока колонки.
+fn surface(chunk: &Chunk, x: usize, z: usize) -> (usize, MaterialType) {
+    for section in (0..SECTION_COUNT).rev() {
+        if chunk.blocks.uniform_section(section) == Some(MaterialType::AIR) {
+            continue;
+        }
+        let start = section * SECTION_HEIGHT;
+        for y in (start..start + SECTION_HEIGHT).rev() {
+            let material = chunk.get_block(y, x, z).unwrap();
+            if material != MaterialType::AIR {
+                return (y, material);
+            }
+        }
+    }
+    (0, MaterialType::AIR)
+}
+
+/// Высота дна под водной гладью `top`.
+fn floor_below(chunk: &Chunk, x: usize, z: usize, top: usize) -> usize {
+    (0..top)
+        .rev()
+        .find(|&y| chunk.get_block(y, x, z) != Some(MaterialType::WATER))
+        .unwrap_or(0)
+}
+
+fn shade(color: Rgb<u8>, factor: f32) -> Rgb<u8> {
+    Rgb(color.0.map(|c| (c as f32 * factor) as u8))
+}
+
+pub fn material_color(material: MaterialType) -> Rgb<u8> {
+    Rgb(match material {
+        MaterialType::DIRT => [134, 96, 67],
+        MaterialType::GRASS => [95, 159, 53],
+        MaterialType::ROCK => [60, 60, 60],
+        MaterialType::WATER => [48, 96, 200],
+        MaterialType::AIR => [0, 0, 0],
+        MaterialType::DEBUG => [255, 0, 255],
+        MaterialType::SAND => [219, 207, 142],
+        MaterialType::SNOW => [240, 244, 250],
+        MaterialType::LOG => [102, 81, 50],
+        MaterialType::LEAVES => [55, 120, 40],
+        MaterialType::STONE => [125, 125, 125],
+        MaterialType::COAL => [45, 45, 45],
+        MaterialType::IRON => [170, 140, 120],
+        MaterialType::GOLD => [230, 200, 60],
+        MaterialType::LAVA => [207, 92, 20],
+    })
+}
+
+/// Цвет биома по его индексу в пресете; палитра повторяется после десяти.
+pub fn biome_color(biome: usize) -> Rgb<u8> {
+    const PALETTE: [[u8; 3]; 10] = [
+        [110, 180, 70],
+        [150, 150, 150],
+        [230, 210, 130],
+        [70, 140, 90],
+        [210, 230, 240],
+        [190, 120, 60],
+        [90, 110, 200],
+        [200, 90, 140],
+        [120, 80, 170],
+        [60, 170, 170],
+    ];
+    Rgb(PALETTE[biome % PALETTE.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain_gen::chunk::CHUNK_Y_SIZE;
+
+    #[test]
+    fn map_matches_generated_chunks() {
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(3);
+        let area = MapArea {
+            min: [-20, 5],
+            size: [24, 3],
+        };
+        let map = render_map(&noise, &preset, area, MapColoring::Surface);
+        assert_eq!(map.colors.dimensions(), (24, 3));
+
+        // Колонка (-20, 6) лежит в чанке (-2, 0) с локальными x = 12, z = 6.
+        let mut chunk = Chunk::new([-2, 0, 0]);
+        chunk.update_blocks([-2, 0, 0], &noise, &preset);
+        let (top, material) = surface(&chunk, 13, 7);
+        assert_eq!(map.heights.get_pixel(0, 1).0[0] as usize, top.min(255));
+        if material != MaterialType::WATER {
+            assert_eq!(*map.colors.get_pixel(0, 1), material_color(material));
+        }
+        assert!(top < CHUNK_Y_SIZE);
+    }
+}
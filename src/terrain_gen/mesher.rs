@@ -0,0 +1,567 @@
+use crate::{
+    core::config::GraphicsConfig,
+    render::{atlas::MaterialType, pipelines::terrain::BlockVertex},
+};
+
+use super::{
+    block::{Direction, Quad},
+    chunk::{CHUNK_AREA, CHUNK_Y_SIZE, Chunk, WORLD_MIN_Y},
+    light::{FULL_SKY, LightChannel, MAX_LIGHT},
+};
+
+/// Настройки затенения меша из [`GraphicsConfig`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MeshSettings {
+    pub ambient_occlusion: bool,
+    pub ao_strength: f32,
+    pub face_shading: bool,
+    pub lighting: bool,
+    pub smooth_lighting: bool,
+}
+
+impl MeshSettings {
+    pub fn from_config(graphics: &GraphicsConfig) -> Self {
+        Self {
+            ambient_occlusion: graphics.ambient_occlusion,
+            ao_strength: graphics.ao_strength.clamp(0.0, 1.0),
+            face_shading: graphics.face_shading,
+            lighting: graphics.lighting,
+            smooth_lighting: graphics.smooth_lighting,
+        }
+    }
+
+    /// Яркость вершины по числу открытых сторон угла (0..=3).
+    fn ao_brightness(&self, ao: u8) -> f32 {
+        1.0 - self.ao_strength * (3 - ao) as f32 / 3.0
+    }
+}
+
+impl Default for MeshSettings {
+    fn default() -> Self {
+        Self::from_config(&GraphicsConfig::default())
+    }
+}
+
+/// Насколько верх жидкости под воздухом ниже края блока.
+pub const FLUID_SURFACE_DROP: f32 = 0.125;
+
+/// Проход отрисовки: непрозрачные блоки или полупрозрачные жидкости.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum MeshPass {
+    Opaque,
+    Fluid,
+}
+
+/// Меш одного слоя чанка; слои складываются в общий буфер через `LayerSpan`.
+#[derive(Default, Clone)]
+pub(super) struct LayerMesh {
+    pub verts: Vec<BlockVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl LayerMesh {
+    fn push_quad(&mut self, quad: &Quad) {
+        let base = self.verts.len() as u32;
+        self.verts.extend_from_slice(&quad.vertices);
+        self.indices.extend_from_slice(&quad.get_indices_v(base));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verts.is_empty()
+    }
+}
+
+/// Что должно совпасть у соседних граней, чтобы их склеить: материал,
+/// AO и свет углов в порядке [`Direction::corners`], опущен ли верх жидкости.
+#[derive(Copy, Clone, PartialEq)]
+struct FaceKey {
+    material: MaterialType,
+    ao: [u8; 4],
+    light: [u8; 4],
+    surface: bool,
+}
+
+impl FaceKey {
+    /// Грань с разным AO или светом по углам склеивать нельзя: градиент
+    /// растянулся бы на всю грань.
+    fn mergeable(&self) -> bool {
+        self.ao.iter().all(|&ao| ao == self.ao[0])
+            && self.light.iter().all(|&light| light == self.light[0])
+    }
+}
+
+type FaceMask = [[Option<FaceKey>; CHUNK_AREA]; CHUNK_AREA];
+
+/// Прямоугольник склеенных граней в сетке слоя: `x`, `z` — угол (0..16), `w`, `d` — размер.
+struct FaceRect {
+    x: usize,
+    z: usize,
+    w: usize,
+    d: usize,
+    key: FaceKey,
+}
+
+/// Видимые грани слоя `y` прохода `pass`, склеенные жадно. Верх и низ собираются
+/// в прямоугольники по x и z; боковые грани слоя высотой в один блок
+/// склеиваются только вдоль своей плоскости. Слой не зависит от соседних
+/// слоёв, поэтому частичная перезаливка по `LayerSpan` продолжает работать.
+pub(super) fn greedy_layer(
+    chunk: &Chunk,
+    y: usize,
+    settings: &MeshSettings,
+    pass: MeshPass,
+) -> LayerMesh {
+    let mut layer = LayerMesh::default();
+    for side in Direction::ALL {
+        let mask = face_mask(chunk, y, side, settings, pass);
+        let (along_x, along_z) = match side {
+            Direction::TOP | Direction::BOTTOM => (true, true),
+            Direction::RIGHT | Direction::LEFT => (false, true),
+            Direction::FRONT | Direction::BACK => (true, false),
+        };
+        let face_shade = if settings.face_shading {
+            side.brightness()
+        } else {
+            1.0
+        };
+        for rect in merge_faces(&mask, along_x, along_z) {
+            let mut quad = Quad::sized(
+                rect.key.material,
+                side,
+                world_position(chunk, rect.x, y, rect.z),
+                [rect.w as i32, 1, rect.d as i32],
+            );
+            let corners = side.corners();
+            for (i, vertex) in quad.vertices.iter_mut().enumerate() {
+                if rect.key.surface && corners[i].0[1] == 1 {
+                    vertex.pos[1] -= FLUID_SURFACE_DROP;
+                }
+                vertex.ao = settings.ao_brightness(rect.key.ao[i]);
+                vertex.face_shade = face_shade;
+                let light = rect.key.light[i];
+                vertex.light = [LightChannel::Sky, LightChannel::Block]
+                    .map(|channel| channel.get(light) as f32 / MAX_LIGHT as f32);
+            }
+            // Делим по более светлой диагонали, иначе тёмный угол
+            // расползается треугольником на половину грани.
+            let ao = rect.key.ao;
+            quad.flipped = ao[0] + ao[2] < ao[1] + ao[3];
+            layer.push_quad(&quad);
+        }
+    }
+    layer
+}
+
+/// Ключ каждой видимой грани направления `side` в слое для прохода `pass`.
+fn face_mask(
+    chunk: &Chunk,
+    y: usize,
+    side: Direction,
+    settings: &MeshSettings,
+    pass: MeshPass,
+) -> FaceMask {
+    let mut mask = [[None; CHUNK_AREA]; CHUNK_AREA];
+    let normal = side.to_vec();
+    let fluid = pass == MeshPass::Fluid;
+    for (x, row) in mask.iter_mut().enumerate() {
+        for (z, cell) in row.iter_mut().enumerate() {
+            let block = chunk.get_block(y, x + 1, z + 1).unwrap();
+            if block == MaterialType::AIR || block.is_fluid() != fluid {
+                continue;
+            }
+            let neighbour = [
+                x as i32 + 1 + normal.x,
+                y as i32 + normal.y,
+                z as i32 + 1 + normal.z,
+            ];
+            if !face_visible(chunk, neighbour, pass) {
+                continue;
+            }
+            let surface = fluid
+                && !chunk
+                    .get_block(y + 1, x + 1, z + 1)
+                    .is_some_and(|above| above.is_fluid());
+            let ao = if settings.ambient_occlusion {
+                side.corners()
+                    .map(|(corner, _)| vertex_ao(chunk, neighbour, side, corner))
+            } else {
+                [3; 4]
+            };
+            let light = if !settings.lighting {
+                [FULL_SKY; 4]
+            } else if settings.smooth_lighting {
+                side.corners()
+                    .map(|(corner, _)| vertex_light(chunk, neighbour, side, corner))
+            } else {
+                [light_at(chunk, neighbour); 4]
+            };
+            *cell = Some(FaceKey {
+                material: block,
+                ao,
+                light,
+                surface,
+            });
+        }
+    }
+    mask
+}
+
+/// Грань видна, если за ней прозрачный блок или край мира по высоте.
+/// Сквозь листву видно всё, сквозь жидкость — только твёрдые блоки:
+/// жидкость с жидкостью не граничит.
+fn face_visible(chunk: &Chunk, neighbour: [i32; 3], pass: MeshPass) -> bool {
+    if !(0..CHUNK_Y_SIZE as i32).contains(&neighbour[1]) {
+        return true;
+    }
+    let block = chunk.get_block(
+        neighbour[1] as usize,
+        neighbour[0] as usize,
+        neighbour[2] as usize,
+    );
+    match pass {
+        MeshPass::Opaque => block.is_some_and(|b| b.is_transparent()),
+        MeshPass::Fluid => block.is_some_and(|b| b.is_transparent() && !b.is_fluid()),
+    }
+}
+
+/// Классический воксельный AO: сколько из двух боковых и углового соседа
+/// перед гранью не заслоняют вершину (0..=3). Два боковых закрывают угол целиком.
+/// `front` — блок перед гранью в координатах с паддингом, `corner` — угол из
+/// [`Direction::corners`]. Соседи по x/z берутся из паддинга, поэтому на стыке
+/// чанков AO совпадает.
+fn vertex_ao(chunk: &Chunk, front: [i32; 3], side: Direction, corner: [i8; 3]) -> u8 {
+    let (u, v) = side.texture_axes();
+    let mut side_u = front;
+    side_u[u] += corner[u] as i32 * 2 - 1;
+    let mut side_v = front;
+    side_v[v] += corner[v] as i32 * 2 - 1;
+    let mut diagonal = side_u;
+    diagonal[v] = side_v[v];
+    let (a, b) = (occludes(chunk, side_u), occludes(chunk, side_v));
+    if a && b {
+        0
+    } else {
+        3 - a as u8 - b as u8 - occludes(chunk, diagonal) as u8
+    }
+}
+
+/// Сглаженный свет вершины: среднее по клетке перед гранью и её соседям
+/// у угла, что пропускают свет. Диагональ за двумя закрытыми сторонами не видна.
+fn vertex_light(chunk: &Chunk, front: [i32; 3], side: Direction, corner: [i8; 3]) -> u8 {
+    let (u, v) = side.texture_axes();
+    let mut side_u = front;
+    side_u[u] += corner[u] as i32 * 2 - 1;
+    let mut side_v = front;
+    side_v[v] += corner[v] as i32 * 2 - 1;
+    let mut diagonal = side_u;
+    diagonal[v] = side_v[v];
+    let mut samples = [light_at(chunk, front), 0, 0, 0];
+    let mut count = 1;
+    let (open_u, open_v) = (passes_light(chunk, side_u), passes_light(chunk, side_v));
+    let open_diagonal = (open_u || open_v) && passes_light(chunk, diagonal);
+    for (open, pos) in [
+        (open_u, side_u),
+        (open_v, side_v),
+        (open_diagonal, diagonal),
+    ] {
+        if open {
+            samples[count] = light_at(chunk, pos);
+            count += 1;
+        }
+    }
+    let average = |channel: LightChannel| {
+        let sum: usize = samples[..count]
+            .iter()
+            .map(|&l| channel.get(l) as usize)
+            .sum();
+        ((sum + count / 2) / count) as u8
+    };
+    (average(LightChannel::Sky) << 4) | average(LightChannel::Block)
+}
+
+/// Свет клетки с паддингом; выше мира — открытое небо.
+fn light_at(chunk: &Chunk, pos: [i32; 3]) -> u8 {
+    if pos[1] >= CHUNK_Y_SIZE as i32 {
+        return FULL_SKY;
+    }
+    if pos.iter().any(|&c| c < 0) {
+        return 0;
+    }
+    chunk
+        .get_light(pos[1] as usize, pos[0] as usize, pos[2] as usize)
+        .unwrap_or(0)
+}
+
+fn passes_light(chunk: &Chunk, pos: [i32; 3]) -> bool {
+    if pos[1] >= CHUNK_Y_SIZE as i32 {
+        return true;
+    }
+    if pos.iter().any(|&c| c < 0) {
+        return false;
+    }
+    chunk
+        .get_block(pos[1] as usize, pos[0] as usize, pos[2] as usize)
+        .is_some_and(|block| block.light_opacity() < MAX_LIGHT)
+}
+
+/// Заслоняет ли блок свет: всё, кроме воздуха и воды.
+fn occludes(chunk: &Chunk, pos: [i32; 3]) -> bool {
+    if pos.iter().any(|&c| c < 0) {
+        return false;
+    }
+    chunk
+        .get_block(pos[1] as usize, pos[0] as usize, pos[2] as usize)
+        .is_some_and(|block| block != MaterialType::AIR && block != MaterialType::WATER)
+}
+
+/// Жадное покрытие маски прямоугольниками с одинаковым ключом: сначала
+/// растём вдоль z, затем добавляем целые ряды по x.
+fn merge_faces(mask: &FaceMask, along_x: bool, along_z: bool) -> Vec<FaceRect> {
+    let mut used = [[false; CHUNK_AREA]; CHUNK_AREA];
+    let mut rects = Vec::new();
+    let free = |used: &[[bool; CHUNK_AREA]; CHUNK_AREA], x: usize, z: usize, key: FaceKey| {
+        !used[x][z] && mask[x][z] == Some(key)
+    };
+    for x in 0..CHUNK_AREA {
+        for z in 0..CHUNK_AREA {
+            let Some(key) = mask[x][z] else {
+                continue;
+            };
+            if used[x][z] {
+                continue;
+            }
+            let (along_x, along_z) = (along_x && key.mergeable(), along_z && key.mergeable());
+            let mut d = 1;
+            while along_z && z + d < CHUNK_AREA && free(&used, x, z + d, key) {
+                d += 1;
+            }
+            let mut w = 1;
+            while along_x && x + w < CHUNK_AREA && (z..z + d).all(|zz| free(&used, x + w, zz, key))
+            {
+                w += 1;
+            }
+            for row in &mut used[x..x + w] {
+                row[z..z + d].fill(true);
+            }
+            rects.push(FaceRect { x, z, w, d, key });
+        }
+    }
+    rects
+}
+
+/// Мировой угол блока `(x, y, z)` слоя (x, z без паддинга).
+fn world_position(chunk: &Chunk, x: usize, y: usize, z: usize) -> [i32; 3] {
+    [
+        x as i32 + chunk.offset[0] * CHUNK_AREA as i32,
+        y as i32 + WORLD_MIN_Y,
+        z as i32 + chunk.offset[2] * CHUNK_AREA as i32,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::terrain_gen::{
+        chunk::CHUNK_AREA_WITH_PADDING, noise::NoiseGenerator, preset::WorldGenPreset,
+    };
+
+    /// Прежний мешер: по грани на каждую видимую сторону блока.
+    fn naive_layer(chunk: &Chunk, y: usize) -> LayerMesh {
+        let mut layer = LayerMesh::default();
+        for side in Direction::ALL {
+            let mask = face_mask(chunk, y, side, &MeshSettings::default(), MeshPass::Opaque);
+            for (x, row) in mask.iter().enumerate() {
+                for (z, cell) in row.iter().enumerate() {
+                    if let Some(key) = *cell {
+                        let quad = Quad::new(key.material, side, world_position(chunk, x, y, z));
+                        layer.push_quad(&quad);
+                    }
+                }
+            }
+        }
+        layer
+    }
+
+    /// Единичные грани, которые покрывает меш: угол ячейки, нормаль и тайл.
+    /// Заодно проверяет, что грани не перекрываются и тайл повторяется по блокам.
+    fn covered_faces(layer: &LayerMesh) -> HashSet<([i32; 3], [i32; 3], [u32; 2])> {
+        let mut faces = HashSet::new();
+        for quad in layer.verts.chunks(4) {
+            let min = |axis: usize| quad.iter().map(|v| v.pos[axis] as i32).min().unwrap();
+            let max = |axis: usize| quad.iter().map(|v| v.pos[axis] as i32).max().unwrap();
+            let lo = [min(0), min(1), min(2)];
+            let hi = [max(0), max(1), max(2)];
+            let flat = (0..3).find(|&axis| lo[axis] == hi[axis]).unwrap();
+            // Нормаль по обходу вершин (против часовой стрелки снаружи).
+            let a = quad[0].pos;
+            let (b, c) = (quad[1].pos, quad[2].pos);
+            let e1 = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let e2 = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            let n = [
+                e1[1] * e2[2] - e1[2] * e2[1],
+                e1[2] * e2[0] - e1[0] * e2[2],
+                e1[0] * e2[1] - e1[1] * e2[0],
+            ];
+            let normal = n.map(|c| (c as i32).signum());
+            let extent: Vec<i32> = (0..3).map(|axis| (hi[axis] - lo[axis]).max(1)).collect();
+            let max_uv = quad
+                .iter()
+                .map(|v| v.texture_coordinates[0] * v.texture_coordinates[1])
+                .fold(0.0, f32::max);
+            assert_eq!(max_uv as i32, extent.iter().product::<i32>());
+            let tile = quad[0].tile.map(|t| (t * 16.0) as u32);
+            for dx in 0..extent[0] {
+                for dy in 0..extent[1] {
+                    for dz in 0..extent[2] {
+                        let mut cell = [lo[0] + dx, lo[1] + dy, lo[2] + dz];
+                        if normal[flat] > 0 {
+                            cell[flat] -= 1;
+                        }
+                        assert!(faces.insert((cell, normal, tile)), "overlap at {cell:?}");
+                    }
+                }
+            }
+        }
+        faces
+    }
+
+    #[test]
+    fn greedy_covers_exactly_the_naive_faces() {
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(5);
+        let mut chunk = Chunk::new([1, 0, -2]);
+        chunk.update_blocks([1, 0, -2], &noise, &preset);
+        // Несколько вырезов, чтобы у слоя были рваные края.
+        for (x, z) in [(3, 3), (4, 3), (10, 12), (1, 16)] {
+            for y in 60..70 {
+                *chunk.get_block_mut(y, x, z).unwrap() = MaterialType::AIR;
+            }
+        }
+        let no_ao = MeshSettings {
+            ambient_occlusion: false,
+            ..MeshSettings::default()
+        };
+        let (mut naive_quads, mut greedy_quads) = (0, 0);
+        for y in 0..CHUNK_Y_SIZE {
+            let naive = covered_faces(&naive_layer(&chunk, y));
+            let greedy = greedy_layer(&chunk, y, &no_ao, MeshPass::Opaque);
+            naive_quads += naive.len();
+            greedy_quads += greedy.verts.len() / 4;
+            assert_eq!(covered_faces(&greedy), naive, "layer {y}");
+            // С AO склеивается меньше граней, но покрытие то же.
+            let with_ao = greedy_layer(&chunk, y, &MeshSettings::default(), MeshPass::Opaque);
+            assert_eq!(covered_faces(&with_ao), naive, "layer {y} with AO");
+        }
+        assert!(
+            greedy_quads * 2 < naive_quads,
+            "{greedy_quads} vs {naive_quads}"
+        );
+    }
+
+    #[test]
+    fn flat_layer_is_one_quad_per_side() {
+        let mut chunk = Chunk::new([0, 0, 0]);
+        for x in 0..CHUNK_AREA_WITH_PADDING {
+            for z in 0..CHUNK_AREA_WITH_PADDING {
+                *chunk.get_block_mut(70, x, z).unwrap() = MaterialType::GRASS;
+            }
+        }
+        let layer = greedy_layer(&chunk, 70, &MeshSettings::default(), MeshPass::Opaque);
+        // Верх и низ целиком; по бокам соседи в паддинге закрывают грани.
+        assert_eq!(layer.verts.len(), 2 * 4);
+        assert_eq!(layer.indices.len(), 2 * 6);
+        let top = &layer.verts[..4];
+        assert!(top.iter().any(|v| v.texture_coordinates == [16.0, 16.0]));
+    }
+
+    #[test]
+    fn corner_block_darkens_one_vertex_and_flips_the_diagonal() {
+        let mut chunk = Chunk::new([0, 0, 0]);
+        let y = 100;
+        *chunk.get_block_mut(y, 5, 5).unwrap() = MaterialType::STONE;
+        // Блок по диагонали над гранью заслоняет только угол (1, 1, 1).
+        *chunk.get_block_mut(y + 1, 6, 6).unwrap() = MaterialType::STONE;
+        let settings = MeshSettings::default();
+        let layer = greedy_layer(&chunk, y, &settings, MeshPass::Opaque);
+        let top_y = (y as i32 + WORLD_MIN_Y + 1) as f32;
+        let top = layer
+            .verts
+            .chunks(4)
+            .position(|quad| quad.iter().all(|v| v.pos[1] == top_y))
+            .unwrap();
+        let quad = &layer.verts[top * 4..top * 4 + 4];
+        assert_eq!(quad[2].pos, [5.0, top_y, 5.0]);
+        assert!(quad[2].ao < 1.0);
+        assert!([0, 1, 3].iter().all(|&i| quad[i].ao == 1.0));
+        assert!(quad.iter().all(|v| v.face_shade == 1.0));
+        let base = top as u32 * 4;
+        assert_eq!(layer.indices[top * 6], base + 1);
+
+        let flat = MeshSettings {
+            ambient_occlusion: false,
+            face_shading: false,
+            ..settings
+        };
+        let layer = greedy_layer(&chunk, y, &flat, MeshPass::Opaque);
+        assert!(
+            layer
+                .verts
+                .iter()
+                .all(|v| v.ao == 1.0 && v.face_shade == 1.0)
+        );
+    }
+
+    #[test]
+    fn water_goes_to_its_own_pass_with_a_lowered_surface() {
+        let mut chunk = Chunk::new([0, 0, 0]);
+        let y = 100;
+        *chunk.get_block_mut(y, 5, 5).unwrap() = MaterialType::STONE;
+        for x in [5, 6] {
+            *chunk.get_block_mut(y + 1, x, 5).unwrap() = MaterialType::WATER;
+        }
+        let settings = MeshSettings::default();
+        // Камень под водой виден сверху.
+        let stone = greedy_layer(&chunk, y, &settings, MeshPass::Opaque);
+        assert_eq!(stone.verts.len(), 6 * 4);
+        assert!(greedy_layer(&chunk, y + 1, &settings, MeshPass::Opaque).is_empty());
+
+        let water = greedy_layer(&chunk, y + 1, &settings, MeshPass::Fluid);
+        // Между двумя блоками воды граней нет, верх склеен в одну грань,
+        // снизу грань только над воздухом.
+        let quads: Vec<_> = water.verts.chunks(4).collect();
+        assert_eq!(quads.len(), 1 + 4 + 1);
+        let surface_y = (y as i32 + WORLD_MIN_Y + 2) as f32 - FLUID_SURFACE_DROP;
+        let top = quads
+            .iter()
+            .find(|quad| quad.iter().all(|v| v.pos[1] == surface_y))
+            .unwrap();
+        assert!(top.iter().any(|v| v.pos[0] == 6.0));
+        // Верх боковых граней опущен вместе с поверхностью.
+        assert!(water.verts.iter().all(|v| v.pos[1] <= surface_y));
+    }
+
+    #[test]
+    fn faces_behind_leaves_are_not_culled() {
+        let mut chunk = Chunk::new([0, 0, 0]);
+        let y = 100;
+        *chunk.get_block_mut(y, 5, 5).unwrap() = MaterialType::STONE;
+        *chunk.get_block_mut(y, 6, 5).unwrap() = MaterialType::LEAVES;
+        *chunk.get_block_mut(y, 4, 5).unwrap() = MaterialType::WATER;
+        let settings = MeshSettings::default();
+        let layer = greedy_layer(&chunk, y, &settings, MeshPass::Opaque);
+        // Камень (x = 4 без паддинга) виден со всех шести сторон, в том числе
+        // сквозь листву.
+        let stone = covered_faces(&layer)
+            .into_iter()
+            .filter(|(cell, _, _)| cell[0] == 4)
+            .count();
+        assert_eq!(stone, 6);
+        // У листвы закрыта только грань к камню.
+        assert_eq!(layer.verts.len(), (6 + 5) * 4);
+        // У воды закрыта только грань к камню.
+        let water = greedy_layer(&chunk, y, &settings, MeshPass::Fluid);
+        assert_eq!(water.verts.len(), 5 * 4);
+    }
+}
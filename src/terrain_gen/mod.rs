@@ -1,5 +1,19 @@
 pub mod biomes;
 pub mod block;
+pub mod caves;
 pub mod chunk;
+pub mod features;
+pub mod flat;
+pub mod format;
 pub mod generator;
+pub mod light;
+pub mod map;
+pub mod mesher;
 pub mod noise;
+pub mod ores;
+pub mod preset;
+pub mod region;
+pub mod saver;
+pub mod source;
+pub mod storage;
+pub mod water;
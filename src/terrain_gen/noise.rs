@@ -1,19 +1,162 @@
-use noise::{NoiseFn, Perlin};
+use noise::{NoiseFn, OpenSimplex, Perlin};
+
+use super::biomes::{BiomeParameters, TerrainShape};
+
+/// Частота доменного искажения относительно базовой частоты биома.
+const WARP_FREQUENCY_SCALE: f64 = 0.5;
 
 #[derive(Clone)]
 pub struct NoiseGenerator {
+    seed: u32,
     perlin: Perlin,
+    // Отдельные генераторы для смещения по x/z, чтобы искажение не коррелировало с рельефом.
+    warp_x: Perlin,
+    warp_z: Perlin,
+    temperature: Perlin,
+    humidity: Perlin,
+    river: Perlin,
+    // 3D-поля для пещер: два для тоннелей и одно для крупных полостей.
+    cave_a: OpenSimplex,
+    cave_b: OpenSimplex,
+    cave_cheese: OpenSimplex,
 }
 
 impl NoiseGenerator {
     pub fn new(seed: u32) -> Self {
-        let perlin = Perlin::new(seed);
-        Self { perlin }
+        Self {
+            seed,
+            perlin: Perlin::new(seed),
+            warp_x: Perlin::new(seed.wrapping_add(1)),
+            warp_z: Perlin::new(seed.wrapping_add(2)),
+            temperature: Perlin::new(seed.wrapping_add(3)),
+            humidity: Perlin::new(seed.wrapping_add(4)),
+            cave_a: OpenSimplex::new(seed.wrapping_add(5)),
+            cave_b: OpenSimplex::new(seed.wrapping_add(6)),
+            cave_cheese: OpenSimplex::new(seed.wrapping_add(7)),
+            river: Perlin::new(seed.wrapping_add(8)),
+        }
+    }
+
+    /// Пара независимых 3D-полей для тоннелей: тоннель там, где оба близки к нулю.
+    pub fn get_spaghetti(&self, x: f32, y: f32, z: f32, frequency: f32) -> (f32, f32) {
+        let f = frequency as f64;
+        let p = [x as f64 * f, y as f64 * f, z as f64 * f];
+        (self.cave_a.get(p) as f32, self.cave_b.get(p) as f32)
+    }
+
+    /// 3D-поле крупных полостей.
+    pub fn get_cheese(&self, x: f32, y: f32, z: f32, frequency: f32) -> f32 {
+        let f = frequency as f64;
+        self.cave_cheese
+            .get([x as f64 * f, y as f64 * f, z as f64 * f]) as f32
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Низкочастотные температура и влажность в точке, оба в `[-1, 1]`.
+    pub fn get_climate(&self, x: f32, z: f32, frequency: f32) -> (f32, f32) {
+        let f = frequency as f64;
+        let p = [x as f64 * f, z as f64 * f];
+        (self.temperature.get(p) as f32, self.humidity.get(p) as f32)
+    }
+
+    /// Шум русел: реки текут вдоль его нулевой линии.
+    pub fn get_river(&self, x: f32, z: f32, frequency: f32) -> f32 {
+        let f = frequency as f64;
+        self.river.get([x as f64 * f, z as f64 * f]) as f32
+    }
+
+    /// Смещение высоты относительно `base_height` в диапазоне `[-amplitude, amplitude]`.
+    pub fn get_height(&self, x: f32, z: f32, biome: &BiomeParameters) -> f32 {
+        let (x, z) = self.warp(x as f64, z as f64, biome);
+        let value = match biome.shape {
+            TerrainShape::Fbm => self.fbm(x, z, biome),
+            TerrainShape::Ridged => self.ridged(x, z, biome),
+        };
+        value as f32 * biome.amplitude
+    }
+
+    /// Доменное искажение: сдвигаем точку выборки низкочастотным шумом.
+    fn warp(&self, x: f64, z: f64, biome: &BiomeParameters) -> (f64, f64) {
+        if biome.warp_strength <= 0.0 {
+            return (x, z);
+        }
+        let freq = biome.frequency as f64 * WARP_FREQUENCY_SCALE;
+        let strength = biome.warp_strength as f64;
+        let dx = self.warp_x.get([x * freq, z * freq]) * strength;
+        let dz = self.warp_z.get([x * freq, z * freq]) * strength;
+        (x + dx, z + dz)
+    }
+
+    /// Fractional Brownian motion, нормированный в `[-1, 1]`.
+    fn fbm(&self, x: f64, z: f64, biome: &BiomeParameters) -> f64 {
+        let mut frequency = biome.frequency as f64;
+        let mut amplitude = 1.0;
+        let mut sum = 0.0;
+        let mut norm = 0.0;
+        for _ in 0..biome.octaves.max(1) {
+            sum += self.perlin.get([x * frequency, z * frequency]) * amplitude;
+            norm += amplitude;
+            amplitude *= biome.persistence as f64;
+            frequency *= biome.lacunarity as f64;
+        }
+        (sum / norm).clamp(-1.0, 1.0)
+    }
+
+    /// Ridged multifractal (по Мусгрейву), нормированный в `[-1, 1]`.
+    /// Каждая октава взвешивается предыдущей, поэтому детали копятся на гребнях.
+    fn ridged(&self, x: f64, z: f64, biome: &BiomeParameters) -> f64 {
+        let mut frequency = biome.frequency as f64;
+        let mut amplitude = 1.0;
+        let mut weight = 1.0;
+        let mut sum = 0.0;
+        let mut norm = 0.0;
+        for _ in 0..biome.octaves.max(1) {
+            let mut signal = 1.0 - self.perlin.get([x * frequency, z * frequency]).abs();
+            signal *= signal;
+            signal *= weight;
+            weight = (signal * 2.0).clamp(0.0, 1.0);
+            sum += signal * amplitude;
+            norm += amplitude;
+            amplitude *= biome.persistence as f64;
+            frequency *= biome.lacunarity as f64;
+        }
+        ((sum / norm) * 2.0 - 1.0).clamp(-1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain_gen::biomes::{MOUNTAIN_PARAMS, PRAIRIE_PARAMS};
+
+    #[test]
+    fn height_stays_within_amplitude() {
+        let noise = NoiseGenerator::new(42);
+        for biome in [PRAIRIE_PARAMS, MOUNTAIN_PARAMS] {
+            for i in 0..2000 {
+                let x = (i * 37 % 1009) as f32 - 500.0;
+                let z = (i * 91 % 1013) as f32 - 500.0;
+                let h = noise.get_height(x, z, &biome);
+                assert!(h.abs() <= biome.amplitude + f32::EPSILON);
+            }
+        }
     }
 
-    pub fn get_height(&self, x: f32, z: f32, frequency: f32, amplitude: f32) -> f32 {
-        self.perlin
-            .get([x as f64 * frequency as f64, z as f64 * frequency as f64]) as f32
-            * amplitude
+    #[test]
+    fn octaves_change_the_result() {
+        let noise = NoiseGenerator::new(7);
+        let single = BiomeParameters {
+            octaves: 1,
+            ..PRAIRIE_PARAMS
+        };
+        let differs = (0..64).any(|i| {
+            let x = i as f32 * 3.3;
+            let z = i as f32 * 1.7;
+            (noise.get_height(x, z, &single) - noise.get_height(x, z, &PRAIRIE_PARAMS)).abs() > 1e-3
+        });
+        assert!(differs);
     }
 }
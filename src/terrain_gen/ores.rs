@@ -0,0 +1,161 @@
+use anyhow::Result;
+use cgmath::Vector3;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::render::atlas::MaterialType;
+
+use super::{chunk::CHUNK_AREA, features::FeatureRng};
+
+/// Файл правил руд по умолчанию.
+pub const ORES_PATH: &str = "assets/worldgen/ores.ron";
+/// Насколько далеко от центра может уйти жила; меньше `CHUNK_AREA`,
+/// чтобы хватало одного кольца соседних ячеек.
+pub const MAX_VEIN_RADIUS: i32 = 4;
+
+/// Правило одной руды.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OreRule {
+    pub material: MaterialType,
+    /// Диапазон высот (включительно), в котором начинаются жилы; может уходить ниже нуля.
+    pub min_y: i32,
+    pub max_y: i32,
+    /// Количество блоков в жиле.
+    pub vein_size: u32,
+    /// Среднее число жил на ячейку 16x16.
+    pub veins_per_chunk: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OreConfig {
+    pub veins: Vec<OreRule>,
+}
+
+impl OreConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let raw = fs::read_to_string(path)?;
+        let config: OreConfig = ron::from_str(&raw)?;
+        Ok(config)
+    }
+
+    /// Загружает правила из файла; при ошибке пишет предупреждение и берёт встроенные.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        Self::load(path).unwrap_or_else(|err| {
+            log::warn!("Failed to load ore rules {}: {err:?}", path.display());
+            Self::default()
+        })
+    }
+}
+
+impl Default for OreConfig {
+    fn default() -> Self {
+        Self {
+            veins: vec![
+                OreRule {
+                    material: MaterialType::COAL,
+                    min_y: 1,
+                    max_y: 48,
+                    vein_size: 10,
+                    veins_per_chunk: 6.0,
+                },
+                OreRule {
+                    material: MaterialType::IRON,
+                    min_y: -40,
+                    max_y: 32,
+                    vein_size: 6,
+                    veins_per_chunk: 4.0,
+                },
+                OreRule {
+                    material: MaterialType::GOLD,
+                    min_y: -60,
+                    max_y: 12,
+                    vein_size: 5,
+                    veins_per_chunk: 1.0,
+                },
+            ],
+        }
+    }
+}
+
+impl OreRule {
+    /// Блоки всех жил правила с центрами в ячейке `cell`, в мировых координатах.
+    /// Зависят только от сида, ячейки и номера правила.
+    pub fn veins_for_cell(
+        &self,
+        world_seed: u32,
+        rule_index: usize,
+        cell: [i32; 2],
+    ) -> Vec<Vector3<i32>> {
+        let mut rng = FeatureRng::for_cell(
+            world_seed ^ (rule_index as u32 + 1).wrapping_mul(0x85EB_CA6B),
+            cell[0],
+            cell[1],
+        );
+        let mut out = Vec::new();
+        if self.max_y < self.min_y {
+            return out;
+        }
+
+        for _ in 0..rng.attempts(self.veins_per_chunk) {
+            let start = Vector3::new(
+                cell[0] * CHUNK_AREA as i32 + rng.range(0, CHUNK_AREA as i32),
+                rng.range(self.min_y, self.max_y + 1),
+                cell[1] * CHUNK_AREA as i32 + rng.range(0, CHUNK_AREA as i32),
+            );
+            // Случайное блуждание от центра, ограниченное радиусом.
+            let mut pos = start;
+            for _ in 0..self.vein_size {
+                out.push(pos);
+                let step = match rng.range(0, 6) {
+                    0 => Vector3::new(1, 0, 0),
+                    1 => Vector3::new(-1, 0, 0),
+                    2 => Vector3::new(0, 1, 0),
+                    3 => Vector3::new(0, -1, 0),
+                    4 => Vector3::new(0, 0, 1),
+                    _ => Vector3::new(0, 0, -1),
+                };
+                let next = pos + step;
+                let d = next - start;
+                if d.x.abs() <= MAX_VEIN_RADIUS
+                    && d.y.abs() <= MAX_VEIN_RADIUS
+                    && d.z.abs() <= MAX_VEIN_RADIUS
+                {
+                    pos = next;
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ore_rules_file_parses() {
+        let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(ORES_PATH);
+        let config = OreConfig::load(&path).expect("ores RON should parse");
+        assert!(!config.veins.is_empty());
+    }
+
+    #[test]
+    fn veins_respect_height_range_and_radius() {
+        let config = OreConfig::default();
+        for (i, rule) in config.veins.iter().enumerate() {
+            for cx in -3..3 {
+                for cz in -3..3 {
+                    let blocks = rule.veins_for_cell(10, i, [cx, cz]);
+                    assert_eq!(blocks, rule.veins_for_cell(10, i, [cx, cz]));
+                    for pos in blocks {
+                        assert!(pos.y >= rule.min_y - MAX_VEIN_RADIUS);
+                        assert!(pos.y <= rule.max_y + MAX_VEIN_RADIUS);
+                        let cell_x = (pos.x + MAX_VEIN_RADIUS).div_euclid(CHUNK_AREA as i32);
+                        assert!((cell_x - cx).abs() <= 1);
+                    }
+                }
+            }
+        }
+    }
+}
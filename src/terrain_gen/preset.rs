@@ -0,0 +1,233 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    core::{
+        atomic_file::write_atomic,
+        config::{DEFAULT_SUPERFLAT_LAYERS, WorldConfig, WorldType},
+    },
+    render::atlas::MaterialType,
+};
+
+use super::{
+    biomes::BiomeDef,
+    caves::CaveParameters,
+    flat::parse_layers,
+    ores::{ORES_PATH, OreConfig, OreRule},
+    water::WaterSettings,
+};
+
+/// Каталог с пресетами генерации (`<name>.ron`).
+pub const PRESETS_DIR: &str = "assets/worldgen";
+/// Пресет, который берётся, если в конфиге ничего не выбрано.
+pub const DEFAULT_PRESET: &str = "default";
+/// Копия пресета внутри `saves/<world>/` — она авторитетна при повторной загрузке.
+pub const WORLD_PRESET_FILE: &str = "worldgen.ron";
+
+/// Шум климата, по которому выбираются биомы.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClimateSettings {
+    /// Частота карт температуры и влажности (меньше — крупнее биомы).
+    pub frequency: f32,
+    /// Ширина переходной зоны между биомами в пространстве климата.
+    pub blend_sigma: f32,
+    /// Биомы с меньшим весом не участвуют в смешивании высоты.
+    pub min_blend_weight: f32,
+}
+
+/// Общие правила поверхности поверх блоков биома.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SurfaceRules {
+    /// Выше этой высоты поверхность всегда засыпана `snow_block`.
+    pub snow_line: usize,
+    pub snow_block: MaterialType,
+    /// Блок поверхности у самой воды (для биомов с `beaches: true`).
+    pub beach_block: MaterialType,
+    /// Сколько слоёв под поверхностью заполняется блоком-наполнителем биома.
+    pub filler_depth: usize,
+}
+
+/// Пресет генерации мира: биомы, шум, уровень моря, правила поверхности,
+/// пещеры, руды и декорации.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorldGenPreset {
+    pub name: String,
+    /// Уровень моря; `None` — взять `terrain.land_level` из конфига при создании мира.
+    #[serde(default)]
+    pub sea_level: Option<usize>,
+    pub climate: ClimateSettings,
+    pub surface: SurfaceRules,
+    pub biomes: Vec<BiomeDef>,
+    #[serde(default)]
+    pub caves: CaveParameters,
+    /// Реки и озёра.
+    #[serde(default)]
+    pub water: WaterSettings,
+    /// Правила руд; `None` — взять `assets/worldgen/ores.ron` при создании мира.
+    #[serde(default)]
+    pub ores: Option<OreConfig>,
+    /// Ставить ли деревья, валуны и руины.
+    #[serde(default = "default_decorations")]
+    pub decorations: bool,
+    /// Тип мира; при создании берётся из `world.world_type` конфига.
+    #[serde(default)]
+    pub world_type: WorldType,
+}
+
+fn default_decorations() -> bool {
+    true
+}
+
+impl WorldGenPreset {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let raw = fs::read_to_string(path)?;
+        let preset: WorldGenPreset = ron::from_str(&raw)?;
+        if preset.biomes.is_empty() {
+            anyhow::bail!("preset {} has no biomes", preset.name);
+        }
+        let sigma = preset.climate.blend_sigma;
+        if sigma <= 0.0 || !sigma.is_finite() {
+            anyhow::bail!(
+                "preset {} needs climate.blend_sigma > 0, got {sigma}",
+                preset.name
+            );
+        }
+        Ok(preset)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        write_atomic(path, serialized.as_bytes())
+            .with_context(|| format!("failed to write preset to {}", path.display()))
+    }
+
+    pub fn preset_path(name: &str) -> PathBuf {
+        PathBuf::from(PRESETS_DIR).join(format!("{name}.ron"))
+    }
+
+    /// Пресет мира из `save_dir`. Если мир уже сохранял свой пресет — используем
+    /// его, иначе берём `world.preset` из `assets/worldgen`, дополняем значениями
+    /// из конфига и сохраняем копию рядом с миром.
+    pub fn for_world(save_dir: &Path, world: &WorldConfig, fallback_sea_level: usize) -> Self {
+        let world_copy = save_dir.join(WORLD_PRESET_FILE);
+        let preset = Self::peek_world(save_dir, world, fallback_sea_level);
+        if !world_copy.exists()
+            && let Err(err) = preset.save(&world_copy)
+        {
+            log::warn!("Failed to store world preset: {err:?}");
+        }
+        preset
+    }
+
+    /// То же, что [`WorldGenPreset::for_world`], но без записи копии в мир.
+    pub fn peek_world(save_dir: &Path, world: &WorldConfig, fallback_sea_level: usize) -> Self {
+        let world_copy = save_dir.join(WORLD_PRESET_FILE);
+        if world_copy.exists() {
+            match Self::load(&world_copy) {
+                Ok(preset) => return preset,
+                Err(err) => log::warn!(
+                    "Failed to load world preset {}: {err:?}",
+                    world_copy.display()
+                ),
+            }
+        }
+
+        let preset_name = &world.preset;
+        let mut preset = Self::load(Self::preset_path(preset_name)).unwrap_or_else(|err| {
+            log::warn!("Failed to load worldgen preset '{preset_name}': {err:?}");
+            Self::default()
+        });
+        preset.world_type = world.world_type.clone();
+        preset.resolve(fallback_sea_level);
+        preset
+    }
+
+    /// Заполняет необязательные поля, чтобы копия в сохранении не зависела
+    /// от конфига и общих файлов.
+    fn resolve(&mut self, fallback_sea_level: usize) {
+        self.sea_level.get_or_insert(fallback_sea_level);
+        if self.ores.is_none() {
+            self.ores = Some(OreConfig::load_or_default(ORES_PATH));
+        }
+        if let WorldType::Superflat { layers } = &mut self.world_type
+            && let Err(err) = parse_layers(layers)
+        {
+            log::warn!("Invalid superflat layers '{layers}': {err:?}");
+            *layers = DEFAULT_SUPERFLAT_LAYERS.to_string();
+        }
+    }
+
+    pub fn sea_level(&self) -> usize {
+        self.sea_level.unwrap_or(DEFAULT_SEA_LEVEL)
+    }
+
+    pub fn ores(&self) -> &[OreRule] {
+        self.ores.as_ref().map_or(&[], |o| o.veins.as_slice())
+    }
+}
+
+const DEFAULT_SEA_LEVEL: usize = 9;
+
+/// Встроенная копия `assets/worldgen/default.ron`: запасной пресет не расходится с файлом.
+const BUNDLED_DEFAULT: &str = include_str!("../../assets/worldgen/default.ron");
+
+impl Default for WorldGenPreset {
+    fn default() -> Self {
+        let mut preset: Self =
+            ron::from_str(BUNDLED_DEFAULT).expect("встроенный default.ron должен разбираться");
+        preset.sea_level.get_or_insert(DEFAULT_SEA_LEVEL);
+        preset.ores.get_or_insert_with(OreConfig::default);
+        preset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_dir::TestDir;
+
+    #[test]
+    fn bundled_presets_parse() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(PRESETS_DIR);
+        for name in [DEFAULT_PRESET, "islands"] {
+            let preset = WorldGenPreset::load(root.join(format!("{name}.ron")))
+                .unwrap_or_else(|e| panic!("{name}.ron should parse: {e:?}"));
+            assert_eq!(preset.name, name);
+        }
+    }
+
+    #[test]
+    fn preset_without_blend_width_is_rejected() {
+        let dir = TestDir::new("preset_sigma");
+        let mut preset = WorldGenPreset::default();
+        preset.climate.blend_sigma = 0.0;
+        let path = dir.join("flat_blend.ron");
+        preset.save(&path).unwrap();
+        assert!(WorldGenPreset::load(&path).is_err());
+    }
+
+    #[test]
+    fn preset_round_trips_through_ron() {
+        let preset = WorldGenPreset::default();
+        let asset = WorldGenPreset::load(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join(PRESETS_DIR)
+                .join("default.ron"),
+        )
+        .expect("load default preset");
+        let resolved = WorldGenPreset {
+            sea_level: Some(DEFAULT_SEA_LEVEL),
+            ores: Some(OreConfig::default()),
+            ..asset
+        };
+        let raw = ron::to_string(&preset).expect("serialize preset");
+        assert_eq!(raw, ron::to_string(&resolved).expect("serialize asset"));
+        let back: WorldGenPreset = ron::from_str(&raw).expect("parse preset");
+        assert_eq!(ron::to_string(&back).expect("serialize again"), raw);
+    }
+}
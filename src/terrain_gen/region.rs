@@ -0,0 +1,724 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+
+use super::chunk::Chunk;
+use crate::core::{atomic_file::write_atomic, level::unix_now};
+
+/// Подкаталог мира с регион-файлами.
+pub const REGION_DIR: &str = "region";
+/// Подкаталог мира, куда откладываются нечитаемые чанки и регионы.
+pub const QUARANTINE_DIR: &str = "quarantine";
+/// Сторона региона в чанках.
+pub const REGION_SIZE: i32 = 32;
+/// Версия 2: две копии таблицы с поколением и CRC; версия 1 читается
+/// и при первой записи пересобирается в новую.
+pub const REGION_VERSION: u32 = 2;
+
+const REGION_CHUNKS: usize = (REGION_SIZE * REGION_SIZE) as usize;
+const REGION_MAGIC: &[u8; 4] = b"WCRG";
+/// Запись таблицы: смещение, длина, CRC32 данных, способ сжатия и 3 байта запаса.
+const ENTRY_SIZE: usize = 16;
+/// Метка и версия в начале файла; пишутся один раз при его создании.
+const PREFIX_SIZE: usize = 8;
+/// Копия таблицы: поколение, CRC32 поколения и записей, сами записи.
+const TABLE_SIZE: usize = 12 + REGION_CHUNKS * ENTRY_SIZE;
+/// Новая таблица пишется на место старшей из двух копий, поэтому сбой
+/// посреди записи оставляет целой предыдущую.
+const HEADER_SIZE: usize = PREFIX_SIZE + 2 * TABLE_SIZE;
+/// Регион сжимается, когда мёртвых данных больше живых и больше этого объёма.
+const COMPACT_MIN_DEAD: u64 = 1024 * 1024;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Lz4,
+}
+
+impl Compression {
+    fn tag(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Lz4 => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Lz4),
+            other => bail!("unknown chunk compression {other}"),
+        }
+    }
+}
+
+/// Новые данные для записи таблицы региона; `None` удаляет чанк.
+type SlotUpdate<'a> = (usize, Option<&'a [u8]>);
+
+#[derive(Copy, Clone, Default)]
+struct Entry {
+    offset: u32,
+    len: u32,
+    checksum: u32,
+    compression: u8,
+}
+
+impl Entry {
+    fn read(raw: &[u8]) -> Self {
+        let word = |i: usize| u32::from_le_bytes(raw[i..i + 4].try_into().unwrap());
+        Self {
+            offset: word(0),
+            len: word(4),
+            checksum: word(8),
+            compression: raw[12],
+        }
+    }
+
+    fn write(&self, out: &mut [u8]) {
+        out[0..4].copy_from_slice(&self.offset.to_le_bytes());
+        out[4..8].copy_from_slice(&self.len.to_le_bytes());
+        out[8..12].copy_from_slice(&self.checksum.to_le_bytes());
+        out[12] = self.compression;
+        out[13..16].fill(0);
+    }
+}
+
+/// Таблица смещений региона.
+struct Table {
+    entries: Vec<u8>,
+    generation: u64,
+    /// Копия в файле, из которой таблица прочитана; `None` у версии 1.
+    copy: Option<usize>,
+}
+
+impl Table {
+    fn new() -> Self {
+        Self {
+            entries: vec![0; REGION_CHUNKS * ENTRY_SIZE],
+            generation: 0,
+            copy: None,
+        }
+    }
+
+    fn entry(&self, slot: usize) -> Entry {
+        Entry::read(&self.entries[slot * ENTRY_SIZE..][..ENTRY_SIZE])
+    }
+
+    fn set(&mut self, slot: usize, entry: &Entry) {
+        entry.write(&mut self.entries[slot * ENTRY_SIZE..][..ENTRY_SIZE]);
+    }
+
+    fn entries(&self) -> impl Iterator<Item = Entry> + '_ {
+        (0..REGION_CHUNKS).map(|slot| self.entry(slot))
+    }
+
+    fn checksum(generation: u64, entries: &[u8]) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&generation.to_le_bytes());
+        hasher.update(entries);
+        hasher.finalize()
+    }
+
+    /// Копия таблицы в формате файла.
+    fn encode(&self, generation: u64) -> Vec<u8> {
+        let mut out = Vec::with_capacity(TABLE_SIZE);
+        out.extend_from_slice(&generation.to_le_bytes());
+        out.extend_from_slice(&Self::checksum(generation, &self.entries).to_le_bytes());
+        out.extend_from_slice(&self.entries);
+        out
+    }
+
+    /// Разбирает копию таблицы; `None`, если она не записывалась или порвана.
+    fn decode(raw: &[u8], copy: usize) -> Option<Self> {
+        if raw.len() < TABLE_SIZE {
+            return None;
+        }
+        let generation = u64::from_le_bytes(raw[0..8].try_into().unwrap());
+        let checksum = u32::from_le_bytes(raw[8..12].try_into().unwrap());
+        let entries = &raw[12..TABLE_SIZE];
+        (generation > 0 && Self::checksum(generation, entries) == checksum).then(|| Self {
+            entries: entries.to_vec(),
+            generation,
+            copy: Some(copy),
+        })
+    }
+}
+
+/// Хранилище чанков мира в регион-файлах `r.<x>.<z>.region` по 32x32 колонки.
+/// В начале файла — версия и две копии таблицы смещений, за ними данные
+/// чанков; каждый чанк сжат отдельно и защищён контрольной суммой. Новые данные
+/// дописываются в конец, старые остаются мёртвым местом до сжатия региона.
+/// Не потокобезопасно: доступ к одному миру идёт через общий мьютекс.
+pub struct RegionStore {
+    dir: PathBuf,
+    quarantine_dir: PathBuf,
+    compression: Compression,
+}
+
+impl RegionStore {
+    pub fn new(save_dir: &Path) -> Self {
+        Self {
+            dir: save_dir.join(REGION_DIR),
+            quarantine_dir: save_dir.join(QUARANTINE_DIR),
+            compression: Compression::Lz4,
+        }
+    }
+
+    fn region_path(&self, region: [i32; 2]) -> PathBuf {
+        self.dir
+            .join(format!("r.{}.{}.region", region[0], region[1]))
+    }
+
+    /// Регион и номер записи в его таблице для колонки `[x, z]`.
+    fn locate(column: [i32; 2]) -> ([i32; 2], usize) {
+        let region = [
+            column[0].div_euclid(REGION_SIZE),
+            column[1].div_euclid(REGION_SIZE),
+        ];
+        let slot =
+            column[1].rem_euclid(REGION_SIZE) * REGION_SIZE + column[0].rem_euclid(REGION_SIZE);
+        (region, slot as usize)
+    }
+
+    /// Данные чанка или `None`, если он ещё не сохранялся.
+    pub fn read_chunk(&self, column: [i32; 2]) -> Result<Option<Vec<u8>>> {
+        let (region, slot) = Self::locate(column);
+        let path = self.region_path(region);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let mut file = File::open(&path)?;
+        let table = read_table(&mut file)
+            .with_context(|| format!("bad region header in {}", path.display()))?;
+        let entry = table.entry(slot);
+        if entry.len == 0 {
+            return Ok(None);
+        }
+
+        let payload = read_payload(&mut file, &entry)?;
+        if crc32fast::hash(&payload) != entry.checksum {
+            bail!("checksum mismatch for chunk {column:?}");
+        }
+        let data = match Compression::from_tag(entry.compression)? {
+            Compression::None => payload,
+            Compression::Lz4 => lz4_flex::decompress_size_prepended(&payload)
+                .with_context(|| format!("corrupt compressed chunk {column:?}"))?,
+        };
+        Ok(Some(data))
+    }
+
+    /// Координаты всех регион-файлов мира.
+    pub fn regions(&self) -> Vec<[i32; 2]> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut regions: Vec<[i32; 2]> = entries
+            .flatten()
+            .filter_map(|entry| parse_region_name(entry.file_name().to_str()?))
+            .collect();
+        regions.sort();
+        regions
+    }
+
+    /// Колонки, сохранённые в регионе; ошибка, если не читается его заголовок.
+    pub fn region_columns(&self, region: [i32; 2]) -> Result<Vec<[i32; 2]>> {
+        let path = self.region_path(region);
+        let mut file = File::open(&path)?;
+        let table = read_table(&mut file)
+            .with_context(|| format!("bad region header in {}", path.display()))?;
+        Ok((0..REGION_CHUNKS)
+            .filter(|&slot| table.entry(slot).len > 0)
+            .map(|slot| slot_column(region, slot))
+            .collect())
+    }
+
+    pub fn write_chunk(&self, column: [i32; 2], data: &[u8]) -> Result<()> {
+        self.write_chunks(&[(column, data.to_vec())])
+    }
+
+    /// Записывает пачку чанков: данные дописываются в конец региона, затем
+    /// переписывается только таблица. Новый регион и сжатие, когда мёртвого
+    /// места становится слишком много, идут через [`Self::rewrite_region`].
+    pub fn write_chunks(&self, chunks: &[([i32; 2], Vec<u8>)]) -> Result<()> {
+        let mut by_region: HashMap<[i32; 2], Vec<SlotUpdate>> = HashMap::new();
+        for (column, data) in chunks {
+            let (region, slot) = Self::locate(*column);
+            by_region
+                .entry(region)
+                .or_default()
+                .push((slot, Some(data)));
+        }
+        for (region, updates) in by_region {
+            self.append_to_region(region, &updates).with_context(|| {
+                format!("failed to write {}", self.region_path(region).display())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Дописывает чанки в конец региона и пишет новую таблицу на место другой
+    /// копии. Данные сбрасываются на диск раньше таблицы, а текущая копия
+    /// не трогается, так что сбой посреди записи оставляет старые, целые
+    /// версии чанков.
+    fn append_to_region(&self, region: [i32; 2], updates: &[SlotUpdate]) -> Result<()> {
+        let path = self.region_path(region);
+        if !path.exists() {
+            return self.rewrite_region(region, updates);
+        }
+        let mut file = fs::OpenOptions::new().read(true).write(true).open(&path)?;
+        let mut table = read_table(&mut file)?;
+        let Some(copy) = table.copy else {
+            // Регион версии 1 один раз пересобирается в новый формат.
+            drop(file);
+            return self.rewrite_region(region, updates);
+        };
+        // Пишем за концом и файла, и всех записей таблицы, чтобы не наложиться
+        // на данные обрезанного чанка: их отсеет контрольная сумма.
+        let mut end = table
+            .entries()
+            .map(|entry| entry.offset as u64 + entry.len as u64)
+            .fold(file.metadata()?.len().max(HEADER_SIZE as u64), u64::max);
+        for &(slot, data) in updates {
+            let entry = match data {
+                Some(data) => {
+                    let (compression, payload) = self.encode(data);
+                    file.seek(SeekFrom::Start(end))?;
+                    file.write_all(&payload)?;
+                    let entry = Entry {
+                        offset: u32::try_from(end).context("region file too large")?,
+                        len: payload.len() as u32,
+                        checksum: crc32fast::hash(&payload),
+                        compression: compression.tag(),
+                    };
+                    end += payload.len() as u64;
+                    entry
+                }
+                None => Entry::default(),
+            };
+            table.set(slot, &entry);
+        }
+        file.sync_data()?;
+        file.seek(SeekFrom::Start(table_offset(1 - copy)))?;
+        file.write_all(&table.encode(table.generation + 1))?;
+        file.sync_data()?;
+        drop(file);
+
+        let live = HEADER_SIZE as u64 + table.entries().map(|entry| entry.len as u64).sum::<u64>();
+        let dead = end.saturating_sub(live);
+        if dead > live && dead > COMPACT_MIN_DEAD {
+            self.rewrite_region(region, &[])?;
+        }
+        Ok(())
+    }
+
+    /// Откладывает сохранённые данные колонки в `quarantine/` как есть (сжатыми)
+    /// и убирает их из региона, чтобы чанк сгенерировался заново. Если не читается
+    /// сам заголовок региона, в карантин уходит весь файл. Возвращает путь копии.
+    pub fn quarantine_chunk(&self, column: [i32; 2]) -> Result<PathBuf> {
+        let (region, slot) = Self::locate(column);
+        let path = self.region_path(region);
+        let mut file = File::open(&path)?;
+        let Ok(table) = read_table(&mut file) else {
+            drop(file);
+            fs::create_dir_all(&self.quarantine_dir)?;
+            let target = self.quarantine_dir.join(format!(
+                "r.{}.{}.{}.region",
+                region[0],
+                region[1],
+                unix_now()
+            ));
+            fs::rename(&path, &target)?;
+            return Ok(target);
+        };
+        let entry = table.entry(slot);
+        let payload = read_remains(&mut file, &entry);
+        drop(file);
+        let target = self.quarantine_bytes(column, &payload)?;
+        self.rewrite_region(region, &[(slot, None)])?;
+        Ok(target)
+    }
+
+    /// Пересобирает регион целиком во временный файл и подменяет старый,
+    /// выбрасывая мёртвые данные; `None` в обновлении удаляет чанк из таблицы.
+    /// Чанк, данные которого не читаются (смещение или длина за концом файла),
+    /// считается повреждённым: он выпадает из региона, а то, что от него
+    /// осталось, уходит в карантин. Иначе один битый чанк не давал бы
+    /// сохранять весь регион.
+    fn rewrite_region(&self, region: [i32; 2], updates: &[SlotUpdate]) -> Result<()> {
+        let path = self.region_path(region);
+        let mut payloads: Vec<Option<(Entry, Vec<u8>)>> = vec![None; REGION_CHUNKS];
+        if path.exists() {
+            let mut file = File::open(&path)?;
+            let table = read_table(&mut file)?;
+            for (slot, stored) in payloads.iter_mut().enumerate() {
+                let entry = table.entry(slot);
+                // Заменяемые чанки читать незачем.
+                if entry.len == 0 || updates.iter().any(|&(s, _)| s == slot) {
+                    continue;
+                }
+                // Обрезанный или испорченный чанк уходит в карантин.
+                let column = slot_column(region, slot);
+                match read_payload(&mut file, &entry) {
+                    Ok(payload) if crc32fast::hash(&payload) == entry.checksum => {
+                        *stored = Some((entry, payload));
+                    }
+                    Ok(payload) => {
+                        log::warn!("Dropping corrupted chunk {column:?}");
+                        self.quarantine_bytes(column, &payload)?;
+                    }
+                    Err(err) => {
+                        log::warn!("Dropping unreadable chunk {column:?}: {err:?}");
+                        let remains = read_remains(&mut file, &entry);
+                        self.quarantine_bytes(column, &remains)?;
+                    }
+                }
+            }
+        }
+        // Повторная запись того же чанка в пачке: побеждает последняя.
+        for &(slot, data) in updates {
+            let Some(data) = data else {
+                payloads[slot] = None;
+                continue;
+            };
+            let (compression, payload) = self.encode(data);
+            let entry = Entry {
+                offset: 0,
+                len: payload.len() as u32,
+                checksum: crc32fast::hash(&payload),
+                compression: compression.tag(),
+            };
+            payloads[slot] = Some((entry, payload));
+        }
+
+        let mut table = Table::new();
+        let mut data = Vec::new();
+        for (slot, stored) in payloads.iter_mut().enumerate() {
+            if let Some((entry, payload)) = stored {
+                entry.offset =
+                    u32::try_from(HEADER_SIZE + data.len()).context("region file too large")?;
+                table.set(slot, entry);
+                data.extend_from_slice(payload);
+            }
+        }
+        let mut out = Vec::with_capacity(HEADER_SIZE + data.len());
+        out.extend_from_slice(REGION_MAGIC);
+        out.extend_from_slice(&REGION_VERSION.to_le_bytes());
+        out.extend_from_slice(&table.encode(1));
+        out.resize(HEADER_SIZE, 0);
+        out.extend_from_slice(&data);
+        write_atomic(&path, &out)?;
+        Ok(())
+    }
+
+    /// Кладёт сырые данные колонки в `quarantine/`. Возвращает путь копии.
+    fn quarantine_bytes(&self, column: [i32; 2], data: &[u8]) -> Result<PathBuf> {
+        fs::create_dir_all(&self.quarantine_dir)?;
+        let target =
+            self.quarantine_dir
+                .join(format!("c.{}.{}.{}.bin", column[0], column[1], unix_now()));
+        write_atomic(&target, data)?;
+        Ok(target)
+    }
+
+    fn encode(&self, data: &[u8]) -> (Compression, Vec<u8>) {
+        if self.compression == Compression::Lz4 {
+            let compressed = lz4_flex::compress_prepend_size(data);
+            if compressed.len() < data.len() {
+                return (Compression::Lz4, compressed);
+            }
+        }
+        (Compression::None, data.to_vec())
+    }
+
+    /// Переносит старые файлы `chunk_X_Y_Z.bin` из каталога мира в регионы
+    /// и удаляет их. Возвращает число перенесённых чанков.
+    pub fn migrate_legacy_chunks(&self, save_dir: &Path) -> Result<usize> {
+        let Ok(entries) = fs::read_dir(save_dir) else {
+            return Ok(0);
+        };
+        let mut batch = Vec::new();
+        let mut migrated = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(offset) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(parse_legacy_name)
+            else {
+                continue;
+            };
+            let mut chunk = Chunk::new(offset);
+            if let Err(err) = chunk.load_from(&path, offset) {
+                // Битый файл оставляем на месте: чанк просто сгенерируется заново.
+                log::warn!("Skipping unreadable {}: {err:?}", path.display());
+                continue;
+            }
+            batch.push(([offset[0], offset[2]], chunk.block_bytes()));
+            migrated.push(path);
+        }
+        self.write_chunks(&batch)?;
+        for path in &migrated {
+            fs::remove_file(path)?;
+        }
+        Ok(migrated.len())
+    }
+}
+
+/// `chunk_X_Y_Z.bin` -> `[X, Y, Z]`.
+fn parse_legacy_name(name: &str) -> Option<[i32; 3]> {
+    let coords = name.strip_prefix("chunk_")?.strip_suffix(".bin")?;
+    let mut parts = coords.split('_').map(|p| p.parse::<i32>().ok());
+    let offset = [parts.next()??, parts.next()??, parts.next()??];
+    parts.next().is_none().then_some(offset)
+}
+
+/// `r.X.Z.region` -> `[X, Z]`.
+fn parse_region_name(name: &str) -> Option<[i32; 2]> {
+    let coords = name.strip_prefix("r.")?.strip_suffix(".region")?;
+    let (x, z) = coords.split_once('.')?;
+    Some([x.parse().ok()?, z.parse().ok()?])
+}
+
+/// Колонка `[x, z]` записи `slot` региона.
+fn slot_column(region: [i32; 2], slot: usize) -> [i32; 2] {
+    let slot = slot as i32;
+    [
+        region[0] * REGION_SIZE + slot % REGION_SIZE,
+        region[1] * REGION_SIZE + slot / REGION_SIZE,
+    ]
+}
+
+/// Смещение копии таблицы в файле.
+fn table_offset(copy: usize) -> u64 {
+    (PREFIX_SIZE + copy * TABLE_SIZE) as u64
+}
+
+fn read_payload(file: &mut File, entry: &Entry) -> Result<Vec<u8>> {
+    let mut payload = vec![0; entry.len as usize];
+    file.seek(SeekFrom::Start(entry.offset as u64))?;
+    file.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+/// Сколько есть данных записи до конца файла; для карантина обрезанных чанков.
+fn read_remains(file: &mut File, entry: &Entry) -> Vec<u8> {
+    let mut remains = Vec::new();
+    if file.seek(SeekFrom::Start(entry.offset as u64)).is_ok() {
+        let _ = file.take(entry.len as u64).read_to_end(&mut remains);
+    }
+    remains
+}
+
+/// Действующая таблица региона: из двух копий — целая с большим поколением.
+fn read_table(file: &mut File) -> Result<Table> {
+    let mut prefix = [0; PREFIX_SIZE];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut prefix)?;
+    if &prefix[0..4] != REGION_MAGIC {
+        bail!("not a region file");
+    }
+    let version = u32::from_le_bytes(prefix[4..8].try_into().unwrap());
+    match version {
+        1 => {
+            let mut table = Table::new();
+            file.read_exact(&mut table.entries)?;
+            Ok(table)
+        }
+        REGION_VERSION => {
+            // Обрезанный файл может хранить только первую копию.
+            let mut raw = Vec::with_capacity(2 * TABLE_SIZE);
+            file.take(2 * TABLE_SIZE as u64).read_to_end(&mut raw)?;
+            raw.chunks(TABLE_SIZE)
+                .enumerate()
+                .filter_map(|(copy, raw)| Table::decode(raw, copy))
+                .max_by_key(|table| table.generation)
+                .context("both region tables are damaged")
+        }
+        _ => bail!("unsupported region version {version}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_dir::TestDir;
+
+    #[test]
+    fn chunks_round_trip_and_overwrite() {
+        let dir = TestDir::new("region_round_trip");
+        let store = RegionStore::new(&dir);
+        let a = vec![7u8; 50_000];
+        let b: Vec<u8> = (0..3000u32).map(|i| (i * 31 % 251) as u8).collect();
+        store.write_chunk([0, 0], &a).unwrap();
+        store.write_chunk([-1, 33], &b).unwrap();
+        store.write_chunk([31, 31], &b).unwrap();
+        assert_eq!(store.read_chunk([0, 0]).unwrap().as_deref(), Some(&a[..]));
+        assert_eq!(store.read_chunk([-1, 33]).unwrap().as_deref(), Some(&b[..]));
+        assert_eq!(store.read_chunk([1, 0]).unwrap(), None);
+        assert_eq!(store.regions(), [[-1, 1], [0, 0]]);
+        assert_eq!(store.region_columns([0, 0]).unwrap(), [[0, 0], [31, 31]]);
+        assert_eq!(store.region_columns([-1, 1]).unwrap(), [[-1, 33]]);
+
+        store.write_chunk([0, 0], &b).unwrap();
+        assert_eq!(store.read_chunk([0, 0]).unwrap().as_deref(), Some(&b[..]));
+        assert_eq!(store.read_chunk([31, 31]).unwrap().as_deref(), Some(&b[..]));
+        // Сжатая колонка из одного материала занимает доли исходного объёма.
+        let size = fs::metadata(store.region_path([0, 0])).unwrap().len();
+        assert!(size < (HEADER_SIZE + 2 * b.len() + a.len() / 10) as u64);
+    }
+
+    #[test]
+    fn overwrites_are_appended_until_compaction() {
+        let dir = TestDir::new("region_append");
+        let store = RegionStore::new(&dir);
+        // Несжимаемые данные, чтобы размер файла был предсказуем.
+        let mut seed = 1u32;
+        let data: Vec<u8> = (0..256 * 1024)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed >> 24) as u8
+            })
+            .collect();
+        let path = store.region_path([0, 0]);
+        store.write_chunk([0, 0], &data).unwrap();
+        let first = fs::metadata(&path).unwrap().len();
+        store.write_chunk([0, 0], &data).unwrap();
+        assert_eq!(
+            fs::metadata(&path).unwrap().len(),
+            first + data.len() as u64
+        );
+
+        // Мёртвого места больше живого и больше порога — регион сжимается.
+        for _ in 0..4 {
+            store.write_chunk([0, 0], &data).unwrap();
+        }
+        assert_eq!(fs::metadata(&path).unwrap().len(), first);
+        assert_eq!(store.read_chunk([0, 0]).unwrap(), Some(data));
+    }
+
+    #[test]
+    fn corrupted_chunk_fails_checksum() {
+        let dir = TestDir::new("region_corrupt");
+        let store = RegionStore::new(&dir);
+        store
+            .write_chunk([2, 3], &[1, 2, 3, 4, 5, 6, 7, 8])
+            .unwrap();
+        let path = store.region_path([0, 0]);
+        let mut raw = fs::read(&path).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0xFF;
+        fs::write(&path, raw).unwrap();
+        assert!(store.read_chunk([2, 3]).is_err());
+
+        store.write_chunk([4, 3], &[9; 16]).unwrap();
+        let moved = store.quarantine_chunk([2, 3]).unwrap();
+        assert!(moved.starts_with(dir.join(QUARANTINE_DIR)));
+        assert_eq!(fs::read(&moved).unwrap().len(), 8);
+        assert_eq!(store.read_chunk([2, 3]).unwrap(), None);
+        assert_eq!(store.read_chunk([4, 3]).unwrap(), Some(vec![9; 16]));
+
+        // Испорченный заголовок: в карантин уходит весь регион.
+        fs::write(&path, b"garbage").unwrap();
+        assert!(store.read_chunk([4, 3]).is_err());
+        store.quarantine_chunk([4, 3]).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn torn_table_write_keeps_the_previous_table() {
+        let dir = TestDir::new("region_torn");
+        let store = RegionStore::new(&dir);
+        store.write_chunk([0, 0], &[1; 64]).unwrap();
+        store.write_chunk([0, 0], &[2; 64]).unwrap();
+        // Вторая запись легла во вторую копию; рвём её посреди записей,
+        // через границу страницы, как при сбое питания.
+        let path = store.region_path([0, 0]);
+        let mut raw = fs::read(&path).unwrap();
+        let torn = table_offset(1) as usize + 4000;
+        raw[torn..torn + 200].fill(0xAB);
+        fs::write(&path, raw).unwrap();
+        assert_eq!(store.read_chunk([0, 0]).unwrap(), Some(vec![1; 64]));
+
+        // Следующая запись идёт поверх порванной копии, целая не трогается.
+        store.write_chunk([1, 0], &[3; 64]).unwrap();
+        assert_eq!(store.read_chunk([0, 0]).unwrap(), Some(vec![1; 64]));
+        assert_eq!(store.read_chunk([1, 0]).unwrap(), Some(vec![3; 64]));
+    }
+
+    #[test]
+    fn version_one_region_is_read_and_upgraded() {
+        let dir = TestDir::new("region_v1");
+        let store = RegionStore::new(&dir);
+        let payload = [7u8; 32];
+        let mut raw = Vec::new();
+        raw.extend_from_slice(REGION_MAGIC);
+        raw.extend_from_slice(&1u32.to_le_bytes());
+        let mut table = Table::new();
+        let entry = Entry {
+            offset: (PREFIX_SIZE + REGION_CHUNKS * ENTRY_SIZE) as u32,
+            len: payload.len() as u32,
+            checksum: crc32fast::hash(&payload),
+            compression: Compression::None.tag(),
+        };
+        table.set(RegionStore::locate([3, 1]).1, &entry);
+        raw.extend_from_slice(&table.entries);
+        raw.extend_from_slice(&payload);
+        fs::create_dir_all(dir.join(REGION_DIR)).unwrap();
+        let path = store.region_path([0, 0]);
+        fs::write(&path, raw).unwrap();
+
+        assert_eq!(store.read_chunk([3, 1]).unwrap(), Some(payload.to_vec()));
+        store.write_chunk([4, 1], &[8; 32]).unwrap();
+        let version = u32::from_le_bytes(fs::read(&path).unwrap()[4..8].try_into().unwrap());
+        assert_eq!(version, REGION_VERSION);
+        assert_eq!(store.read_chunk([3, 1]).unwrap(), Some(payload.to_vec()));
+        assert_eq!(store.read_chunk([4, 1]).unwrap(), Some(vec![8; 32]));
+    }
+
+    #[test]
+    fn legacy_files_are_migrated() {
+        let dir = TestDir::new("region_migrate");
+        let chunk = Chunk::new([5, 0, -2]);
+        fs::write(dir.join("chunk_5_0_-2.bin"), chunk.block_bytes()).unwrap();
+        let store = RegionStore::new(&dir);
+        assert_eq!(store.migrate_legacy_chunks(&dir).unwrap(), 1);
+        assert!(!dir.join("chunk_5_0_-2.bin").exists());
+        assert_eq!(
+            store.read_chunk([5, -2]).unwrap(),
+            Some(chunk.block_bytes())
+        );
+        assert_eq!(parse_legacy_name("chunk_1_2.bin"), None);
+    }
+
+    #[test]
+    fn truncated_chunk_does_not_block_the_region() {
+        let dir = TestDir::new("region_truncated");
+        let store = RegionStore::new(&dir);
+        let data: Vec<u8> = (0..4000u32).map(|i| (i * 7 % 253) as u8).collect();
+        store.write_chunk([0, 0], &data).unwrap();
+        store.write_chunk([1, 0], &data).unwrap();
+        // Обрезаем файл посреди данных последнего чанка, [1, 0].
+        let path = store.region_path([0, 0]);
+        let len = fs::metadata(&path).unwrap().len();
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 10)
+            .unwrap();
+        assert!(store.read_chunk([1, 0]).is_err());
+
+        // Соседний чанк пишется, битый выпадает из региона в карантин.
+        store.write_chunk([2, 0], &[5; 32]).unwrap();
+        store.rewrite_region([0, 0], &[]).unwrap();
+        assert_eq!(store.read_chunk([1, 0]).unwrap(), None);
+        assert_eq!(store.read_chunk([0, 0]).unwrap(), Some(data));
+        assert_eq!(store.read_chunk([2, 0]).unwrap(), Some(vec![5; 32]));
+        assert_eq!(fs::read_dir(dir.join(QUARANTINE_DIR)).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use anyhow::{Result, bail};
+use crossbeam_channel::{Receiver, Sender};
+
+use super::region::RegionStore;
+
+enum SaveMsg {
+    Chunk([i32; 2], Vec<u8>),
+    /// Ответить, когда всё поставленное раньше окажется на диске;
+    /// в ответе — сколько чанков записать так и не удалось.
+    Flush(Sender<usize>),
+}
+
+/// Прогресс текущей волны сохранения; обнуляется, когда очередь пустеет
+/// и несохранённых чанков не осталось.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SaveProgress {
+    pub written: usize,
+    pub queued: usize,
+    /// Чанки, запись которых не удалась; они держатся в памяти и пишутся
+    /// снова с каждой следующей пачкой.
+    pub failed: usize,
+}
+
+impl SaveProgress {
+    pub fn is_idle(&self) -> bool {
+        self.written + self.failed >= self.queued
+    }
+}
+
+/// Фоновая запись чанков в регион-файлы. Всё, что накопилось в очереди,
+/// пишется одной пачкой. Пачка, которую записать не удалось, не теряется:
+/// она добавляется к следующей, пока запись не пройдёт.
+pub struct ChunkSaver {
+    tx: Sender<SaveMsg>,
+    progress: Arc<Mutex<SaveProgress>>,
+}
+
+impl ChunkSaver {
+    pub fn new(region: Arc<Mutex<RegionStore>>) -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let progress = Arc::new(Mutex::new(SaveProgress::default()));
+        let worker_progress = progress.clone();
+        thread::spawn(move || run(rx, region, worker_progress));
+        Self { tx, progress }
+    }
+
+    pub fn queue(&self, column: [i32; 2], data: Vec<u8>) {
+        self.progress.lock().unwrap().queued += 1;
+        let _ = self.tx.send(SaveMsg::Chunk(column, data));
+    }
+
+    pub fn progress(&self) -> SaveProgress {
+        *self.progress.lock().unwrap()
+    }
+
+    /// Блокирует поток, пока не будут записаны все поставленные чанки.
+    /// Ошибка, если часть из них записать не удалось.
+    pub fn flush(&self) -> Result<()> {
+        let (done_tx, done_rx) = crossbeam_channel::bounded(1);
+        self.tx.send(SaveMsg::Flush(done_tx))?;
+        match done_rx.recv()? {
+            0 => Ok(()),
+            failed => bail!("{failed} chunks could not be saved"),
+        }
+    }
+}
+
+fn run(rx: Receiver<SaveMsg>, region: Arc<Mutex<RegionStore>>, progress: Arc<Mutex<SaveProgress>>) {
+    // Чанки, которые не удалось записать прошлой пачкой, по одному на колонку.
+    let mut failed: Vec<([i32; 2], Vec<u8>)> = Vec::new();
+    while let Ok(msg) = rx.recv() {
+        let mut batch = std::mem::take(&mut failed);
+        let mut slots: HashMap<[i32; 2], usize> = batch
+            .iter()
+            .enumerate()
+            .map(|(i, (c, _))| (*c, i))
+            .collect();
+        // Свежие данные колонки заменяют старые в пачке, в том числе неудачные;
+        // заменённые считаются записанными вместе с новыми.
+        let mut superseded = 0;
+        let mut waiters = Vec::new();
+        let mut next = Some(msg);
+        while let Some(msg) = next {
+            match msg {
+                SaveMsg::Chunk(column, data) => {
+                    if let Some(&slot) = slots.get(&column) {
+                        batch[slot].1 = data;
+                        superseded += 1;
+                    } else {
+                        slots.insert(column, batch.len());
+                        batch.push((column, data));
+                    }
+                }
+                SaveMsg::Flush(done) => waiters.push(done),
+            }
+            next = rx.try_recv().ok();
+        }
+
+        if !batch.is_empty() {
+            let result = region.lock().unwrap().write_chunks(&batch);
+            let mut progress = progress.lock().unwrap();
+            progress.written += superseded;
+            match result {
+                Ok(()) => {
+                    progress.written += batch.len();
+                    progress.failed = 0;
+                }
+                Err(err) => {
+                    log::error!("Failed to save {} chunks: {err:?}", batch.len());
+                    progress.failed = batch.len();
+                    failed = batch;
+                }
+            }
+            if progress.is_idle() && progress.failed == 0 {
+                *progress = SaveProgress::default();
+            }
+        }
+        for done in waiters {
+            let _ = done.send(failed.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_dir::TestDir;
+
+    #[test]
+    fn flush_waits_for_queued_chunks() {
+        let dir = TestDir::new("saver");
+        let region = Arc::new(Mutex::new(RegionStore::new(&dir)));
+        let saver = ChunkSaver::new(region.clone());
+        for x in 0..40 {
+            saver.queue([x, -x], vec![x as u8; 4096]);
+        }
+        saver.flush().unwrap();
+
+        let progress = saver.progress();
+        let stored = region.lock().unwrap().read_chunk([39, -39]).unwrap();
+
+        assert!(progress.is_idle());
+        assert_eq!(stored, Some(vec![39; 4096]));
+    }
+
+    #[test]
+    fn failed_batch_is_kept_and_retried() {
+        let dir = TestDir::new("saver_fail");
+        // Файл на месте каталога регионов: запись падает.
+        let blocker = dir.join(super::super::region::REGION_DIR);
+        std::fs::write(&blocker, b"").unwrap();
+        let region = Arc::new(Mutex::new(RegionStore::new(&dir)));
+        let saver = ChunkSaver::new(region.clone());
+        saver.queue([1, 2], vec![1; 64]);
+        saver.queue([3, 4], vec![3; 64]);
+        assert!(saver.flush().is_err());
+        let progress = saver.progress();
+
+        // Правка той же колонки заменяет неудачные данные, повтор проходит.
+        std::fs::remove_file(&blocker).unwrap();
+        saver.queue([1, 2], vec![2; 64]);
+        let retried = saver.flush();
+        let stored = region.lock().unwrap().read_chunk([1, 2]).unwrap();
+        let other = region.lock().unwrap().read_chunk([3, 4]).unwrap();
+
+        assert_eq!(progress.failed, 2);
+        assert!(progress.is_idle());
+        assert!(retried.is_ok());
+        assert_eq!(saver.progress(), SaveProgress::default());
+        assert_eq!(stored, Some(vec![2; 64]));
+        assert_eq!(other, Some(vec![3; 64]));
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    path::Path,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use crate::core::config::WorldConfig;
+
+use super::{chunk::Chunk, noise::NoiseGenerator, preset::WorldGenPreset, region::RegionStore};
+
+/// Откуда взялись блоки чанка.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChunkOrigin {
+    /// Прочитан из региона.
+    Loaded,
+    /// Сохранения не было, сгенерирован.
+    Generated,
+    /// Сохранение не читалось: отложено в карантин, чанк сгенерирован заново.
+    Regenerated,
+}
+
+/// CPU-часть мира без окна и GPU: регион-файлы, пресет и шум генерации.
+/// Её делят рабочие потоки [`super::generator::TerrainGen`] и утилита `wgpucraft-world`.
+#[derive(Clone)]
+pub struct ChunkSource {
+    region: Arc<Mutex<RegionStore>>,
+    noise: NoiseGenerator,
+    preset: Arc<WorldGenPreset>,
+    quarantined: Arc<AtomicUsize>,
+}
+
+impl ChunkSource {
+    /// Открывает мир в `save_dir`; старые файлы чанков переносятся в регионы.
+    pub fn open(save_dir: &Path, world: &WorldConfig, land_level: usize) -> Self {
+        let _ = std::fs::create_dir_all(save_dir);
+        let region = RegionStore::new(save_dir);
+        match region.migrate_legacy_chunks(save_dir) {
+            Ok(0) => {}
+            Ok(count) => log::info!("Migrated {count} chunk files to regions"),
+            Err(err) => log::warn!("Chunk migration stopped: {err:?}"),
+        }
+        Self {
+            region: Arc::new(Mutex::new(region)),
+            noise: NoiseGenerator::new(world.seed),
+            preset: Arc::new(WorldGenPreset::for_world(save_dir, world, land_level)),
+            quarantined: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn region(&self) -> Arc<Mutex<RegionStore>> {
+        self.region.clone()
+    }
+
+    /// Сколько повреждённых чанков отложено в карантин.
+    pub fn quarantined(&self) -> usize {
+        self.quarantined.load(Ordering::Relaxed)
+    }
+
+    /// Заполняет чанк колонки `offset` из сохранения или генерацией.
+    pub fn load_or_generate(&self, chunk: &mut Chunk, offset: [i32; 3]) -> ChunkOrigin {
+        let column = [offset[0], offset[2]];
+        let stored = self.region.lock().unwrap().read_chunk(column);
+        let origin = match stored
+            .and_then(|data| data.map(|data| chunk.load_bytes(&data, offset)).transpose())
+        {
+            Ok(Some(())) => return ChunkOrigin::Loaded,
+            Ok(None) => ChunkOrigin::Generated,
+            Err(err) => {
+                self.quarantine(column, err);
+                ChunkOrigin::Regenerated
+            }
+        };
+        self.generate(chunk, offset);
+        origin
+    }
+
+    /// Генерирует чанк, не заглядывая в сохранение.
+    pub fn generate(&self, chunk: &mut Chunk, offset: [i32; 3]) {
+        chunk.update_blocks(offset, &self.noise, &self.preset);
+    }
+
+    /// Откладывает нечитаемый чанк в карантин, чтобы на его месте сгенерировался новый.
+    fn quarantine(&self, column: [i32; 2], err: anyhow::Error) {
+        self.quarantined.fetch_add(1, Ordering::Relaxed);
+        match self.region.lock().unwrap().quarantine_chunk(column) {
+            Ok(path) => log::warn!(
+                "Chunk {column:?} is corrupt ({err:#}); moved to {} and regenerated",
+                path.display()
+            ),
+            Err(move_err) => log::warn!(
+                "Chunk {column:?} is corrupt ({err:#}) and could not be quarantined: {move_err:?}"
+            ),
+        }
+    }
+}
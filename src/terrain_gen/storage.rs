@@ -0,0 +1,410 @@
+use std::ops::{Deref, DerefMut};
+
+use anyhow::{Context, Result, bail};
+
+use crate::render::atlas::MaterialType;
+
+use super::chunk::{
+    CHUNK_AREA_WITH_PADDING, CHUNK_Y_SIZE, TOTAL_CHUNK_SIZE, WORLD_MIN_Y, material_from_u8,
+    material_to_u8,
+};
+
+/// Высота секции хранения в слоях.
+pub const SECTION_HEIGHT: usize = 16;
+pub const SECTION_COUNT: usize = CHUNK_Y_SIZE / SECTION_HEIGHT;
+/// Блоков в одной секции (вместе с паддингом по x/z).
+pub const SECTION_VOLUME: usize =
+    SECTION_HEIGHT * CHUNK_AREA_WITH_PADDING * CHUNK_AREA_WITH_PADDING;
+
+const TAG_UNIFORM: u8 = 0;
+const TAG_RAW: u8 = 1;
+
+/// Блоки чанка, разбитые на секции по 16 слоёв. Однородная секция (обычно
+/// воздух над рельефом) хранит только материал, остальные — палитру
+/// и упакованные индексы в неё.
+#[derive(Clone)]
+pub struct BlockStorage {
+    sections: Vec<Section>,
+}
+
+#[derive(Clone)]
+enum Section {
+    Uniform(MaterialType),
+    Paletted(PalettedSection),
+}
+
+#[derive(Clone)]
+struct PalettedSection {
+    palette: Vec<MaterialType>,
+    /// Бит на индекс: 1, 2, 4 или 8, чтобы индексы не пересекали границу слова.
+    bits: usize,
+    words: Vec<u64>,
+}
+
+impl BlockStorage {
+    pub fn new(fill: MaterialType) -> Self {
+        Self {
+            sections: vec![Section::Uniform(fill); SECTION_COUNT],
+        }
+    }
+
+    /// Материал по линейному индексу чанка (`y * 18 * 18 + x * 18 + z`).
+    pub fn get(&self, index: usize) -> MaterialType {
+        let (section, local) = split_index(index);
+        match &self.sections[section] {
+            Section::Uniform(material) => *material,
+            Section::Paletted(p) => p.get(local),
+        }
+    }
+
+    pub fn set(&mut self, index: usize, material: MaterialType) {
+        let (section, local) = split_index(index);
+        let slot = &mut self.sections[section];
+        match slot {
+            Section::Uniform(current) if *current == material => {}
+            Section::Uniform(current) => {
+                let mut p = PalettedSection::filled(*current);
+                p.set(local, material);
+                *slot = Section::Paletted(p);
+            }
+            Section::Paletted(p) => p.set(local, material),
+        }
+    }
+
+    /// Изменяемый доступ к блоку; запись в хранилище — при drop.
+    pub fn get_mut(&mut self, index: usize) -> BlockMut<'_> {
+        let value = self.get(index);
+        BlockMut {
+            storage: self,
+            index,
+            value,
+            original: value,
+        }
+    }
+
+    /// Однородна ли секция целиком, и каким материалом.
+    pub fn uniform_section(&self, section: usize) -> Option<MaterialType> {
+        match self.sections.get(section)? {
+            Section::Uniform(material) => Some(*material),
+            Section::Paletted(_) => None,
+        }
+    }
+
+    /// Убирает неиспользуемые записи палитр и сворачивает однородные секции.
+    /// Вызывается после массовых изменений (генерация, загрузка).
+    pub fn compact(&mut self) {
+        for slot in &mut self.sections {
+            if let Section::Paletted(p) = slot {
+                match p.compacted() {
+                    Some(compacted) => *p = compacted,
+                    None => *slot = Section::Uniform(p.palette[p.id_at(0)]),
+                }
+            }
+        }
+    }
+
+    /// Все блоки по порядку линейного индекса.
+    pub fn iter(&self) -> impl Iterator<Item = MaterialType> + '_ {
+        (0..TOTAL_CHUNK_SIZE).map(|i| self.get(i))
+    }
+
+    /// Секции подряд: однородная занимает два байта, остальные — по байту на блок.
+    pub(super) fn write_sections(&self, out: &mut Vec<u8>) {
+        for (section, slot) in self.sections.iter().enumerate() {
+            match slot {
+                Section::Uniform(material) => {
+                    out.push(TAG_UNIFORM);
+                    out.push(material_to_u8(*material));
+                }
+                Section::Paletted(_) => {
+                    out.push(TAG_RAW);
+                    let start = section * SECTION_VOLUME;
+                    out.extend(
+                        (start..start + SECTION_VOLUME).map(|i| material_to_u8(self.get(i))),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Обратное к [`BlockStorage::write_sections`] для колонки из `count` секций,
+    /// начинавшейся на высоте `min_y`. Если высота мира с тех пор изменилась,
+    /// недостающие секции снизу заполняются камнем, сверху — воздухом.
+    pub(super) fn read_sections(mut rest: &[u8], count: usize, min_y: i32) -> Result<Self> {
+        let shift = min_y - WORLD_MIN_Y;
+        if shift % SECTION_HEIGHT as i32 != 0 {
+            bail!("column bottom {min_y} is not aligned to sections");
+        }
+        let shift = shift / SECTION_HEIGHT as i32;
+        let mut storage = Self::new(MaterialType::AIR);
+        for section in 0..SECTION_COUNT as i32 {
+            if section < shift {
+                storage.sections[section as usize] = Section::Uniform(MaterialType::STONE);
+            }
+        }
+        for stored in 0..count {
+            let Some((&tag, tail)) = rest.split_first() else {
+                bail!("chunk data ends at section {stored}");
+            };
+            let len = match tag {
+                TAG_UNIFORM => 1,
+                TAG_RAW => SECTION_VOLUME,
+                other => bail!("unknown section tag {other}"),
+            };
+            if tail.len() < len {
+                bail!("section {stored} is truncated");
+            }
+            let (body, tail) = tail.split_at(len);
+            rest = tail;
+            let target = stored as i32 + shift;
+            if !(0..SECTION_COUNT as i32).contains(&target) {
+                continue;
+            }
+            let section = target as usize;
+            if tag == TAG_UNIFORM {
+                storage.sections[section] = Section::Uniform(decode_material(body[0])?);
+            } else {
+                let start = section * SECTION_VOLUME;
+                for (i, value) in body.iter().enumerate() {
+                    storage.set(start + i, decode_material(*value)?);
+                }
+            }
+        }
+        if !rest.is_empty() {
+            bail!("{} trailing bytes after sections", rest.len());
+        }
+        storage.compact();
+        Ok(storage)
+    }
+
+    /// Примерный объём памяти под блоки, в байтах.
+    pub fn memory_bytes(&self) -> usize {
+        self.sections
+            .iter()
+            .map(|s| match s {
+                Section::Uniform(_) => std::mem::size_of::<Section>(),
+                Section::Paletted(p) => {
+                    std::mem::size_of::<Section>()
+                        + p.words.capacity() * std::mem::size_of::<u64>()
+                        + p.palette.capacity() * std::mem::size_of::<MaterialType>()
+                }
+            })
+            .sum()
+    }
+}
+
+impl PalettedSection {
+    fn filled(material: MaterialType) -> Self {
+        Self {
+            palette: vec![material],
+            bits: 1,
+            words: vec![0; SECTION_VOLUME.div_ceil(64)],
+        }
+    }
+
+    fn per_word(&self) -> usize {
+        64 / self.bits
+    }
+
+    fn id_at(&self, local: usize) -> usize {
+        let per_word = self.per_word();
+        let shift = (local % per_word) * self.bits;
+        ((self.words[local / per_word] >> shift) & ((1 << self.bits) - 1)) as usize
+    }
+
+    fn set_id(&mut self, local: usize, id: usize) {
+        let per_word = self.per_word();
+        let shift = (local % per_word) * self.bits;
+        let mask = ((1u64 << self.bits) - 1) << shift;
+        let word = &mut self.words[local / per_word];
+        *word = (*word & !mask) | ((id as u64) << shift);
+    }
+
+    fn get(&self, local: usize) -> MaterialType {
+        self.palette[self.id_at(local)]
+    }
+
+    fn set(&mut self, local: usize, material: MaterialType) {
+        let id = match self.palette.iter().position(|m| *m == material) {
+            Some(id) => id,
+            None => {
+                self.palette.push(material);
+                if self.palette.len() > 1 << self.bits {
+                    self.repack(self.bits * 2);
+                }
+                self.palette.len() - 1
+            }
+        };
+        self.set_id(local, id);
+    }
+
+    fn repack(&mut self, bits: usize) {
+        let ids: Vec<usize> = (0..SECTION_VOLUME).map(|i| self.id_at(i)).collect();
+        self.bits = bits;
+        self.words = vec![0; SECTION_VOLUME.div_ceil(64 / bits)];
+        for (i, id) in ids.into_iter().enumerate() {
+            self.set_id(i, id);
+        }
+    }
+
+    /// Секция только с используемыми записями палитры; `None`, если остался
+    /// один материал и секцию можно хранить как однородную.
+    fn compacted(&self) -> Option<Self> {
+        let mut remap = vec![usize::MAX; self.palette.len()];
+        let mut palette = Vec::new();
+        for i in 0..SECTION_VOLUME {
+            let id = self.id_at(i);
+            if remap[id] == usize::MAX {
+                remap[id] = palette.len();
+                palette.push(self.palette[id]);
+            }
+        }
+        if palette.len() == 1 {
+            return None;
+        }
+        let bits = bits_for(palette.len());
+        let mut out = Self {
+            palette,
+            bits,
+            words: vec![0; SECTION_VOLUME.div_ceil(64 / bits)],
+        };
+        for i in 0..SECTION_VOLUME {
+            out.set_id(i, remap[self.id_at(i)]);
+        }
+        Some(out)
+    }
+}
+
+fn bits_for(palette_len: usize) -> usize {
+    let mut bits = 1;
+    while palette_len > 1 << bits {
+        bits *= 2;
+    }
+    bits
+}
+
+/// Неизвестный байт материала — признак порчи или файла из более новой версии.
+pub(super) fn decode_material(value: u8) -> Result<MaterialType> {
+    material_from_u8(value).with_context(|| format!("unknown material id {value}"))
+}
+
+fn split_index(index: usize) -> (usize, usize) {
+    (index / SECTION_VOLUME, index % SECTION_VOLUME)
+}
+
+/// Изменяемая ссылка на блок в упакованном хранилище. Ведёт себя как
+/// `&mut MaterialType`: новое значение записывается при выходе из области видимости.
+pub struct BlockMut<'a> {
+    storage: &'a mut BlockStorage,
+    index: usize,
+    value: MaterialType,
+    original: MaterialType,
+}
+
+impl Deref for BlockMut<'_> {
+    type Target = MaterialType;
+
+    fn deref(&self) -> &MaterialType {
+        &self.value
+    }
+}
+
+impl DerefMut for BlockMut<'_> {
+    fn deref_mut(&mut self) -> &mut MaterialType {
+        &mut self.value
+    }
+}
+
+impl Drop for BlockMut<'_> {
+    fn drop(&mut self) {
+        if self.value != self.original {
+            self.storage.set(self.index, self.value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_plain_array_after_random_writes() {
+        let mut storage = BlockStorage::new(MaterialType::AIR);
+        let mut plain = vec![MaterialType::AIR; TOTAL_CHUNK_SIZE];
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        for _ in 0..50_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            // Пишем в нижние секции, чтобы палитры успели вырасти.
+            let index = (state as usize >> 8) % (SECTION_VOLUME * 3);
+            let material = MaterialType::ALL[(state >> 40) as usize % MaterialType::ALL.len()];
+            storage.set(index, material);
+            plain[index] = material;
+        }
+        storage.compact();
+        assert!(storage.iter().eq(plain.iter().copied()));
+    }
+
+    #[test]
+    fn uniform_sections_take_no_block_data() {
+        let mut storage = BlockStorage::new(MaterialType::AIR);
+        let full = storage.memory_bytes();
+        for i in 0..SECTION_VOLUME {
+            storage.set(i, MaterialType::STONE);
+        }
+        storage.set(SECTION_VOLUME, MaterialType::DIRT);
+        storage.set(SECTION_VOLUME, MaterialType::AIR);
+        storage.compact();
+        assert_eq!(storage.uniform_section(0), Some(MaterialType::STONE));
+        assert_eq!(storage.uniform_section(1), Some(MaterialType::AIR));
+        assert_eq!(storage.memory_bytes(), full);
+        assert!(full < TOTAL_CHUNK_SIZE / 50);
+    }
+
+    #[test]
+    fn section_bytes_round_trip() {
+        let mut storage = BlockStorage::new(MaterialType::STONE);
+        for i in SECTION_VOLUME * 4..SECTION_VOLUME * 5 {
+            storage.set(i, MaterialType::ALL[i % MaterialType::ALL.len()]);
+        }
+        storage.compact();
+        let mut bytes = Vec::new();
+        storage.write_sections(&mut bytes);
+        assert!(bytes.len() < SECTION_VOLUME * 2);
+        let back = BlockStorage::read_sections(&bytes, SECTION_COUNT, WORLD_MIN_Y).unwrap();
+        assert!(back.iter().eq(storage.iter()));
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(BlockStorage::read_sections(truncated, SECTION_COUNT, WORLD_MIN_Y).is_err());
+        // Неизвестный материал не превращается молча в воздух.
+        bytes[1] = 200;
+        assert!(BlockStorage::read_sections(&bytes, SECTION_COUNT, WORLD_MIN_Y).is_err());
+    }
+
+    #[test]
+    fn sections_shift_when_world_height_changes() {
+        let mut storage = BlockStorage::new(MaterialType::AIR);
+        storage.set(0, MaterialType::GRASS);
+        let mut bytes = Vec::new();
+        storage.write_sections(&mut bytes);
+        // Колонка, записанная при дне мира на 32 блока выше нынешнего.
+        let raised = WORLD_MIN_Y + 2 * SECTION_HEIGHT as i32;
+        let back = BlockStorage::read_sections(&bytes, SECTION_COUNT, raised).unwrap();
+        assert_eq!(back.uniform_section(0), Some(MaterialType::STONE));
+        assert_eq!(back.uniform_section(1), Some(MaterialType::STONE));
+        assert_eq!(back.get(2 * SECTION_VOLUME), MaterialType::GRASS);
+        assert!(BlockStorage::read_sections(&bytes, SECTION_COUNT, WORLD_MIN_Y + 1).is_err());
+    }
+
+    #[test]
+    fn block_mut_writes_back_on_drop() {
+        let mut storage = BlockStorage::new(MaterialType::AIR);
+        {
+            let mut block = storage.get_mut(42);
+            assert_eq!(*block, MaterialType::AIR);
+            *block = MaterialType::GRASS;
+        }
+        assert_eq!(storage.get(42), MaterialType::GRASS);
+        assert_eq!(storage.get(43), MaterialType::AIR);
+    }
+}
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    biomes::{ColumnSample, sample_column},
+    features::FeatureRng,
+    noise::NoiseGenerator,
+    preset::WorldGenPreset,
+};
+
+/// Соль сида для ячеек озёр, чтобы они не совпадали с ячейками структур.
+const LAKE_SEED_SALT: u32 = 0x1A4E_5EED;
+
+/// Параметры рек и озёр.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaterSettings {
+    /// Частота шума русел; `0.0` отключает реки.
+    pub river_frequency: f32,
+    /// Полуширина русла в единицах шума (река идёт вдоль нуля шума).
+    pub river_width: f32,
+    /// Ширина склона берега за руслом, в тех же единицах.
+    pub river_bank: f32,
+    /// Глубина дна реки под уровнем моря.
+    pub river_depth: f32,
+    /// Размер ячейки, в которой может лежать одно озеро.
+    pub lake_cell_size: i32,
+    /// Вероятность озера в ячейке; `0.0` отключает озёра.
+    pub lake_chance: f32,
+    pub lake_min_radius: f32,
+    pub lake_max_radius: f32,
+    /// Глубина чаши озера в центре.
+    pub lake_depth: f32,
+    /// Ширина насыпного берега вокруг озера (не меньше 2, иначе вода
+    /// может зависнуть на диагонали).
+    pub lake_bank: f32,
+}
+
+pub const DEFAULT_WATER: WaterSettings = WaterSettings {
+    river_frequency: 0.003,
+    river_width: 0.02,
+    river_bank: 0.04,
+    river_depth: 3.0,
+    lake_cell_size: 128,
+    lake_chance: 0.35,
+    lake_min_radius: 8.0,
+    lake_max_radius: 20.0,
+    lake_depth: 5.0,
+    lake_bank: 3.0,
+};
+
+impl Default for WaterSettings {
+    fn default() -> Self {
+        DEFAULT_WATER
+    }
+}
+
+/// Озеро со своим уровнем воды, не зависящим от уровня моря.
+#[derive(Copy, Clone, Debug)]
+pub struct Lake {
+    pub center: [f32; 2],
+    pub radius: f32,
+    pub level: usize,
+}
+
+/// Реки и озёра вокруг участка мира. Озеро целиком лежит в своей ячейке,
+/// поэтому соседние чанки находят одни и те же озёра.
+pub struct WaterMap {
+    lakes: Vec<Lake>,
+}
+
+impl WaterMap {
+    /// Озёра, которые могут задеть колонки в прямоугольнике `[min, max]` (мировые x, z).
+    pub fn around(
+        preset: &WorldGenPreset,
+        noise: &NoiseGenerator,
+        min: [i32; 2],
+        max: [i32; 2],
+    ) -> Self {
+        let cell = preset.water.lake_cell_size;
+        let mut lakes = Vec::new();
+        if preset.water.lake_chance > 0.0 && cell > 0 {
+            for cell_x in min[0].div_euclid(cell)..=max[0].div_euclid(cell) {
+                for cell_z in min[1].div_euclid(cell)..=max[1].div_euclid(cell) {
+                    lakes.extend(lake_in_cell(preset, noise, [cell_x, cell_z]));
+                }
+            }
+        }
+        Self { lakes }
+    }
+
+    pub fn lakes(&self) -> &[Lake] {
+        &self.lakes
+    }
+
+    /// Колонка с учётом русел и озёр: высота дна и локальный уровень воды.
+    pub fn sample(
+        &self,
+        preset: &WorldGenPreset,
+        noise: &NoiseGenerator,
+        x: f32,
+        z: f32,
+    ) -> ColumnSample {
+        let water = &preset.water;
+        let mut sample = sample_column(preset, noise, x, z);
+
+        if water.river_frequency > 0.0 && water.river_width > 0.0 {
+            let r = noise.get_river(x, z, water.river_frequency).abs();
+            let edge = water.river_width + water.river_bank;
+            if r < edge {
+                let t = if r <= water.river_width {
+                    1.0
+                } else {
+                    (edge - r) / water.river_bank
+                };
+                let bed = preset.sea_level() as f32 - water.river_depth;
+                let carved = sample.height + (bed - sample.height) * smoothstep(t);
+                sample.height = sample.height.min(carved);
+            }
+        }
+
+        // Озёра идут после рек: насыпной берег перекрывает русло,
+        // чтобы вода озера не вытекала в реку висящей стеной.
+        for lake in &self.lakes {
+            let d = ((x - lake.center[0]).powi(2) + (z - lake.center[1]).powi(2)).sqrt();
+            let level = lake.level as f32;
+            if d < lake.radius {
+                let k = d / lake.radius;
+                let bed = level - water.lake_depth * (1.0 - k * k);
+                sample.height = sample.height.min(bed);
+                sample.water_level = lake.level;
+            } else if d < lake.radius + water.lake_bank {
+                sample.height = sample.height.max(level);
+            }
+        }
+        sample
+    }
+}
+
+/// Озеро в ячейке, если оно там есть. Уровень воды — по самой низкой точке
+/// рельефа на берегу, поэтому котловина почти не требует насыпи.
+fn lake_in_cell(preset: &WorldGenPreset, noise: &NoiseGenerator, cell: [i32; 2]) -> Option<Lake> {
+    let water = &preset.water;
+    let mut rng = FeatureRng::for_cell(noise.seed() ^ LAKE_SEED_SALT, cell[0], cell[1]);
+    if rng.next_f32() >= water.lake_chance {
+        return None;
+    }
+    let size = water.lake_cell_size as f32;
+    let radius =
+        water.lake_min_radius + (water.lake_max_radius - water.lake_min_radius) * rng.next_f32();
+    let margin = radius + water.lake_bank;
+    let span = size - 2.0 * margin;
+    if span <= 0.0 {
+        return None;
+    }
+    let center = [
+        cell[0] as f32 * size + margin + span * rng.next_f32(),
+        cell[1] as f32 * size + margin + span * rng.next_f32(),
+    ];
+
+    let rim = [
+        (0.0, 0.0),
+        (radius, 0.0),
+        (-radius, 0.0),
+        (0.0, radius),
+        (0.0, -radius),
+    ];
+    let lowest = rim
+        .iter()
+        .map(|(dx, dz)| sample_column(preset, noise, center[0] + dx, center[1] + dz).height)
+        .fold(f32::MAX, f32::min);
+    let level = lowest.round() as i32;
+    if level <= preset.sea_level() as i32 {
+        // Такое озеро ничем не отличается от моря.
+        return None;
+    }
+    Some(Lake {
+        center,
+        radius,
+        level: level as usize,
+    })
+}
+
+fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(
+        map: &WaterMap,
+        preset: &WorldGenPreset,
+        noise: &NoiseGenerator,
+        x: i32,
+        z: i32,
+    ) -> (i32, usize) {
+        let s = map.sample(preset, noise, x as f32, z as f32);
+        (s.height.round().max(0.0) as i32, s.water_level)
+    }
+
+    #[test]
+    fn lake_water_never_hangs_over_neighbours() {
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(10);
+        let map = WaterMap::around(&preset, &noise, [-2048, -2048], [2048, 2048]);
+        assert!(!map.lakes().is_empty(), "no lakes found");
+
+        for lake in map.lakes().iter().take(4) {
+            let reach = (lake.radius + preset.water.lake_bank) as i32 + 2;
+            let (cx, cz) = (lake.center[0] as i32, lake.center[1] as i32);
+            for x in cx - reach..=cx + reach {
+                for z in cz - reach..=cz + reach {
+                    let (height, level) = column(&map, &preset, &noise, x, z);
+                    if height >= level as i32 {
+                        continue;
+                    }
+                    // Вода в колонке: каждый сосед — либо та же вода, либо берег не ниже уровня.
+                    for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                        let (nh, nl) = column(&map, &preset, &noise, x + dx, z + dz);
+                        assert!(
+                            nl == level || nh >= level as i32,
+                            "water at ({x}, {z}) level {level} spills into ({}, {})",
+                            x + dx,
+                            z + dz
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rivers_cut_below_sea_level() {
+        let preset = WorldGenPreset::default();
+        let noise = NoiseGenerator::new(10);
+        let map = WaterMap { lakes: Vec::new() };
+        let sea = preset.sea_level() as f32;
+        let mut channels = 0;
+        for i in -3000..3000 {
+            let x = i as f32;
+            let r = noise.get_river(x, 0.0, preset.water.river_frequency).abs();
+            if r < preset.water.river_width {
+                channels += 1;
+                let sample = map.sample(&preset, &noise, x, 0.0);
+                assert!(sample.height < sea, "river bed above water at x={x}");
+            }
+        }
+        assert!(channels > 0, "no river crossed the sampled line");
+    }
+}
@@ -16,6 +16,12 @@ pub struct GlyphCache {
     entries: HashMap<GlyphKey, CachedGlyph>,
 }
 
+impl Default for GlyphCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GlyphCache {
     pub fn new() -> Self {
         Self {
@@ -28,6 +28,7 @@ impl FontManager {
         let path_ref = path.as_ref();
         let face = self.library.new_face(path_ref, 0)?;
         let handle = FontHandle(self.fonts.len());
+        #[allow(clippy::arc_with_non_send_sync)]
         let font = Arc::new(Font { id: handle, face });
         self.fonts.push(font);
         Ok(handle)
@@ -70,10 +71,8 @@ impl Font {
     pub fn load_glyph_bitmap(&self, glyph_id: u32, pixel_size: u32) -> Result<Option<GlyphBitmap>> {
         self.face.set_pixel_sizes(0, pixel_size)?;
         let glyph_index = glyph_id;
-        self.face.load_glyph(
-            glyph_index as u32,
-            LoadFlag::RENDER | LoadFlag::TARGET_NORMAL,
-        )?;
+        self.face
+            .load_glyph(glyph_index, LoadFlag::RENDER | LoadFlag::TARGET_NORMAL)?;
         let glyph_slot = self.face.glyph();
         let bitmap = glyph_slot.bitmap();
         let width = bitmap.width() as u32;
@@ -220,6 +220,7 @@ impl TextSystem {
         Ok((width, max_h))
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_vertices(
         &mut self,
         text: &str,
@@ -23,6 +23,7 @@ mod tests {
 
     #[test]
     fn label_pref_size_scales_with_font() {
+        #[allow(clippy::arc_with_non_send_sync)]
         let font = Arc::new(
             BitmapFont::load_from_path("assets/fonts/OpenLukyanov.ttf", 16.0, 400)
                 .expect("font available for tests"),
@@ -41,6 +42,22 @@ mod tests {
         assert!(sz[0] > 0.0);
         assert!(sz[1] >= 16.0);
     }
+
+    #[test]
+    fn text_input_respects_max_len() {
+        let mut input = TextInputSpec {
+            text: String::new(),
+            placeholder: String::new(),
+            max_len: 4,
+            padding: 8.0,
+            min_height: 48.0,
+        };
+        assert!(input.insert("ab\ncdef"));
+        assert_eq!(input.text, "abcd");
+        assert!(!input.insert("x"));
+        assert!(input.backspace());
+        assert_eq!(input.text, "abc");
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -64,10 +81,50 @@ impl ButtonSpec {
     }
 }
 
+/// Однострочное поле ввода. Текст правит HUD, пока поле в фокусе.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TextInputSpec {
+    #[serde(default)]
+    pub text: String,
+    /// Подпись, которая видна, пока поле пустое.
+    #[serde(default)]
+    pub placeholder: String,
+    #[serde(default = "TextInputSpec::default_max_len")]
+    pub max_len: usize,
+    #[serde(default = "ButtonSpec::default_padding")]
+    pub padding: f32,
+    #[serde(default = "ButtonSpec::default_height")]
+    pub min_height: f32,
+}
+
+impl TextInputSpec {
+    fn default_max_len() -> usize {
+        32
+    }
+
+    /// Дописывает печатные символы, пока текст не длиннее `max_len`.
+    pub fn insert(&mut self, input: &str) -> bool {
+        let mut changed = false;
+        for c in input.chars().filter(|c| !c.is_control()) {
+            if self.text.chars().count() >= self.max_len {
+                break;
+            }
+            self.text.push(c);
+            changed = true;
+        }
+        changed
+    }
+
+    pub fn backspace(&mut self) -> bool {
+        self.text.pop().is_some()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum UiElement {
     Label(LabelSpec),
     Button(ButtonSpec),
+    TextInput(TextInputSpec),
     Panel {
         #[serde(default = "UiElement::default_panel_color")]
         color: [u8; 4],
@@ -124,6 +181,14 @@ impl UiElement {
                     ]
                 }
             }
+            UiElement::TextInput(input) => {
+                let scale = ctx.text_scale;
+                let text_h = ctx.font.as_ref().map_or(16.0, |font| font.height() as f32);
+                [
+                    input.max_len as f32 * 8.0 * scale + input.padding * 2.0,
+                    (text_h * scale + input.padding * 2.0).max(input.min_height * scale),
+                ]
+            }
             UiElement::Panel { .. } => [0.0, 0.0],
             UiElement::Image { .. } => [0.0, 0.0],
             UiElement::Spacer { size } => [0.0, *size],
@@ -19,9 +19,9 @@ mod tests {
     use super::*;
     use std::collections::HashSet;
 
-    fn walk_ids(node: &UiNode, acc: &mut HashSet<String>) {
+    fn walk_ids(node: &UiNode, acc: &mut Vec<String>) {
         if let Some(id) = &node.id {
-            acc.insert(id.clone());
+            acc.push(id.clone());
         }
         for child in &node.children {
             walk_ids(child, acc);
@@ -31,15 +31,25 @@ mod tests {
     #[test]
     fn ron_menu_files_parse() {
         let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets/ui");
-        let files = ["menu_main.ron", "menu_settings.ron", "menu_advanced.ron"];
+        let files = [
+            "menu_main.ron",
+            "menu_create_world.ron",
+            "menu_settings.ron",
+            "menu_advanced.ron",
+            "menu_worlds.ron",
+            "menu_rename_world.ron",
+            "menu_delete_world.ron",
+            "menu_backups.ron",
+        ];
         for f in files {
             let path = root.join(f);
             let ui = load_ron(&path).expect("RON should parse");
             assert!(ui.id.is_some(), "root id must be set for {}", f);
             // ensure ids are unique within the tree
-            let mut ids = HashSet::new();
+            let mut ids = Vec::new();
             walk_ids(&ui, &mut ids);
-            assert_eq!(ids.len(), ids.iter().count(), "duplicate ids in {}", f);
+            let unique: HashSet<&String> = ids.iter().collect();
+            assert_eq!(unique.len(), ids.len(), "duplicate ids in {}", f);
         }
     }
 
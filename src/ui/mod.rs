@@ -5,7 +5,7 @@ pub mod loader;
 pub mod renderer;
 pub mod style;
 
-pub use elements::{ButtonSpec, LabelSpec, UiElement};
+pub use elements::{ButtonSpec, LabelSpec, TextInputSpec, UiElement};
 pub use font::BitmapFont;
 pub use layout::{Align, Anchors, Layout, MeasureCtx, RectSpec, ResolvedNode, UiNode, Val};
 pub use loader::{load_json, load_ron};
@@ -5,6 +5,12 @@ pub struct MeshBuilder {
     indices: Vec<u32>,
 }
 
+impl Default for MeshBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MeshBuilder {
     pub fn new() -> Self {
         Self {
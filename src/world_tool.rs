@@ -0,0 +1,513 @@
+//! `wgpucraft-world`: работа с сохранениями без окна и GPU.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+use rayon::prelude::*;
+
+use crate::{
+    core::{
+        config::AppConfig,
+        level::{LevelData, world_dir},
+        saves::format_size,
+    },
+    render::atlas::MaterialType,
+    terrain_gen::{
+        chunk::{CHUNK_AREA, CHUNK_AREA_WITH_PADDING, CHUNK_Y_SIZE, Chunk, WORLD_MIN_Y},
+        map::{MapArea, MapColoring, biome_color, render_map},
+        noise::NoiseGenerator,
+        preset::WorldGenPreset,
+        region::{REGION_DIR, RegionStore},
+        source::{ChunkOrigin, ChunkSource},
+        storage::SECTION_COUNT,
+    },
+};
+
+/// Сколько колонок генерируется между записями в регионы.
+const PREGEN_BATCH: usize = 256;
+
+fn cli() -> Command {
+    let world = || {
+        Arg::new("world")
+            .required(true)
+            .help("World folder name inside saves/")
+    };
+    let coord = |name: &'static str| {
+        Arg::new(name)
+            .required(true)
+            .allow_negative_numbers(true)
+            .value_parser(value_parser!(i32))
+    };
+    Command::new("wgpucraft-world")
+        .about("Generate and inspect wgpucraft worlds without a window")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .default_value("config.json")
+                .help("Game config used for new worlds and terrain settings"),
+        )
+        .subcommand(
+            Command::new("pregen")
+                .about("Generate every missing chunk within a radius and save it")
+                .arg(world())
+                .arg(
+                    Arg::new("radius")
+                        .long("radius")
+                        .required(true)
+                        .value_parser(value_parser!(u32))
+                        .help("Radius in chunks"),
+                )
+                .arg(
+                    Arg::new("center")
+                        .long("center")
+                        .num_args(2)
+                        .value_names(["X", "Z"])
+                        .allow_negative_numbers(true)
+                        .value_parser(value_parser!(i32))
+                        .help("Center in block coordinates (default 0 0)"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_parser(value_parser!(u32))
+                        .help("Seed for a world that does not exist yet"),
+                ),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Print chunk, size and material statistics")
+                .arg(world()),
+        )
+        .subcommand(
+            Command::new("verify")
+                .about("Check level.ron and every saved chunk")
+                .arg(world())
+                .arg(
+                    Arg::new("quarantine")
+                        .long("quarantine")
+                        .action(ArgAction::SetTrue)
+                        .help("Move unreadable chunks to quarantine/ so they regenerate"),
+                ),
+        )
+        .subcommand(
+            Command::new("map")
+                .about("Render a top-down PNG of a seed or an existing world")
+                .arg(
+                    Arg::new("world")
+                        .long("world")
+                        .conflicts_with("seed")
+                        .help("Take seed, preset and world type from this world"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_parser(value_parser!(u32))
+                        .help("Seed to render (default: seed from the config)"),
+                )
+                .arg(
+                    Arg::new("preset")
+                        .long("preset")
+                        .conflicts_with("world")
+                        .help("Worldgen preset from assets/worldgen (default: from the config)"),
+                )
+                .arg(
+                    Arg::new("center")
+                        .long("center")
+                        .num_args(2)
+                        .value_names(["X", "Z"])
+                        .allow_negative_numbers(true)
+                        .value_parser(value_parser!(i32))
+                        .help("Center in block coordinates (default 0 0)"),
+                )
+                .arg(
+                    Arg::new("size")
+                        .long("size")
+                        .default_value("512")
+                        .value_parser(value_parser!(u32).range(1..=8192))
+                        .help("Width and height in blocks, one pixel per block"),
+                )
+                .arg(
+                    Arg::new("color")
+                        .long("color")
+                        .default_value("surface")
+                        .value_parser(["surface", "biome"])
+                        .help("Colour by top block or by dominant biome"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .default_value("map.png")
+                        .help("Output PNG"),
+                )
+                .arg(Arg::new("heightmap").long("heightmap").help(
+                    "Also write a grayscale heightmap PNG (one level per block from y = -64)",
+                )),
+        )
+        .subcommand(
+            Command::new("dump")
+                .about("Print the block column at a world position")
+                .arg(world())
+                .arg(coord("x"))
+                .arg(coord("z")),
+        )
+}
+
+/// Точка входа утилиты; код выхода 1 — ошибка или найденные повреждения.
+pub fn run() -> ExitCode {
+    env_logger::init();
+    let matches = cli().get_matches();
+    let result =
+        load_config(matches.get_one::<String>("config").unwrap()).and_then(|config| match matches
+            .subcommand()
+        {
+            Some(("pregen", args)) => pregen(config, args).map(|()| true),
+            Some(("stats", args)) => stats(config, args).map(|()| true),
+            Some(("verify", args)) => verify(config, args),
+            Some(("map", args)) => map(config, args).map(|()| true),
+            Some(("dump", args)) => dump(config, args).map(|()| true),
+            _ => unreachable!("clap requires a subcommand"),
+        });
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// В отличие от игры, не создаёт `config.json`, если его нет.
+fn load_config(path: &str) -> Result<AppConfig> {
+    if Path::new(path).exists() {
+        AppConfig::load_or_default(path)
+    } else {
+        Ok(AppConfig::default())
+    }
+}
+
+/// Конфиг с параметрами существующего мира; `level.ron` не перезаписывается.
+fn existing_world(mut config: AppConfig, args: &ArgMatches) -> Result<(AppConfig, RegionStore)> {
+    let name = args.get_one::<String>("world").unwrap();
+    let dir = world_dir(name);
+    if !dir.is_dir() {
+        bail!("world {name:?} not found in {}", dir.display());
+    }
+    config.world.world_name = name.clone();
+    match LevelData::load(&dir) {
+        Ok(level) => level.apply_to(&mut config),
+        Err(err) => log::warn!("Using config world settings: {err:#}"),
+    }
+    let region = RegionStore::new(&dir);
+    Ok((config, region))
+}
+
+fn pregen(mut config: AppConfig, args: &ArgMatches) -> Result<()> {
+    let name = args.get_one::<String>("world").unwrap();
+    config.world.world_name = name.clone();
+    if let Some(seed) = args.get_one::<u32>("seed") {
+        if world_dir(name).exists() {
+            bail!("--seed only applies to new worlds; {name:?} already exists");
+        }
+        config.world.seed = *seed;
+    }
+    // Создаёт level.ron для нового мира или подхватывает настройки старого.
+    LevelData::open(&mut config);
+    let dir = world_dir(name);
+    let source = ChunkSource::open(&dir, &config.world, config.terrain.land_level);
+
+    let radius = *args.get_one::<u32>("radius").unwrap() as i32;
+    let center: Vec<i32> = args
+        .get_many::<i32>("center")
+        .map(|c| c.copied().collect())
+        .unwrap_or_else(|| vec![0, 0]);
+    let center = [
+        center[0].div_euclid(CHUNK_AREA as i32),
+        center[1].div_euclid(CHUNK_AREA as i32),
+    ];
+    let columns: Vec<[i32; 3]> = (-radius..=radius)
+        .flat_map(|dx| (-radius..=radius).map(move |dz| (dx, dz)))
+        .filter(|(dx, dz)| dx * dx + dz * dz <= radius * radius)
+        .map(|(dx, dz)| [center[0] + dx, 0, center[1] + dz])
+        .collect();
+
+    let region = source.region();
+    let mut generated = 0;
+    for (done, batch) in columns.chunks(PREGEN_BATCH).enumerate() {
+        let fresh: Vec<([i32; 2], Vec<u8>)> = batch
+            .par_iter()
+            .filter_map(|&offset| {
+                let mut chunk = Chunk::new(offset);
+                match source.load_or_generate(&mut chunk, offset) {
+                    ChunkOrigin::Loaded => None,
+                    _ => Some(([offset[0], offset[2]], chunk.block_bytes())),
+                }
+            })
+            .collect();
+        generated += fresh.len();
+        region.lock().unwrap().write_chunks(&fresh)?;
+        let processed = (done * PREGEN_BATCH + batch.len()).min(columns.len());
+        println!(
+            "{processed}/{} columns, {generated} generated",
+            columns.len()
+        );
+    }
+    println!(
+        "Done: {generated} new chunks, {} already saved, {} quarantined",
+        columns.len() - generated,
+        source.quarantined()
+    );
+    Ok(())
+}
+
+fn stats(config: AppConfig, args: &ArgMatches) -> Result<()> {
+    let (config, region) = existing_world(config, args)?;
+    let dir = world_dir(&config.world.world_name);
+    let mut materials: HashMap<String, u64> = HashMap::new();
+    let (mut chunks, mut uniform_sections, mut unreadable) = (0usize, 0usize, 0usize);
+    let regions = region.regions();
+    for &r in &regions {
+        let Ok(columns) = region.region_columns(r) else {
+            unreadable += 1;
+            continue;
+        };
+        for column in columns {
+            let Some(chunk) = read_saved(&region, column).ok().flatten() else {
+                unreadable += 1;
+                continue;
+            };
+            chunks += 1;
+            uniform_sections += (0..SECTION_COUNT)
+                .filter(|&s| chunk.blocks.uniform_section(s).is_some())
+                .count();
+            for_each_interior_block(&chunk, |material| {
+                *materials.entry(format!("{material:?}")).or_default() += 1;
+            });
+        }
+    }
+    let disk = dir_bytes(&dir.join(REGION_DIR));
+    println!(
+        "World {:?} (seed {})",
+        config.world.world_name, config.world.seed
+    );
+    println!("Regions: {}", regions.len());
+    println!("Chunks: {chunks} ({unreadable} unreadable)");
+    println!(
+        "Region files: {} ({} per chunk)",
+        format_size(disk),
+        format_size(disk / chunks.max(1) as u64)
+    );
+    println!(
+        "Uniform sections: {:.1}%",
+        100.0 * uniform_sections as f64 / (chunks * SECTION_COUNT).max(1) as f64
+    );
+    let total: u64 = materials.values().sum();
+    let mut materials: Vec<(String, u64)> = materials.into_iter().collect();
+    materials.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    for (material, count) in materials {
+        println!(
+            "  {material:<8} {count:>12} {:>6.2}%",
+            100.0 * count as f64 / total.max(1) as f64
+        );
+    }
+    Ok(())
+}
+
+fn verify(config: AppConfig, args: &ArgMatches) -> Result<bool> {
+    let (config, region) = existing_world(config, args)?;
+    let dir = world_dir(&config.world.world_name);
+    let quarantine = args.get_flag("quarantine");
+    let mut problems = 0;
+    if let Err(err) = LevelData::load(&dir) {
+        problems += 1;
+        println!("level.ron: {err:#}");
+    }
+    let mut checked = 0;
+    for r in region.regions() {
+        let columns = match region.region_columns(r) {
+            Ok(columns) => columns,
+            Err(err) => {
+                problems += 1;
+                println!("region {r:?}: {err:#}");
+                continue;
+            }
+        };
+        for column in columns {
+            checked += 1;
+            let Err(err) = read_saved(&region, column) else {
+                continue;
+            };
+            problems += 1;
+            println!("chunk {column:?}: {err:#}");
+            if quarantine {
+                let moved = region.quarantine_chunk(column)?;
+                println!("  moved to {}", moved.display());
+            }
+        }
+    }
+    println!("Checked {checked} chunks, {problems} problems");
+    Ok(problems == 0)
+}
+
+fn map(mut config: AppConfig, args: &ArgMatches) -> Result<()> {
+    let dir = match args.get_one::<String>("world") {
+        Some(name) => {
+            let (world_config, _) = existing_world(config, args)?;
+            config = world_config;
+            world_dir(name)
+        }
+        None => {
+            if let Some(seed) = args.get_one::<u32>("seed") {
+                config.world.seed = *seed;
+            }
+            if let Some(preset) = args.get_one::<String>("preset") {
+                config.world.preset = preset.clone();
+            }
+            // Без мира пресет берётся из assets/worldgen.
+            PathBuf::new()
+        }
+    };
+    let preset = WorldGenPreset::peek_world(&dir, &config.world, config.terrain.land_level);
+    let noise = NoiseGenerator::new(config.world.seed);
+
+    let size = *args.get_one::<u32>("size").unwrap();
+    let center: Vec<i32> = args
+        .get_many::<i32>("center")
+        .map(|c| c.copied().collect())
+        .unwrap_or_else(|| vec![0, 0]);
+    let half = (size / 2) as i32;
+    let area = MapArea {
+        min: [center[0] - half, center[1] - half],
+        size: [size, size],
+    };
+    let coloring = match args.get_one::<String>("color").map(String::as_str) {
+        Some("biome") => MapColoring::Biome,
+        _ => MapColoring::Surface,
+    };
+    let map = render_map(&noise, &preset, area, coloring);
+
+    let out = args.get_one::<String>("out").unwrap();
+    map.colors
+        .save(out)
+        .with_context(|| format!("failed to write {out}"))?;
+    println!("Seed {}: wrote {out}", config.world.seed);
+    if let Some(path) = args.get_one::<String>("heightmap") {
+        map.heights
+            .save(path)
+            .with_context(|| format!("failed to write {path}"))?;
+        println!("Wrote heightmap {path}");
+    }
+    if coloring == MapColoring::Biome {
+        for (index, biome) in preset.biomes.iter().enumerate() {
+            let [r, g, b] = biome_color(index).0;
+            println!("  #{r:02x}{g:02x}{b:02x}  {}", biome.name);
+        }
+    }
+    Ok(())
+}
+
+fn dump(config: AppConfig, args: &ArgMatches) -> Result<()> {
+    let (config, region) = existing_world(config, args)?;
+    let x = *args.get_one::<i32>("x").unwrap();
+    let z = *args.get_one::<i32>("z").unwrap();
+    let offset = [
+        x.div_euclid(CHUNK_AREA as i32),
+        0,
+        z.div_euclid(CHUNK_AREA as i32),
+    ];
+    let chunk = match read_saved(&region, [offset[0], offset[2]])? {
+        Some(chunk) => {
+            println!("Column {x} {z} from chunk {offset:?} (saved)");
+            chunk
+        }
+        None => {
+            // Колонка ещё не сохранялась: показываем то, что сгенерирует игра.
+            println!("Column {x} {z} from chunk {offset:?} (not saved, generated)");
+            let dir = world_dir(&config.world.world_name);
+            let mut chunk = Chunk::new(offset);
+            ChunkSource::open(&dir, &config.world, config.terrain.land_level)
+                .generate(&mut chunk, offset);
+            chunk
+        }
+    };
+    let local_x = x.rem_euclid(CHUNK_AREA as i32) as usize + 1;
+    let local_z = z.rem_euclid(CHUNK_AREA as i32) as usize + 1;
+    let column: Vec<MaterialType> = (0..CHUNK_Y_SIZE)
+        .map(|y| chunk.get_block(y, local_x, local_z).unwrap())
+        .collect();
+    // Сверху вниз, одинаковые блоки подряд сливаются в диапазон.
+    let mut top = CHUNK_Y_SIZE;
+    while top > 0 {
+        let material = column[top - 1];
+        let mut bottom = top - 1;
+        while bottom > 0 && column[bottom - 1] == material {
+            bottom -= 1;
+        }
+        let (from, to) = (bottom as i32 + WORLD_MIN_Y, top as i32 - 1 + WORLD_MIN_Y);
+        if from == to {
+            println!("{from:>12}  {material:?}");
+        } else {
+            println!("{:>12}  {material:?}", format!("{from}..={to}"));
+        }
+        top = bottom;
+    }
+    Ok(())
+}
+
+/// Сохранённый чанк без генерации и карантина; `None`, если колонки нет.
+fn read_saved(region: &RegionStore, column: [i32; 2]) -> Result<Option<Chunk>> {
+    let Some(data) = region.read_chunk(column)? else {
+        return Ok(None);
+    };
+    let offset = [column[0], 0, column[1]];
+    let mut chunk = Chunk::new(offset);
+    chunk
+        .load_bytes(&data, offset)
+        .context("chunk data does not decode")?;
+    Ok(Some(chunk))
+}
+
+/// Блоки колонки без паддинга соседей.
+fn for_each_interior_block(chunk: &Chunk, mut f: impl FnMut(MaterialType)) {
+    for y in 0..CHUNK_Y_SIZE {
+        for x in 1..CHUNK_AREA_WITH_PADDING - 1 {
+            for z in 1..CHUNK_AREA_WITH_PADDING - 1 {
+                f(chunk.get_block(y, x, z).unwrap());
+            }
+        }
+    }
+}
+
+fn dir_bytes(path: &Path) -> u64 {
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.metadata().ok())
+                .map(|meta| meta.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_parses_negative_coordinates() {
+        cli().debug_assert();
+        let matches = cli()
+            .try_get_matches_from(["wgpucraft-world", "dump", "w", "-5", "17"])
+            .unwrap();
+        let (_, args) = matches.subcommand().unwrap();
+        assert_eq!(args.get_one::<i32>("x"), Some(&-5));
+    }
+}